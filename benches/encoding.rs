@@ -0,0 +1,157 @@
+//! Per-op timings for encoding and signing each transaction type.
+//!
+//! Run with `cargo bench`. This is a plain `harness = false` binary: every
+//! case runs for about a second and reports the mean time and number of heap
+//! allocations per call.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    hint::black_box,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use alloy::primitives::{keccak256, Address, U256};
+use example_7702::{
+    signing::sign_hash,
+    tx::{Authorization7702, Eip1559Transaction, Eip7702Transaction, LegacyTransaction},
+};
+use secp256k1::SecretKey;
+
+const DEPLOYER_KEY: &str = "0x0fad2ca996a24d116097c481c27a59652a3d3611dfed64d8f9bf86568b1f431d";
+const EOA_KEY: &str = "0x411bdd63dc116ba53e0e3fbe752ba21f869e272d4f544c8d545c617ce43f654e";
+
+const TARGET: Duration = Duration::from_secs(1);
+
+/// Counts every allocation (reallocs included) made through the global allocator.
+struct CountingAlloc;
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn bench<T>(name: &str, mut f: impl FnMut() -> T) {
+    // warm up caches and the allocator before measuring
+    for _ in 0..10 {
+        black_box(f());
+    }
+
+    let mut iters = 0u64;
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+    let start = Instant::now();
+    while start.elapsed() < TARGET {
+        black_box(f());
+        iters += 1;
+    }
+    let per_op = start.elapsed().as_nanos() / iters as u128;
+    let allocs_per_op = (ALLOCATIONS.load(Ordering::Relaxed) - allocations) / iters;
+    println!("{name:<40} {per_op:>10} ns/op {allocs_per_op:>4} allocs/op ({iters} iters)");
+}
+
+fn legacy_tx() -> LegacyTransaction {
+    LegacyTransaction {
+        nonce: U256::from(7),
+        gas_price: U256::from(1_000_000_000u64),
+        gas_limit: U256::from(21000u64),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        data: vec![],
+        v: 2709,
+        r: U256::MAX,
+        s: U256::MAX,
+    }
+}
+
+fn eip1559_tx() -> Eip1559Transaction {
+    Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(1_000_000_000u64),
+        gas_limit: U256::from(21000u64),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 1,
+        r: U256::MAX,
+        s: U256::MAX,
+    }
+}
+
+fn authorization() -> Authorization7702 {
+    Authorization7702::new(
+        0,
+        Address::repeat_byte(0x4c),
+        U256::from(3),
+        EOA_KEY.to_string(),
+    )
+    .unwrap()
+}
+
+fn eip7702_tx(authorization_list: Vec<Authorization7702>) -> Eip7702Transaction {
+    Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(1_000_000_000u64),
+        gas_limit: U256::from(46000u64),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        data: vec![],
+        access_list: vec![],
+        authorization_list,
+        y_parity: 1,
+        r: U256::MAX,
+        s: U256::MAX,
+    }
+}
+
+fn main() {
+    let secret_key =
+        SecretKey::from_slice(&hex::decode(DEPLOYER_KEY.trim_start_matches("0x")).unwrap())
+            .unwrap();
+
+    let legacy = legacy_tx();
+    bench("legacy/rlp_encode_signed", || legacy.rlp_encode_signed());
+
+    let eip1559 = eip1559_tx();
+    bench("1559/rlp_encode_signed", || eip1559.rlp_encode_signed());
+
+    let eip7702 = eip7702_tx(vec![authorization()]);
+    bench("7702/rlp_encode_signed (1 auth)", || {
+        eip7702.rlp_encode_signed()
+    });
+
+    let eip7702 = eip7702_tx((0..10).map(|_| authorization()).collect());
+    bench("7702/rlp_encode_signed (10 auths)", || {
+        eip7702.rlp_encode_signed()
+    });
+
+    let hash = keccak256(eip1559.rlp_encode_unsigned());
+    bench("sign_hash", || sign_hash(&hash, &secret_key));
+
+    bench("Authorization7702::new", authorization);
+
+    // signing dominates building a 7702 tx, and grows linearly with the list
+    for count in [1, 10] {
+        bench(&format!("7702/build + sign ({count} auths)"), || {
+            let mut tx = eip7702_tx((0..count).map(|_| authorization()).collect());
+            let hash = keccak256(tx.rlp_encode_unsigned());
+            (tx.r, tx.s, tx.y_parity) = sign_hash(&hash, &secret_key).unwrap();
+            tx.rlp_encode_signed()
+        });
+    }
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+use example_7702::decode::decode_signed_tx;
+use libfuzzer_sys::fuzz_target;
+
+// Any input must come back as `Ok` or a `TxError`; a panic is a bug.
+fuzz_target!(|data: &[u8]| {
+    let _ = decode_signed_tx(data);
+});
@@ -0,0 +1,67 @@
+//! Just enough ABI decoding to read simple `eth_call` results and revert
+//! reasons: a single static value, or a single `string`.
+
+use alloy::primitives::{Address, U256};
+
+use crate::error::TxError;
+
+/// `Error(string)`, which `require` and `revert("...")` revert with.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+
+/// The 32-byte word at `offset`.
+fn word(data: &[u8], offset: usize) -> Result<&[u8; 32], TxError> {
+    offset
+        .checked_add(32)
+        .and_then(|end| data.get(offset..end))
+        .map(|word| word.try_into().unwrap())
+        .ok_or_else(|| {
+            TxError::Decode(format!(
+                "ABI data too short: no word at {offset} in {} bytes",
+                data.len()
+            ))
+        })
+}
+
+/// A word used as an offset or length; anything past `usize` can't point
+/// inside the data anyway.
+fn word_as_usize(data: &[u8], offset: usize) -> Result<usize, TxError> {
+    let value = U256::from_be_bytes(*word(data, offset)?);
+    usize::try_from(value)
+        .map_err(|_| TxError::Decode(format!("ABI offset or length {value} is out of range")))
+}
+
+/// A `uint256` return value.
+pub fn decode_uint256(data: &[u8]) -> Result<U256, TxError> {
+    Ok(U256::from_be_bytes(*word(data, 0)?))
+}
+
+/// An `address` return value; the 12 bytes of padding must be zero.
+pub fn decode_address(data: &[u8]) -> Result<Address, TxError> {
+    let word = word(data, 0)?;
+    if word[..12].iter().any(|b| *b != 0) {
+        return Err(TxError::Decode(format!(
+            "not an address: 0x{}",
+            hex::encode(word)
+        )));
+    }
+    Ok(Address::from_slice(&word[12..]))
+}
+
+/// A `string` return value: an offset to a length word, then the bytes.
+pub fn decode_string(data: &[u8]) -> Result<String, TxError> {
+    let offset = word_as_usize(data, 0)?;
+    let len = word_as_usize(data, offset)?;
+    let start = offset + 32;
+    let bytes = start
+        .checked_add(len)
+        .and_then(|end| data.get(start..end))
+        .ok_or_else(|| TxError::Decode(format!("ABI string of {len} bytes runs past the data")))?;
+    String::from_utf8(bytes.to_vec())
+        .map_err(|_| TxError::Decode("ABI string is not UTF-8".to_string()))
+}
+
+/// The message of an `Error(string)` revert, if that's what `data` is.
+pub fn revert_reason(data: &[u8]) -> Option<String> {
+    data.strip_prefix(&ERROR_SELECTOR)
+        .and_then(|args| decode_string(args).ok())
+}
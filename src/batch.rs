@@ -0,0 +1,209 @@
+//! One plain 1559 transfer per row of a `--recipients` CSV.
+
+use std::{
+    path::Path,
+    str::FromStr,
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use alloy::primitives::{Address, B256, U256};
+
+use crate::{
+    error::TxError,
+    hexutil::parse_address,
+    rpc::{send_raw, Provider},
+    signing::Signer,
+    tx::Eip1559Transaction,
+};
+
+/// One `address,value` row; `value` is in wei.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipient {
+    /// 1-based line in the file, for reporting.
+    pub line: usize,
+    pub to: Address,
+    pub value: U256,
+}
+
+/// Reads a recipients CSV; see [`parse_recipients`].
+pub fn read_recipients(path: impl AsRef<Path>) -> Result<Vec<Result<Recipient, TxError>>, TxError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| TxError::Config(format!("{}: {err}", path.display())))?;
+    Ok(parse_recipients(&text))
+}
+
+/// Parses `address,value` lines, skipping blank lines and `#` comments.
+///
+/// Every other line yields a row, so the caller decides whether a bad one
+/// skips just that row or aborts the batch.
+pub fn parse_recipients(text: &str) -> Vec<Result<Recipient, TxError>> {
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            let line = line.trim();
+            !line.is_empty() && !line.starts_with('#')
+        })
+        .map(|(i, line)| {
+            parse_row(line)
+                .map_err(|msg| TxError::Config(format!("line {}: {msg}", i + 1)))
+                .map(|(to, value)| Recipient {
+                    line: i + 1,
+                    to,
+                    value,
+                })
+        })
+        .collect()
+}
+
+fn parse_row(line: &str) -> Result<(Address, U256), String> {
+    let (to, value) = line
+        .split_once(',')
+        .ok_or_else(|| format!("expected `address,value`, got {line:?}"))?;
+    let (to, value) = (to.trim(), value.trim());
+    let to = parse_address(to).map_err(|err| err.to_string())?;
+    let value = U256::from_str(value).map_err(|_| format!("invalid amount {value:?}"))?;
+    Ok((to, value))
+}
+
+/// Hands out consecutive nonces for txs from one sender.
+#[derive(Debug)]
+pub struct NonceManager {
+    next: U256,
+}
+
+impl NonceManager {
+    /// Starts at `nonce`, usually the sender's pending nonce.
+    pub fn new(nonce: U256) -> Self {
+        Self { next: nonce }
+    }
+
+    /// Reserves the next nonce.
+    pub fn next_nonce(&mut self) -> U256 {
+        let nonce = self.next;
+        self.next += U256::from(1);
+        nonce
+    }
+
+    /// Gives back the most recent nonce when its tx never made it to the
+    /// node, so the next tx doesn't leave a gap behind it.
+    pub fn release(&mut self, nonce: U256) {
+        if nonce + U256::from(1) == self.next {
+            self.next = nonce;
+        }
+    }
+}
+
+/// The fields every transfer in a batch shares.
+#[derive(Debug, Clone)]
+pub struct TransferTemplate {
+    pub chain_id: u64,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+}
+
+impl TransferTemplate {
+    /// The unsigned transfer for one row.
+    pub fn build(&self, recipient: &Recipient, nonce: U256) -> Eip1559Transaction {
+        Eip1559Transaction {
+            chain_id: self.chain_id,
+            nonce,
+            max_priority_fee_per_gas: self.max_priority_fee_per_gas,
+            max_fee_per_gas: self.max_fee_per_gas,
+            gas_limit: self.gas_limit,
+            to: Some(recipient.to),
+            value: recipient.value,
+            data: vec![],
+            access_list: vec![],
+            y_parity: 0,
+            r: U256::ZERO,
+            s: U256::ZERO,
+        }
+    }
+}
+
+/// What became of one row of a batch.
+#[derive(Debug)]
+pub enum RowOutcome {
+    /// The node accepted the tx, so `nonce` is used up.
+    Sent {
+        recipient: Recipient,
+        nonce: U256,
+        tx_hash: B256,
+    },
+    /// Signing failed or the node rejected the tx; its nonce went to the
+    /// next row.
+    Failed {
+        recipient: Recipient,
+        error: TxError,
+    },
+    /// The send broke off without an answer (a timeout, a dropped
+    /// connection), so the node may have the tx. `nonce` is not reused.
+    Unknown {
+        recipient: Recipient,
+        nonce: U256,
+        error: TxError,
+    },
+    /// The row didn't parse.
+    Invalid(TxError),
+    /// The batch was interrupted before this row's turn.
+    NotSent(Recipient),
+}
+
+/// Signs and sends a transfer per good row, in order, and reports every
+/// row.
+///
+/// Once `interrupted` is set no new tx is sent: the one in flight finishes
+/// and every row after it comes back as [`RowOutcome::NotSent`].
+pub async fn send_batch(
+    provider: &Provider,
+    signer: &impl Signer,
+    rows: Vec<Result<Recipient, TxError>>,
+    template: &TransferTemplate,
+    nonces: &mut NonceManager,
+    interrupted: &AtomicBool,
+) -> Vec<RowOutcome> {
+    let mut outcomes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let recipient = match row {
+            Ok(recipient) => recipient,
+            Err(err) => {
+                outcomes.push(RowOutcome::Invalid(err));
+                continue;
+            }
+        };
+        if interrupted.load(Ordering::SeqCst) {
+            outcomes.push(RowOutcome::NotSent(recipient));
+            continue;
+        }
+
+        let nonce = nonces.next_nonce();
+        let mut tx = template.build(&recipient, nonce);
+        if let Err(error) = signer.finalize(&mut tx).await {
+            // nothing reached the node, so the next row can reuse the nonce
+            nonces.release(nonce);
+            outcomes.push(RowOutcome::Failed { recipient, error });
+            continue;
+        }
+
+        outcomes.push(match send_raw(provider, &tx.rlp_encode_signed()).await {
+            Ok(tx_hash) => RowOutcome::Sent {
+                recipient,
+                nonce,
+                tx_hash,
+            },
+            // the node answered and refused it, so the nonce is still free
+            Err(error @ TxError::Rpc(_)) => {
+                nonces.release(nonce);
+                RowOutcome::Failed { recipient, error }
+            }
+            Err(error) => RowOutcome::Unknown {
+                recipient,
+                nonce,
+                error,
+            },
+        });
+    }
+    outcomes
+}
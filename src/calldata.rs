@@ -0,0 +1,67 @@
+//! Where a tx's calldata comes from: hex on the command line, hex in a
+//! file, or a call without arguments named by its signature.
+
+use std::path::PathBuf;
+
+use alloy::primitives::keccak256;
+
+use crate::{error::TxError, hexutil::parse_hex};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DataInput {
+    /// `--data 0x...`
+    Hex(String),
+    /// `--data-file path`, a file holding the calldata as hex.
+    File(PathBuf),
+    /// `--call 'increment()'`: just the selector of that signature.
+    Call(String),
+}
+
+impl DataInput {
+    /// The one source the flags name, if any. Naming more than one is an
+    /// error rather than a silent pick.
+    pub fn from_flags(
+        data: Option<&str>,
+        data_file: Option<&str>,
+        call: Option<&str>,
+    ) -> Result<Option<Self>, TxError> {
+        match (data, data_file, call) {
+            (None, None, None) => Ok(None),
+            (Some(hex), None, None) => Ok(Some(DataInput::Hex(hex.to_string()))),
+            (None, Some(path), None) => Ok(Some(DataInput::File(path.into()))),
+            (None, None, Some(signature)) => Ok(Some(DataInput::Call(signature.to_string()))),
+            _ => Err(TxError::Config(
+                "pass only one of --data, --data-file and --call".to_string(),
+            )),
+        }
+    }
+
+    /// The calldata bytes.
+    pub fn resolve(&self) -> Result<Vec<u8>, TxError> {
+        match self {
+            DataInput::Hex(hex) => parse_hex(hex),
+            DataInput::File(path) => {
+                let text = std::fs::read_to_string(path)
+                    .map_err(|err| TxError::Config(format!("{}: {err}", path.display())))?;
+                parse_hex(text.trim())
+            }
+            DataInput::Call(signature) => selector(signature).map(|selector| selector.to_vec()),
+        }
+    }
+}
+
+/// The 4-byte selector of a signature without arguments, e.g. `increment()`.
+fn selector(signature: &str) -> Result<[u8; 4], TxError> {
+    let name = signature.strip_suffix("()").unwrap_or_default();
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if !is_identifier {
+        return Err(TxError::Config(format!(
+            "--call takes a function without arguments, like increment(); got {signature:?}"
+        )));
+    }
+    Ok(keccak256(signature)[..4].try_into().unwrap())
+}
@@ -0,0 +1,253 @@
+use std::{collections::HashMap, path::Path};
+
+use serde::Deserialize;
+use serde_json::{Map, Number, Value};
+use toml_edit::{DocumentMut, Item};
+
+use crate::error::TxError;
+
+/// Settings loaded from `--config <path>`.
+///
+/// Every field has a default matching the local geth setup from the README,
+/// so a file only needs the values it wants to change:
+///
+/// ```toml
+/// rpc_url = "http://127.0.0.1:8848"
+/// chain_id = 1337
+/// gas = 60000
+///
+/// [fees]
+/// max_fee_per_gas = 2000000000
+/// max_priority_fee_per_gas = 1000000000
+/// gas_price = 1000000000
+///
+/// [keys]
+/// sender = "0x..."
+/// authority = "0x..."
+/// ```
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub rpc_url: String,
+    pub chain_id: u64,
+    /// Gas limit; when unset each tx type uses its own default.
+    pub gas: Option<u64>,
+    pub fees: FeeConfig,
+    pub keys: KeyConfig,
+}
+
+/// Fees in wei.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FeeConfig {
+    pub max_fee_per_gas: u128,
+    pub max_priority_fee_per_gas: u128,
+    /// Only used by legacy txs.
+    pub gas_price: u128,
+}
+
+/// Hex private keys.
+#[derive(Debug, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyConfig {
+    /// Signs the transaction itself.
+    pub sender: String,
+    /// Signs the 7702 authorization, i.e. the account that gets delegated.
+    pub authority: String,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            rpc_url: "http://127.0.0.1:8848".to_string(),
+            chain_id: 1337,
+            gas: None,
+            fees: FeeConfig::default(),
+            keys: KeyConfig::default(),
+        }
+    }
+}
+
+impl Default for FeeConfig {
+    fn default() -> Self {
+        Self {
+            max_fee_per_gas: 1_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            gas_price: 1_000_000_000, // 1 gwei
+        }
+    }
+}
+
+impl Default for KeyConfig {
+    fn default() -> Self {
+        // the example accounts from the README
+        Self {
+            sender: "0x0fad2ca996a24d116097c481c27a59652a3d3611dfed64d8f9bf86568b1f431d"
+                .to_string(),
+            authority: "0x411bdd63dc116ba53e0e3fbe752ba21f869e272d4f544c8d545c617ce43f654e"
+                .to_string(),
+        }
+    }
+}
+
+/// Command-line values that take precedence over the config file.
+#[derive(Debug, Default, clap::Args)]
+pub struct ConfigOverrides {
+    /// JSON-RPC endpoint of the node
+    #[arg(long)]
+    pub rpc_url: Option<String>,
+
+    /// Chain id to sign for
+    #[arg(long)]
+    pub chain_id: Option<u64>,
+
+    /// Gas limit
+    #[arg(long)]
+    pub gas: Option<u64>,
+
+    /// Max fee per gas in wei (1559 and 7702)
+    #[arg(long)]
+    pub max_fee_per_gas: Option<u128>,
+
+    /// Max priority fee per gas in wei (1559 and 7702)
+    #[arg(long)]
+    pub max_priority_fee_per_gas: Option<u128>,
+
+    /// Gas price in wei (legacy)
+    #[arg(long)]
+    pub gas_price: Option<u128>,
+}
+
+impl Config {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, TxError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| TxError::Config(format!("{}: {err}", path.display())))?;
+        Self::parse(&text).map_err(|err| match err {
+            TxError::Config(msg) => TxError::Config(format!("{}: {msg}", path.display())),
+            other => other,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, TxError> {
+        let doc: DocumentMut = text
+            .parse()
+            .map_err(|err: toml_edit::TomlError| TxError::Config(err.to_string()))?;
+        // toml_edit has no serde support here, so go through a JSON value
+        let value = item_to_json(doc.as_item());
+        serde_json::from_value(value).map_err(|err| TxError::Config(err.to_string()))
+    }
+
+    /// Applies `RPC_URL` and `PRIVATE_KEY` (the sender key) from the
+    /// environment on top of the file.
+    pub fn with_env(mut self, var: impl Fn(&str) -> Option<String>) -> Self {
+        if let Some(rpc_url) = var("RPC_URL") {
+            self.rpc_url = rpc_url;
+        }
+        if let Some(key) = var("PRIVATE_KEY") {
+            self.keys.sender = key;
+        }
+        self
+    }
+
+    /// Applies the command-line values on top of the file: CLI wins.
+    pub fn with_overrides(mut self, overrides: ConfigOverrides) -> Self {
+        if let Some(rpc_url) = overrides.rpc_url {
+            self.rpc_url = rpc_url;
+        }
+        if let Some(chain_id) = overrides.chain_id {
+            self.chain_id = chain_id;
+        }
+        if let Some(gas) = overrides.gas {
+            self.gas = Some(gas);
+        }
+        if let Some(fee) = overrides.max_fee_per_gas {
+            self.fees.max_fee_per_gas = fee;
+        }
+        if let Some(fee) = overrides.max_priority_fee_per_gas {
+            self.fees.max_priority_fee_per_gas = fee;
+        }
+        if let Some(price) = overrides.gas_price {
+            self.fees.gas_price = price;
+        }
+        self
+    }
+}
+
+/// Reads a `.env` file into a map; it does not touch the process environment.
+pub fn read_env_file(path: impl AsRef<Path>) -> Result<HashMap<String, String>, TxError> {
+    let path = path.as_ref();
+    let text = std::fs::read_to_string(path)
+        .map_err(|err| TxError::Config(format!("{}: {err}", path.display())))?;
+    Ok(parse_env(&text))
+}
+
+/// Parses `KEY=value` lines. Blank lines, `#` comments and a leading
+/// `export ` are skipped; values may be wrapped in single or double quotes,
+/// and unquoted values end at a ` #` comment.
+pub fn parse_env(text: &str) -> HashMap<String, String> {
+    text.lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                return None;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            Some((key.trim().to_string(), unquote(value.trim()).to_string()))
+        })
+        .collect()
+}
+
+fn unquote(value: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|rest| rest.strip_suffix(quote))
+        {
+            return inner;
+        }
+    }
+    match value.find(" #") {
+        Some(comment) => value[..comment].trim_end(),
+        None => value,
+    }
+}
+
+fn item_to_json(item: &Item) -> Value {
+    match item {
+        Item::None => Value::Null,
+        Item::Value(value) => value_to_json(value),
+        Item::Table(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, item)| (key.to_string(), item_to_json(item)))
+                .collect(),
+        ),
+        Item::ArrayOfTables(tables) => Value::Array(
+            tables
+                .iter()
+                .map(|table| item_to_json(&Item::Table(table.clone())))
+                .collect(),
+        ),
+    }
+}
+
+fn value_to_json(value: &toml_edit::Value) -> Value {
+    use toml_edit::Value as Toml;
+
+    match value {
+        Toml::String(s) => Value::String(s.value().clone()),
+        Toml::Integer(i) => Value::Number((*i.value()).into()),
+        Toml::Float(f) => Number::from_f64(*f.value()).map_or(Value::Null, Value::Number),
+        Toml::Boolean(b) => Value::Bool(*b.value()),
+        Toml::Datetime(dt) => Value::String(dt.value().to_string()),
+        Toml::Array(array) => Value::Array(array.iter().map(value_to_json).collect()),
+        Toml::InlineTable(table) => Value::Object(
+            table
+                .iter()
+                .map(|(key, value)| (key.to_string(), value_to_json(value)))
+                .collect::<Map<_, _>>(),
+        ),
+    }
+}
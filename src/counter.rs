@@ -0,0 +1,38 @@
+//! Typed calldata for the example `Counter` contract (`contract/src/Counter.sol`),
+//! which the 7702 flow delegates the EOA to.
+
+use alloy::{primitives::U256, sol, sol_types::SolCall};
+
+sol! {
+    interface Counter {
+        function number() external view returns (uint256);
+        function setNumber(uint256 newNumber) external;
+        function increment() external;
+        function sayHello() external returns (string memory);
+        function transferToSender(uint256 amount) external;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterMethod {
+    Number,
+    SetNumber(U256),
+    Increment,
+    SayHello,
+    TransferToSender(U256),
+}
+
+/// ABI-encoded calldata (selector and arguments) for `method`.
+pub fn counter_calldata(method: CounterMethod) -> Vec<u8> {
+    match method {
+        CounterMethod::Number => Counter::numberCall {}.abi_encode(),
+        CounterMethod::SetNumber(number) => {
+            Counter::setNumberCall { newNumber: number }.abi_encode()
+        }
+        CounterMethod::Increment => Counter::incrementCall {}.abi_encode(),
+        CounterMethod::SayHello => Counter::sayHelloCall {}.abi_encode(),
+        CounterMethod::TransferToSender(amount) => {
+            Counter::transferToSenderCall { amount }.abi_encode()
+        }
+    }
+}
@@ -0,0 +1,505 @@
+use std::fmt::Write;
+
+use alloy::primitives::{Address, B256, U256};
+use alloy_rlp::{Decodable, Header, EMPTY_STRING_CODE};
+
+use crate::{
+    error::TxError,
+    gas::total_max_cost,
+    hexutil::parse_hex,
+    signing::{recover_address, SECP256K1N_HALF},
+    summary::describe_access_list,
+    tx::{
+        authorities, Authorization7702, Eip1559Transaction, Eip7702Transaction, LegacyTransaction,
+    },
+};
+
+/// A signed transaction of any of the types this tool can produce.
+#[derive(Debug)]
+pub enum SignedTx {
+    Legacy(LegacyTransaction),
+    Eip1559(Eip1559Transaction),
+    Eip7702(Eip7702Transaction),
+}
+
+impl SignedTx {
+    pub fn rlp_encode_signed(&self) -> Vec<u8> {
+        match self {
+            SignedTx::Legacy(tx) => tx.rlp_encode_signed(),
+            SignedTx::Eip1559(tx) => tx.rlp_encode_signed(),
+            SignedTx::Eip7702(tx) => tx.rlp_encode_signed(),
+        }
+    }
+
+    /// Empty for legacy txs, which can't carry one.
+    pub fn access_list(&self) -> &[(Address, Vec<U256>)] {
+        match self {
+            SignedTx::Legacy(_) => &[],
+            SignedTx::Eip1559(tx) => &tx.access_list,
+            SignedTx::Eip7702(tx) => &tx.access_list,
+        }
+    }
+
+    /// The balance the node wants to see before accepting the tx:
+    /// `value + gas_limit * max fee` (the gas price for legacy). `None` if
+    /// that overflows, which no balance could cover.
+    pub fn max_cost(&self) -> Option<U256> {
+        let (value, gas_limit, fee_per_gas) = match self {
+            SignedTx::Legacy(tx) => (tx.value, tx.gas_limit, tx.gas_price),
+            SignedTx::Eip1559(tx) => (tx.value, tx.gas_limit, tx.max_fee_per_gas),
+            SignedTx::Eip7702(tx) => (tx.value, tx.gas_limit, tx.max_fee_per_gas),
+        };
+        total_max_cost(gas_limit, fee_per_gas, value)
+    }
+
+    pub fn nonce(&self) -> U256 {
+        match self {
+            SignedTx::Legacy(tx) => tx.nonce,
+            SignedTx::Eip1559(tx) => tx.nonce,
+            SignedTx::Eip7702(tx) => tx.nonce,
+        }
+    }
+
+    /// The chain the tx is signed for; `None` for a pre-EIP-155 legacy tx,
+    /// which is valid on any chain.
+    pub fn chain_id(&self) -> Option<u64> {
+        match self {
+            SignedTx::Legacy(tx) => chain_id_from_v(tx.v),
+            SignedTx::Eip1559(tx) => Some(tx.chain_id),
+            SignedTx::Eip7702(tx) => Some(tx.chain_id),
+        }
+    }
+
+    /// Recovers who signed the tx.
+    pub fn sender(&self) -> Result<Address, TxError> {
+        match self {
+            SignedTx::Legacy(tx) => tx.recover_sender(),
+            SignedTx::Eip1559(tx) => recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity),
+            SignedTx::Eip7702(tx) => recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity),
+        }
+    }
+}
+
+/// Decodes the bytes produced by any of the `rlp_encode_signed` methods.
+///
+/// Trailing bytes after the outer list are an error, as is any type byte
+/// other than 0x02 and 0x04 (or a bare list for legacy). So is a high-s
+/// signature, on the tx or on any of its authorizations: nodes reject
+/// those since EIP-2.
+pub fn decode_signed_tx(raw: &[u8]) -> Result<SignedTx, TxError> {
+    let tx = decode_signed_tx_lenient(raw)?;
+    check_low_s(&tx)?;
+    Ok(tx)
+}
+
+/// [`decode_signed_tx`] without the high-s check, for looking at txs no
+/// node would accept.
+pub fn decode_signed_tx_lenient(raw: &[u8]) -> Result<SignedTx, TxError> {
+    match split_envelope(raw)? {
+        (Some(0x02), rlp) => Ok(SignedTx::Eip1559(decode_1559(rlp)?)),
+        (Some(0x04), rlp) => Ok(SignedTx::Eip7702(decode_7702(rlp)?)),
+        (None, rlp) => Ok(SignedTx::Legacy(decode_legacy(rlp)?)),
+        (Some(b), _) => Err(TxError::Decode(format!("unsupported tx type 0x{b:02x}"))),
+    }
+}
+
+/// Splits an EIP-2718 envelope into its type byte and the RLP after it.
+/// A legacy tx has no type byte: it is a bare list, returned whole.
+///
+/// Only the first byte is looked at, so the RLP may still be malformed.
+pub fn split_envelope(raw: &[u8]) -> Result<(Option<u8>, &[u8]), TxError> {
+    match raw.first() {
+        None => Err(TxError::Decode("empty input".to_string())),
+        Some(&b) if b <= 0x7f => Ok((Some(b), &raw[1..])),
+        Some(&b) if b >= 0xc0 => Ok((None, raw)),
+        Some(b) => Err(TxError::Decode(format!(
+            "0x{b:02x} is neither a tx type nor the start of a legacy tx"
+        ))),
+    }
+}
+
+fn check_low_s(tx: &SignedTx) -> Result<(), TxError> {
+    let high_s = |s: U256| s > SECP256K1N_HALF;
+    let s = match tx {
+        SignedTx::Legacy(tx) => tx.s,
+        SignedTx::Eip1559(tx) => tx.s,
+        SignedTx::Eip7702(tx) => {
+            if let Some(i) = tx.authorization_list.iter().position(|auth| high_s(auth.s)) {
+                return Err(TxError::Decode(format!(
+                    "authorization {i} has a high-s signature (EIP-2)"
+                )));
+            }
+            tx.s
+        }
+    };
+    if high_s(s) {
+        return Err(TxError::Decode("high-s signature (EIP-2)".to_string()));
+    }
+    Ok(())
+}
+
+/// The chain id an EIP-155 `v` (`2 * chain_id + 35 + parity`) was signed
+/// for; `None` for the chain-less 27/28 and anything else below 35.
+pub fn chain_id_from_v(v: u64) -> Option<u64> {
+    v.checked_sub(35).map(|rest| rest / 2)
+}
+
+/// Entry point for the `decode` subcommand. `lenient` lets high-s
+/// signatures through.
+pub fn print_decoded(raw_hex: &str, lenient: bool) -> Result<(), TxError> {
+    let raw = parse_hex(raw_hex)?;
+    let tx = if lenient {
+        decode_signed_tx_lenient(&raw)?
+    } else {
+        decode_signed_tx(&raw)?
+    };
+
+    match tx {
+        SignedTx::Legacy(tx) => {
+            println!("Legacy transaction:\n{tx:#?}");
+            match chain_id_from_v(tx.v) {
+                Some(chain_id) => println!("Chain id: {chain_id}"),
+                None => println!("Chain id: none (pre-EIP-155, valid on any chain)"),
+            }
+            print_sender(tx.recover_sender());
+        }
+        SignedTx::Eip1559(tx) => {
+            println!("EIP-1559 transaction:\n{tx:#?}");
+            print_sender(recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity));
+            print!("{}", describe_access_list(&tx.access_list));
+        }
+        SignedTx::Eip7702(tx) => {
+            println!("EIP-7702 transaction:\n{tx:#?}");
+            print_sender(recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity));
+            print!("{}", describe_access_list(&tx.access_list));
+            println!("Authorities:");
+            for (i, (auth, authority)) in tx
+                .authorization_list
+                .iter()
+                .zip(authorities(&tx))
+                .enumerate()
+            {
+                match authority {
+                    Ok(authority) => println!("  [{i}] {authority} -> {}", auth.address),
+                    Err(err) => println!("  [{i}] unrecoverable ({err}) -> {}", auth.address),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+const LEGACY_FIELDS: &[&str] = &[
+    "nonce",
+    "gas_price",
+    "gas_limit",
+    "to",
+    "value",
+    "data",
+    "v",
+    "r",
+    "s",
+];
+const EIP1559_FIELDS: &[&str] = &[
+    "chain_id",
+    "nonce",
+    "max_priority_fee_per_gas",
+    "max_fee_per_gas",
+    "gas_limit",
+    "to",
+    "value",
+    "data",
+    "access_list",
+    "y_parity",
+    "r",
+    "s",
+];
+const EIP7702_FIELDS: &[&str] = &[
+    "chain_id",
+    "nonce",
+    "max_priority_fee_per_gas",
+    "max_fee_per_gas",
+    "gas_limit",
+    "to",
+    "value",
+    "data",
+    "access_list",
+    "authorization_list",
+    "y_parity",
+    "r",
+    "s",
+];
+const ACCESS_ENTRY_FIELDS: &[&str] = &["address", "storage_keys"];
+const AUTHORIZATION_FIELDS: &[&str] = &["chain_id", "address", "nonce", "y_parity", "r", "s"];
+/// Fields shown in decimal as well as hex.
+const QUANTITIES: &[&str] = &[
+    "chain_id",
+    "nonce",
+    "gas_price",
+    "gas_limit",
+    "max_priority_fee_per_gas",
+    "max_fee_per_gas",
+    "value",
+    "v",
+    "y_parity",
+];
+
+/// What an RLP list holds, so [`explain`] can name its items.
+#[derive(Clone, Copy)]
+enum Shape {
+    Fields(&'static [&'static str]),
+    AccessList,
+    StorageKeys,
+    AuthorizationList,
+}
+
+impl Shape {
+    /// The label and, for a nested list, the shape of item `i`.
+    fn item(self, i: usize) -> (String, Option<Shape>) {
+        match self {
+            Shape::Fields(names) => {
+                let name = names.get(i).copied().unwrap_or("(extra)");
+                let shape = match name {
+                    "access_list" => Some(Shape::AccessList),
+                    "storage_keys" => Some(Shape::StorageKeys),
+                    "authorization_list" => Some(Shape::AuthorizationList),
+                    _ => None,
+                };
+                (name.to_string(), shape)
+            }
+            Shape::AccessList => (format!("[{i}]"), Some(Shape::Fields(ACCESS_ENTRY_FIELDS))),
+            Shape::StorageKeys => (format!("[{i}]"), None),
+            Shape::AuthorizationList => {
+                (format!("[{i}]"), Some(Shape::Fields(AUTHORIZATION_FIELDS)))
+            }
+        }
+    }
+}
+
+/// `--explain`: the EIP-2718 type byte of a signed tx, then its RLP as a
+/// tree with every item named after the field it holds.
+pub fn explain(raw: &[u8]) -> Result<String, TxError> {
+    let (type_line, fields, mut rlp) = match split_envelope(raw)? {
+        (Some(0x02), rlp) => ("type: 0x02 (EIP-1559)", EIP1559_FIELDS, rlp),
+        (Some(0x04), rlp) => ("type: 0x04 (EIP-7702)", EIP7702_FIELDS, rlp),
+        (None, rlp) => (
+            "type: legacy (no type byte, a bare RLP list)",
+            LEGACY_FIELDS,
+            rlp,
+        ),
+        (Some(b), _) => return Err(TxError::Decode(format!("unsupported tx type 0x{b:02x}"))),
+    };
+
+    let mut out = format!("{type_line}\n");
+    explain_item(&mut rlp, "rlp", Some(Shape::Fields(fields)), 0, &mut out)?;
+    expect_empty(rlp)?;
+    Ok(out)
+}
+
+fn explain_item(
+    buf: &mut &[u8],
+    label: &str,
+    shape: Option<Shape>,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), TxError> {
+    let indent = "  ".repeat(depth);
+    let header = Header::decode(buf)?;
+    if buf.len() < header.payload_length {
+        return Err(alloy_rlp::Error::InputTooShort.into());
+    }
+    let (payload, rest) = buf.split_at(header.payload_length);
+    *buf = rest;
+
+    if !header.list {
+        let meaning = match label {
+            "to" if payload.is_empty() => " (contract creation)".to_string(),
+            _ if QUANTITIES.contains(&label) => match U256::try_from_be_slice(payload) {
+                Some(value) => format!(" ({value})"),
+                None => String::new(),
+            },
+            _ => String::new(),
+        };
+        // writing to a String can't fail
+        let _ = writeln!(out, "{indent}{label}: 0x{}{meaning}", hex::encode(payload));
+        return Ok(());
+    }
+
+    let note = match shape {
+        Some(Shape::Fields(AUTHORIZATION_FIELDS)) => {
+            "  (signed as keccak256(0x05 || rlp([chain_id, address, nonce])))"
+        }
+        _ => "",
+    };
+    let _ = writeln!(out, "{indent}{label}: [{note}");
+    let mut payload = payload;
+    let mut i = 0;
+    while !payload.is_empty() {
+        let (label, shape) = match shape {
+            Some(shape) => shape.item(i),
+            None => (format!("[{i}]"), None),
+        };
+        explain_item(&mut payload, &label, shape, depth + 1, out)?;
+        i += 1;
+    }
+    let _ = writeln!(out, "{indent}]");
+    Ok(())
+}
+
+fn print_sender(sender: Result<Address, TxError>) {
+    match sender {
+        Ok(sender) => println!("Sender: {sender}"),
+        Err(err) => println!("Sender: unrecoverable ({err})"),
+    }
+}
+
+fn decode_legacy(mut raw: &[u8]) -> Result<LegacyTransaction, TxError> {
+    let mut payload = list_payload(&mut raw)?;
+    expect_empty(raw)?;
+
+    let tx = LegacyTransaction {
+        nonce: U256::decode(&mut payload)?,
+        gas_price: U256::decode(&mut payload)?,
+        gas_limit: U256::decode(&mut payload)?,
+        to: decode_to(&mut payload)?,
+        value: U256::decode(&mut payload)?,
+        data: Header::decode_bytes(&mut payload, false)?.to_vec(),
+        v: u64::decode(&mut payload)?,
+        r: U256::decode(&mut payload)?,
+        s: U256::decode(&mut payload)?,
+    };
+    expect_empty(payload)?;
+    Ok(tx)
+}
+
+fn decode_1559(mut raw: &[u8]) -> Result<Eip1559Transaction, TxError> {
+    let mut payload = list_payload(&mut raw)?;
+    expect_empty(raw)?;
+
+    let tx = Eip1559Transaction {
+        chain_id: u64::decode(&mut payload)?,
+        nonce: U256::decode(&mut payload)?,
+        max_priority_fee_per_gas: U256::decode(&mut payload)?,
+        max_fee_per_gas: U256::decode(&mut payload)?,
+        gas_limit: U256::decode(&mut payload)?,
+        to: decode_to(&mut payload)?,
+        value: U256::decode(&mut payload)?,
+        data: Header::decode_bytes(&mut payload, false)?.to_vec(),
+        access_list: decode_access_list(&mut payload)?,
+        y_parity: u8::decode(&mut payload)?,
+        r: U256::decode(&mut payload)?,
+        s: U256::decode(&mut payload)?,
+    };
+    expect_empty(payload)?;
+    Ok(tx)
+}
+
+fn decode_7702(mut raw: &[u8]) -> Result<Eip7702Transaction, TxError> {
+    let mut payload = list_payload(&mut raw)?;
+    expect_empty(raw)?;
+
+    let tx = Eip7702Transaction {
+        chain_id: u64::decode(&mut payload)?,
+        nonce: U256::decode(&mut payload)?,
+        max_priority_fee_per_gas: U256::decode(&mut payload)?,
+        max_fee_per_gas: U256::decode(&mut payload)?,
+        gas_limit: U256::decode(&mut payload)?,
+        to: decode_to(&mut payload)?,
+        value: U256::decode(&mut payload)?,
+        data: Header::decode_bytes(&mut payload, false)?.to_vec(),
+        access_list: decode_access_list(&mut payload)?,
+        authorization_list: decode_authorization_list(&mut payload)?,
+        y_parity: u8::decode(&mut payload)?,
+        r: U256::decode(&mut payload)?,
+        s: U256::decode(&mut payload)?,
+    };
+    expect_empty(payload)?;
+    Ok(tx)
+}
+
+impl Decodable for Authorization7702 {
+    fn decode(buf: &mut &[u8]) -> alloy_rlp::Result<Self> {
+        let mut payload = Header::decode_bytes(buf, true)?;
+        let auth = Self {
+            chain_id: u64::decode(&mut payload)?,
+            address: Address::decode(&mut payload)?,
+            nonce: U256::decode(&mut payload)?,
+            y_parity: u8::decode(&mut payload)?,
+            r: U256::decode(&mut payload)?,
+            s: U256::decode(&mut payload)?,
+        };
+        if !payload.is_empty() {
+            return Err(alloy_rlp::Error::ListLengthMismatch {
+                expected: 0,
+                got: payload.len(),
+            });
+        }
+        Ok(auth)
+    }
+}
+
+/// Decodes one signed authorization, `rlp([chain_id, address, nonce,
+/// y_parity, r, s])`, as `sign-authorization` prints it. Trailing bytes
+/// and high-s signatures are errors.
+pub fn decode_authorization(mut raw: &[u8]) -> Result<Authorization7702, TxError> {
+    let auth = Authorization7702::decode(&mut raw)?;
+    expect_empty(raw)?;
+    if auth.s > SECP256K1N_HALF {
+        return Err(TxError::Decode(
+            "authorization has a high-s signature (EIP-2)".to_string(),
+        ));
+    }
+    Ok(auth)
+}
+
+fn decode_authorization_list(buf: &mut &[u8]) -> Result<Vec<Authorization7702>, TxError> {
+    let mut payload = list_payload(buf)?;
+    let mut list = Vec::new();
+    while !payload.is_empty() {
+        list.push(Authorization7702::decode(&mut payload)?);
+    }
+    Ok(list)
+}
+
+fn decode_access_list(buf: &mut &[u8]) -> Result<Vec<(Address, Vec<U256>)>, TxError> {
+    let mut payload = list_payload(buf)?;
+    let mut list = Vec::new();
+    while !payload.is_empty() {
+        let mut item = list_payload(&mut payload)?;
+        let address = Address::decode(&mut item)?;
+
+        // storage keys are fixed 32-byte strings, not minimal integers
+        let mut keys_payload = list_payload(&mut item)?;
+        let mut keys = Vec::new();
+        while !keys_payload.is_empty() {
+            keys.push(U256::from_be_bytes(B256::decode(&mut keys_payload)?.0));
+        }
+        expect_empty(item)?;
+        list.push((address, keys));
+    }
+    Ok(list)
+}
+
+/// `to` is an empty string for contract creation, otherwise a 20-byte address.
+fn decode_to(buf: &mut &[u8]) -> Result<Option<Address>, TxError> {
+    if buf.first() == Some(&EMPTY_STRING_CODE) {
+        *buf = &buf[1..];
+        return Ok(None);
+    }
+    Ok(Some(Address::decode(buf)?))
+}
+
+fn list_payload<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8], TxError> {
+    Ok(Header::decode_bytes(buf, true)?)
+}
+
+fn expect_empty(rest: &[u8]) -> Result<(), TxError> {
+    if rest.is_empty() {
+        Ok(())
+    } else {
+        Err(TxError::Decode(format!(
+            "{} unexpected trailing bytes",
+            rest.len()
+        )))
+    }
+}
@@ -0,0 +1,34 @@
+//! Contract creation: creation code plus ABI-encoded constructor arguments.
+
+use alloy::{
+    dyn_abi::{DynSolType, DynSolValue},
+    primitives::Address,
+};
+
+use crate::error::TxError;
+
+/// The data of a creation tx: `bytecode` with the constructor arguments
+/// ABI-encoded after it, the way `abi.encode(args...)` would lay them out.
+pub fn deploy_calldata(bytecode: &[u8], constructor_args: &[DynSolValue]) -> Vec<u8> {
+    let mut calldata = bytecode.to_vec();
+    calldata.extend(DynSolValue::Tuple(constructor_args.to_vec()).abi_encode_params());
+    calldata
+}
+
+/// Parses one `--args` value, written `<type>:<value>` (`uint256:42`,
+/// `address:0x..`, `string:hello`).
+pub fn parse_constructor_arg(arg: &str) -> Result<DynSolValue, TxError> {
+    let invalid = |msg: String| TxError::Config(format!("constructor arg {arg:?}: {msg}"));
+    let (ty, value) = arg
+        .split_once(':')
+        .ok_or_else(|| invalid("expected `<type>:<value>`".to_string()))?;
+    let ty = DynSolType::parse(ty.trim()).map_err(|err| invalid(err.to_string()))?;
+    ty.coerce_str(value.trim())
+        .map_err(|err| invalid(err.to_string()))
+}
+
+/// Where a creation tx from `sender` with `nonce` puts the contract:
+/// the last 20 bytes of `keccak256(rlp([sender, nonce]))`.
+pub fn create_address(sender: Address, nonce: u64) -> Address {
+    sender.create(nonce)
+}
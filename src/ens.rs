@@ -0,0 +1,91 @@
+//! Turning names such as `counter.eth` into addresses for `--to`.
+
+use std::future::Future;
+
+use alloy::primitives::{address, keccak256, Address, B256};
+
+use crate::{
+    abi::decode_address,
+    error::TxError,
+    hexutil::parse_address,
+    rpc::{eth_call, Provider},
+};
+
+/// The ENS registry, at the same address on mainnet and the testnets.
+pub const ENS_REGISTRY: Address = address!("00000000000C2E074eC69A0dFb2997BA6C7d2e1e");
+
+/// Looks a name up.
+pub trait NameResolver {
+    fn resolve(&self, name: &str) -> impl Future<Output = Result<Address, TxError>> + Send;
+}
+
+/// `to` itself if it's an address, otherwise whatever `resolver` makes of
+/// it.
+pub async fn resolve_recipient(resolver: &impl NameResolver, to: &str) -> Result<Address, TxError> {
+    // names have a dot, so anything else is an address, typos included
+    match parse_address(to) {
+        Ok(addr) => Ok(addr),
+        Err(err) if !to.contains('.') => Err(err),
+        Err(_) => resolver.resolve(to).await,
+    }
+}
+
+/// The EIP-137 namehash of a dot-separated name.
+pub fn namehash(name: &str) -> B256 {
+    if name.is_empty() {
+        return B256::ZERO;
+    }
+    name.rsplit('.').fold(B256::ZERO, |node, label| {
+        keccak256([node.as_slice(), keccak256(label).as_slice()].concat())
+    })
+}
+
+/// Resolves through the ENS registry: asks it for the name's resolver, then
+/// asks that resolver for the address.
+pub struct EnsResolver<'a> {
+    provider: &'a Provider,
+    registry: Address,
+}
+
+impl<'a> EnsResolver<'a> {
+    pub fn new(provider: &'a Provider) -> Self {
+        Self {
+            provider,
+            registry: ENS_REGISTRY,
+        }
+    }
+
+    /// For chains with their own deployment of the registry.
+    pub fn with_registry(mut self, registry: Address) -> Self {
+        self.registry = registry;
+        self
+    }
+
+    /// `<selector>(bytes32 node)` on `contract`, answered with an address.
+    async fn call_with_node(
+        &self,
+        contract: Address,
+        signature: &str,
+        node: B256,
+    ) -> Result<Address, TxError> {
+        let data = [&keccak256(signature)[..4], node.as_slice()].concat();
+        decode_address(&eth_call(self.provider, contract, &data).await?)
+    }
+}
+
+impl NameResolver for EnsResolver<'_> {
+    async fn resolve(&self, name: &str) -> Result<Address, TxError> {
+        let node = namehash(name);
+        let resolver = self
+            .call_with_node(self.registry, "resolver(bytes32)", node)
+            .await?;
+        if resolver.is_zero() {
+            return Err(TxError::Config(format!("{name} has no ENS resolver")));
+        }
+        let addr = self.call_with_node(resolver, "addr(bytes32)", node).await?;
+        if addr.is_zero() {
+            return Err(TxError::Config(format!("{name} has no address set")));
+        }
+        Ok(addr)
+    }
+}
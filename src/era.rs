@@ -0,0 +1,218 @@
+//! zkSync Era EIP-712 transactions (type 0x71).
+//!
+//! Unlike the Ethereum types these aren't signed over their RLP: the
+//! signature covers an EIP-712 hash of the fields, and the RLP carries the
+//! Era-only fields (`from`, gas per pubdata, paymaster) after the usual ones.
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy_rlp::{Encodable, Header, EMPTY_LIST_CODE};
+
+use crate::{
+    error::TxError,
+    signing::{eip712_hash, signature_bytes},
+    tx::{encode_to, list_buffer, to_length},
+};
+
+pub const ERA_TX_TYPE: u8 = 0x71;
+/// What the SDKs put in `gas_per_pubdata` unless told otherwise.
+pub const DEFAULT_GAS_PER_PUBDATA: u64 = 50_000;
+/// Far above any price Era has charged per pubdata byte; a limit past this
+/// is more likely a typo than intent.
+pub const MAX_GAS_PER_PUBDATA: u64 = 1_000_000;
+
+/// Rejects a `gas_per_pubdata` limit that can't work: 0 leaves nothing for
+/// the pubdata every tx publishes, and anything above
+/// [`MAX_GAS_PER_PUBDATA`] is out of bounds.
+pub fn check_gas_per_pubdata(gas_per_pubdata: u64) -> Result<(), TxError> {
+    if gas_per_pubdata == 0 || gas_per_pubdata > MAX_GAS_PER_PUBDATA {
+        return Err(TxError::InvalidFees(format!(
+            "gas per pubdata {gas_per_pubdata} is outside 1..={MAX_GAS_PER_PUBDATA}"
+        )));
+    }
+    Ok(())
+}
+
+/// The paymaster that pays the fee, and the input it is called with.
+#[derive(Debug, Clone)]
+pub struct PaymasterParams {
+    pub paymaster: Address,
+    pub input: Vec<u8>,
+}
+
+impl PaymasterParams {
+    /// The `general(bytes)` flow: the paymaster pays without taking anything.
+    pub fn general(paymaster: Address, inner_input: &[u8]) -> Self {
+        let mut input = selector("general(bytes)").to_vec();
+        // a single dynamic argument: its offset, then length-prefixed data
+        input.extend_from_slice(&U256::from(32).to_be_bytes::<32>());
+        encode_bytes(inner_input, &mut input);
+        Self { paymaster, input }
+    }
+
+    /// The `approvalBased(address,uint256,bytes)` flow: the paymaster is
+    /// allowed to pull at least `min_allowance` of `token` from the sender.
+    pub fn approval_based(
+        paymaster: Address,
+        token: Address,
+        min_allowance: U256,
+        inner_input: &[u8],
+    ) -> Self {
+        let mut input = selector("approvalBased(address,uint256,bytes)").to_vec();
+        input.extend_from_slice(token.into_word().as_slice());
+        input.extend_from_slice(&min_allowance.to_be_bytes::<32>());
+        input.extend_from_slice(&U256::from(3 * 32).to_be_bytes::<32>());
+        encode_bytes(inner_input, &mut input);
+        Self { paymaster, input }
+    }
+
+    fn payload_length(&self) -> usize {
+        self.paymaster.length() + self.input.as_slice().length()
+    }
+}
+
+impl Encodable for PaymasterParams {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        self.paymaster.encode(out);
+        self.input.as_slice().encode(out);
+    }
+
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        alloy_rlp::length_of_length(payload_length) + payload_length
+    }
+}
+
+fn selector(signature: &str) -> [u8; 4] {
+    keccak256(signature)[..4].try_into().unwrap()
+}
+
+/// ABI `bytes` tail: length word, then the data right-padded to 32 bytes.
+fn encode_bytes(data: &[u8], out: &mut Vec<u8>) {
+    out.extend_from_slice(&U256::from(data.len()).to_be_bytes::<32>());
+    out.extend_from_slice(data);
+    out.resize(out.len() + (32 - data.len() % 32) % 32, 0);
+}
+
+#[derive(Debug)]
+pub struct EraTransaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    /// Era txs name their sender explicitly.
+    pub from: Address,
+    pub gas_per_pubdata: U256,
+    pub paymaster_params: Option<PaymasterParams>,
+
+    // Signature
+    pub y_parity: u8,
+    pub r: U256,
+    pub s: U256,
+}
+
+impl EraTransaction {
+    /// The EIP-712 hash the sender signs, in the `zkSync` v2 domain.
+    pub fn signing_hash(&self) -> B256 {
+        let paymaster = self.paymaster_params.as_ref();
+        let fields = [
+            keccak256(
+                "Transaction(uint256 txType,uint256 from,uint256 to,uint256 gasLimit,\
+                 uint256 gasPerPubdataByteLimit,uint256 maxFeePerGas,uint256 maxPriorityFeePerGas,\
+                 uint256 paymaster,uint256 nonce,uint256 value,bytes data,bytes32[] factoryDeps,\
+                 bytes paymasterInput)",
+            ),
+            U256::from(ERA_TX_TYPE).into(),
+            self.from.into_word(),
+            self.to.unwrap_or_default().into_word(),
+            self.gas_limit.into(),
+            self.gas_per_pubdata.into(),
+            self.max_fee_per_gas.into(),
+            self.max_priority_fee_per_gas.into(),
+            paymaster
+                .map(|p| p.paymaster)
+                .unwrap_or_default()
+                .into_word(),
+            self.nonce.into(),
+            self.value.into(),
+            keccak256(&self.data),
+            // factory deps aren't supported, so their hash list is empty
+            keccak256(b""),
+            keccak256(paymaster.map(|p| p.input.as_slice()).unwrap_or_default()),
+        ];
+        let struct_hash = keccak256(fields.concat());
+
+        let domain_separator = keccak256(
+            [
+                keccak256("EIP712Domain(string name,string version,uint256 chainId)"),
+                keccak256("zkSync"),
+                keccak256("2"),
+                U256::from(self.chain_id).into(),
+            ]
+            .concat(),
+        );
+        eip712_hash(domain_separator, struct_hash)
+    }
+
+    fn payload_length(&self, signature: &[u8; 65]) -> usize {
+        self.nonce.length()
+            + self.max_priority_fee_per_gas.length()
+            + self.max_fee_per_gas.length()
+            + self.gas_limit.length()
+            + to_length(self.to)
+            + self.value.length()
+            + self.data.as_slice().length()
+            + self.y_parity.length()
+            + self.r.length()
+            + self.s.length()
+            + self.chain_id.length()
+            + self.from.length()
+            + self.gas_per_pubdata.length()
+            + 1 // empty factory deps
+            + signature.as_slice().length()
+            + self
+                .paymaster_params
+                .as_ref()
+                .map_or(1, |params| params.length())
+    }
+
+    /// Fails on a `y_parity` other than 0 or 1, which the packed signature
+    /// can't carry.
+    pub fn rlp_encode_signed(&self) -> Result<Vec<u8>, TxError> {
+        // Era wants the packed signature in its custom signature slot too
+        let signature = signature_bytes(self.r, self.s, self.y_parity)?;
+        let mut buffer = list_buffer(Some(ERA_TX_TYPE), self.payload_length(&signature));
+
+        self.nonce.encode(&mut buffer);
+        self.max_priority_fee_per_gas.encode(&mut buffer);
+        self.max_fee_per_gas.encode(&mut buffer);
+        self.gas_limit.encode(&mut buffer);
+        encode_to(self.to, &mut buffer);
+        self.value.encode(&mut buffer);
+        self.data.as_slice().encode(&mut buffer);
+        self.y_parity.encode(&mut buffer);
+        self.r.encode(&mut buffer);
+        self.s.encode(&mut buffer);
+
+        // the Era-only fields
+        self.chain_id.encode(&mut buffer);
+        self.from.encode(&mut buffer);
+        self.gas_per_pubdata.encode(&mut buffer);
+        // factory deps (contract deployment) - not supported
+        buffer.push(EMPTY_LIST_CODE);
+        signature.as_slice().encode(&mut buffer);
+        match &self.paymaster_params {
+            Some(params) => params.encode(&mut buffer),
+            None => buffer.push(EMPTY_LIST_CODE),
+        }
+        Ok(buffer)
+    }
+}
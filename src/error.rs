@@ -0,0 +1,100 @@
+use std::fmt;
+
+use alloy::primitives::{Address, B256};
+
+/// Errors produced while building, signing or submitting transactions.
+#[derive(Debug)]
+pub enum TxError {
+    /// Transport-level failure (connection refused, bad body, ...).
+    Http(reqwest::Error),
+    /// The node, or something in front of it, refused the request.
+    Rpc(String),
+    /// The bytes handed to the decoder aren't a well-formed signed transaction.
+    Decode(String),
+    /// A signature couldn't be parsed or recovered.
+    Signature(secp256k1::Error),
+    /// An external signer (KMS, hardware wallet) failed or misbehaved.
+    Signer(String),
+    /// The config file couldn't be read or parsed.
+    Config(String),
+    /// Input that should be hex isn't.
+    InvalidHex(String),
+    /// Input that should be an address isn't, or its EIP-55 checksum is off.
+    InvalidAddress(String),
+    /// A private key couldn't be read or isn't a valid secp256k1 key.
+    InvalidKey(String),
+    /// The fee fields can't be valid for any node.
+    InvalidFees(String),
+    /// We were about to sign for a different chain than the node is on.
+    ChainIdMismatch { node: u64, signing: u64 },
+    /// Gave up waiting for a receipt; the tx may still be mined later.
+    Timeout { tx_hash: B256 },
+    /// The authority still didn't delegate to `delegate` when we stopped looking.
+    DelegationTimeout {
+        authority: Address,
+        delegate: Address,
+    },
+    /// The node no longer knows the tx: it left the mempool without being mined.
+    Dropped { tx_hash: B256 },
+    /// A 7702 tx with no authorizations, which EIP-7702 makes invalid.
+    EmptyAuthorizationList,
+}
+
+impl fmt::Display for TxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TxError::Http(err) => write!(f, "http error: {err}"),
+            TxError::Rpc(msg) => write!(f, "rpc error: {msg}"),
+            TxError::Decode(msg) => write!(f, "decode error: {msg}"),
+            TxError::Signature(err) => write!(f, "signature error: {err}"),
+            TxError::Signer(msg) => write!(f, "signer error: {msg}"),
+            TxError::Config(msg) => write!(f, "config error: {msg}"),
+            TxError::InvalidHex(msg) => write!(f, "invalid hex: {msg}"),
+            TxError::InvalidAddress(msg) => write!(f, "invalid address: {msg}"),
+            TxError::InvalidKey(msg) => write!(f, "invalid private key: {msg}"),
+            TxError::InvalidFees(msg) => write!(f, "invalid fees: {msg}"),
+            TxError::ChainIdMismatch { node, signing } => write!(
+                f,
+                "node reports chain id {node} but the tx would be signed for {signing}"
+            ),
+            TxError::Timeout { tx_hash } => write!(
+                f,
+                "timed out waiting for {tx_hash}; it may still be mined, check again later"
+            ),
+            TxError::DelegationTimeout {
+                authority,
+                delegate,
+            } => write!(
+                f,
+                "timed out waiting for {authority} to delegate to {delegate}"
+            ),
+            TxError::Dropped { tx_hash } => write!(
+                f,
+                "{tx_hash} was dropped from the mempool; it won't be mined unless sent again"
+            ),
+            TxError::EmptyAuthorizationList => {
+                write!(f, "a 7702 tx needs at least one authorization")
+            }
+        }
+    }
+}
+
+impl std::error::Error for TxError {}
+
+impl From<reqwest::Error> for TxError {
+    fn from(err: reqwest::Error) -> Self {
+        TxError::Http(err)
+    }
+}
+
+impl From<alloy_rlp::Error> for TxError {
+    fn from(err: alloy_rlp::Error) -> Self {
+        TxError::Decode(err.to_string())
+    }
+}
+
+impl From<secp256k1::Error> for TxError {
+    fn from(err: secp256k1::Error) -> Self {
+        TxError::Signature(err)
+    }
+}
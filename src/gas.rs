@@ -0,0 +1,227 @@
+use std::{fmt, str::FromStr};
+
+use alloy::primitives::{Address, U256};
+
+use crate::{error::TxError, units::format_ether};
+
+/// Base cost of every transaction.
+pub const TX_BASE_GAS: u64 = 21_000;
+/// Calldata cost per byte (EIP-2028).
+const ZERO_BYTE_GAS: u64 = 4;
+const NONZERO_BYTE_GAS: u64 = 16;
+/// EIP-7702 `PER_EMPTY_ACCOUNT_COST`, charged up front for every authorization.
+pub const PER_AUTHORIZATION_GAS: u64 = 25_000;
+/// EIP-2930 cost of every access list address and storage key.
+const ACCESS_LIST_ADDRESS_GAS: u64 = 2_400;
+const ACCESS_LIST_STORAGE_KEY_GAS: u64 = 1_900;
+
+/// Intrinsic gas of a plain call: the base cost, calldata, and any
+/// authorizations. Execution in the callee is not included.
+pub fn intrinsic_gas(data: &[u8], authorization_count: usize) -> u64 {
+    let data_gas: u64 = data
+        .iter()
+        .map(|b| {
+            if *b == 0 {
+                ZERO_BYTE_GAS
+            } else {
+                NONZERO_BYTE_GAS
+            }
+        })
+        .sum();
+    TX_BASE_GAS + data_gas + PER_AUTHORIZATION_GAS * authorization_count as u64
+}
+
+/// What a creation tx pays on top of a call's intrinsic gas: the CREATE
+/// itself, and EIP-3860's charge per 32-byte word of initcode.
+const CREATE_GAS: u64 = 32_000;
+const INITCODE_WORD_GAS: u64 = 2;
+
+/// Intrinsic gas of a creation tx carrying `initcode`. Running the
+/// constructor and storing the code come on top.
+pub fn creation_intrinsic_gas(initcode: &[u8]) -> u64 {
+    let words = initcode.len().div_ceil(32) as u64;
+    intrinsic_gas(initcode, 0) + CREATE_GAS + INITCODE_WORD_GAS * words
+}
+
+/// Up-front cost of pre-declaring an access list.
+pub fn access_list_gas(access_list: &[(Address, Vec<U256>)]) -> u64 {
+    access_list
+        .iter()
+        .map(|(_, keys)| ACCESS_LIST_ADDRESS_GAS + ACCESS_LIST_STORAGE_KEY_GAS * keys.len() as u64)
+        .sum()
+}
+
+/// Rejects fee caps every node would refuse anyway: a priority fee above the
+/// max fee, or a max fee below the current base fee when that is known.
+pub fn check_fees(
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    base_fee: Option<U256>,
+) -> Result<(), TxError> {
+    if max_priority_fee_per_gas > max_fee_per_gas {
+        return Err(TxError::InvalidFees(format!(
+            "max priority fee per gas {max_priority_fee_per_gas} is above max fee per gas {max_fee_per_gas}"
+        )));
+    }
+    if let Some(base_fee) = base_fee {
+        if max_fee_per_gas < base_fee {
+            return Err(TxError::InvalidFees(format!(
+                "max fee per gas {max_fee_per_gas} is below the base fee {base_fee}"
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// The highest base fee the next block can have: EIP-1559 lets it rise by
+/// at most an eighth per block.
+pub fn next_base_fee_ceiling(base_fee: U256) -> U256 {
+    apply_bps(base_fee, 1_250)
+}
+
+/// A warning for a max fee that covers the current base fee but not the
+/// highest one the next block may have, so the tx could sit in the pool
+/// until the base fee drops back.
+pub fn base_fee_headroom_warning(max_fee_per_gas: U256, base_fee: U256) -> Option<String> {
+    let ceiling = next_base_fee_ceiling(base_fee);
+    (max_fee_per_gas < ceiling).then(|| {
+        format!(
+            "max fee per gas {max_fee_per_gas} is below {ceiling}, the most the base fee {base_fee} can rise to by the next block"
+        )
+    })
+}
+
+/// A safety factor for estimated gas, kept in thousandths so applying it is
+/// exact integer math.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasMultiplier {
+    thousandths: u64,
+}
+
+impl GasMultiplier {
+    /// `gas * multiplier`, rounded up.
+    pub fn apply(self, gas: U256) -> U256 {
+        (gas * U256::from(self.thousandths)).div_ceil(U256::from(1000))
+    }
+}
+
+impl FromStr for GasMultiplier {
+    type Err = String;
+
+    /// Parses a decimal such as `1.2`, with at most three fractional digits.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || format!("invalid gas multiplier {s}, expected e.g. 1.2");
+        let (whole, fraction) = s.split_once('.').unwrap_or((s, ""));
+        if fraction.len() > 3 || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let whole: u64 = whole.parse().map_err(|_| invalid())?;
+        let fraction: u64 = format!("{fraction:0<3}").parse().map_err(|_| invalid())?;
+        let thousandths = whole
+            .checked_mul(1000)
+            .and_then(|t| t.checked_add(fraction))
+            .ok_or_else(invalid)?;
+        if thousandths < 1000 {
+            return Err(format!("gas multiplier {s} is below 1"));
+        }
+        Ok(Self { thousandths })
+    }
+}
+
+/// The most a tx can cost the sender, `value + gas_limit * max_fee`, or
+/// `None` if that doesn't fit in 256 bits.
+pub fn total_max_cost(gas_limit: U256, max_fee: U256, value: U256) -> Option<U256> {
+    gas_limit.checked_mul(max_fee)?.checked_add(value)
+}
+
+/// `v` raised by `bps` basis points, so 1_250 is a 12.5% bump. Rounds up,
+/// so a bumped fee is never short of the percentage a node asks for;
+/// saturates at `U256::MAX` only when the result doesn't fit.
+pub fn apply_bps(v: U256, bps: u64) -> U256 {
+    let (bps, scale) = (U256::from(bps), U256::from(10_000));
+    // v = q * 10_000 + r, so only a result that doesn't fit can overflow
+    let whole = (v / scale).saturating_mul(bps);
+    let rest = (v % scale * bps).div_ceil(scale);
+    v.saturating_add(whole).saturating_add(rest)
+}
+
+/// What a mined 1559 or 7702 tx paid per gas: the base fee plus the tip,
+/// unless that is more than the sender's max fee.
+pub fn effective_gas_price(base_fee: U256, max_fee: U256, max_priority: U256) -> U256 {
+    base_fee.saturating_add(max_priority).min(max_fee)
+}
+
+/// Caps a per-gas fee at `cap`, if there is one.
+pub fn clamp_fee(fee: U256, cap: Option<U256>) -> U256 {
+    cap.map_or(fee, |cap| fee.min(cap))
+}
+
+/// What a tx is expected to cost, for `cost-estimate`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CostEstimate {
+    pub gas: u64,
+    /// The part of `gas` the authorizations cost up front.
+    pub authorization_gas: u64,
+    pub authorization_count: usize,
+    pub max_fee: U256,
+    /// What each unit of gas costs if the base fee stays where it is.
+    pub price: U256,
+    /// `gas * max_fee + value`, the most the tx can cost; `None` if that
+    /// overflows.
+    pub worst_case: Option<U256>,
+    /// `gas * price + value`.
+    pub expected: Option<U256>,
+}
+
+/// Prices `gas` for a tx with `authorization_count` authorizations, at
+/// worst and as expected.
+pub fn estimate_cost(
+    gas: u64,
+    authorization_count: usize,
+    value: U256,
+    max_fee: U256,
+    price: U256,
+) -> CostEstimate {
+    let gas_units = U256::from(gas);
+    CostEstimate {
+        gas,
+        authorization_gas: PER_AUTHORIZATION_GAS * authorization_count as u64,
+        authorization_count,
+        max_fee,
+        price,
+        worst_case: total_max_cost(gas_units, max_fee, value),
+        expected: total_max_cost(gas_units, price, value),
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cost = |cost: Option<U256>| match cost {
+            Some(cost) => format!("{cost} wei ({} ETH)", format_ether(cost)),
+            None => "overflows 256 bits".to_string(),
+        };
+        writeln!(f, "Estimated gas: {}", self.gas)?;
+        writeln!(
+            f,
+            "  authorizations: {} ({} x {PER_AUTHORIZATION_GAS})",
+            self.authorization_gas, self.authorization_count
+        )?;
+        writeln!(
+            f,
+            "  the rest:       {}",
+            self.gas.saturating_sub(self.authorization_gas)
+        )?;
+        writeln!(
+            f,
+            "Worst case: {} at the max fee of {} wei",
+            cost(self.worst_case),
+            self.max_fee
+        )?;
+        write!(
+            f,
+            "Expected:   {} at {} wei per gas",
+            cost(self.expected),
+            self.price
+        )
+    }
+}
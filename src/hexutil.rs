@@ -0,0 +1,77 @@
+//! Hex input from the command line, config files and nodes, and the
+//! encodings a signed tx can be printed in.
+
+use alloy::primitives::Address;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use hex::FromHexError;
+
+use crate::error::TxError;
+
+/// Decodes hex with or without a `0x` prefix.
+///
+/// An empty string (or a bare `0x`) is no bytes. Errors say what is wrong
+/// without repeating the input, since it may be a private key.
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, TxError> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    hex::decode(digits).map_err(|err| {
+        TxError::InvalidHex(match err {
+            FromHexError::OddLength => format!("odd number of digits ({})", digits.len()),
+            FromHexError::InvalidHexCharacter { index, .. } => {
+                format!("character {} is not a hex digit", index + 1)
+            }
+            other => other.to_string(),
+        })
+    })
+}
+
+/// Parses an address with or without a `0x` prefix, in any case.
+///
+/// Mixed case means the address carries an EIP-55 checksum, so a typo in
+/// it is caught rather than sent to; all-lowercase and all-uppercase input
+/// is taken as is.
+pub fn parse_address(s: &str) -> Result<Address, TxError> {
+    let digits = s
+        .strip_prefix("0x")
+        .or_else(|| s.strip_prefix("0X"))
+        .unwrap_or(s);
+    if digits.len() != 40 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(TxError::InvalidAddress(format!(
+            "{s:?} is not 40 hex digits"
+        )));
+    }
+    let address = Address::from_slice(&hex::decode(digits).expect("checked above"));
+    let checksummed = digits.chars().any(|c| c.is_ascii_uppercase())
+        && digits.chars().any(|c| c.is_ascii_lowercase());
+    if checksummed && address.to_checksum(None)[2..] != *digits {
+        return Err(TxError::InvalidAddress(format!(
+            "bad EIP-55 checksum in {s}"
+        )));
+    }
+    Ok(address)
+}
+
+/// How `--output-format` prints a signed tx. Every format carries the
+/// same bytes, type byte included.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputFormat {
+    /// `0x`-prefixed hex
+    #[default]
+    Hex,
+    /// Hex without the `0x`
+    HexNoPrefix,
+    /// Standard, padded base64
+    Base64,
+}
+
+impl OutputFormat {
+    pub fn render(self, bytes: &[u8]) -> String {
+        match self {
+            OutputFormat::Hex => format!("0x{}", hex::encode(bytes)),
+            OutputFormat::HexNoPrefix => hex::encode(bytes),
+            OutputFormat::Base64 => STANDARD.encode(bytes),
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use std::io::{self, BufRead, Write};
+
+use secp256k1::SecretKey;
+
+use crate::{error::TxError, hexutil::parse_hex};
+
+/// Parses a hex private key as typed or pasted: surrounding whitespace and
+/// the `0x` prefix are optional. Errors never echo the input back.
+pub fn parse_private_key(input: &str) -> Result<SecretKey, TxError> {
+    let bytes =
+        parse_hex(input.trim()).map_err(|_| TxError::InvalidKey("not valid hex".to_string()))?;
+    SecretKey::from_slice(&bytes)
+        .map_err(|_| TxError::InvalidKey("not a valid secp256k1 private key".to_string()))
+}
+
+/// Asks for the private key on stderr and reads it from stdin, with terminal
+/// echo turned off, so it never ends up in shell history, argv or scrollback.
+pub fn prompt_private_key() -> Result<SecretKey, TxError> {
+    eprint!("Private key: ");
+    io::stderr().flush().ok();
+    let line = read_line_hidden()
+        .map_err(|err| TxError::InvalidKey(format!("failed to read key: {err}")))?;
+    parse_private_key(&line)
+}
+
+#[cfg(unix)]
+fn read_line_hidden() -> io::Result<String> {
+    use std::os::fd::AsRawFd;
+
+    let fd = io::stdin().as_raw_fd();
+    // piped input has no echo to turn off
+    if unsafe { libc::isatty(fd) } != 1 {
+        return read_line();
+    }
+
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut original) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let mut hidden = original;
+    hidden.c_lflag &= !libc::ECHO;
+    // still echo the newline, so the next output starts on a fresh line
+    hidden.c_lflag |= libc::ECHONL;
+    if unsafe { libc::tcsetattr(fd, libc::TCSANOW, &hidden) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let line = read_line();
+    unsafe { libc::tcsetattr(fd, libc::TCSANOW, &original) };
+    line
+}
+
+#[cfg(not(unix))]
+fn read_line_hidden() -> io::Result<String> {
+    read_line()
+}
+
+fn read_line() -> io::Result<String> {
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line)?;
+    Ok(line)
+}
@@ -0,0 +1,258 @@
+//! Signing with a secp256k1 key that never leaves AWS KMS.
+//!
+//! The key must be an `ECC_SECG_P256K1` key with `SIGN_VERIFY` usage. KMS
+//! signs the tx hash as a pre-hashed digest and returns a plain DER
+//! signature, so the recovery id is found by trying both parities against
+//! the address derived from the key's public half.
+
+use std::{
+    future::Future,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use alloy::primitives::{Address, B256, U256};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use reqwest::Client;
+use secp256k1::{ecdsa::Signature, PublicKey};
+use serde::Deserialize;
+use serde_json::json;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    error::TxError,
+    signing::{address_from_pubkey, bytes32_to_u256, find_recovery_id, Signer},
+};
+
+/// DER `SubjectPublicKeyInfo` header for an uncompressed secp256k1 key; the
+/// 65-byte `04 || x || y` point follows it.
+const SECP256K1_SPKI_PREFIX: [u8; 23] = [
+    0x30, 0x56, 0x30, 0x10, 0x06, 0x07, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01, 0x06, 0x05, 0x2b,
+    0x81, 0x04, 0x00, 0x0a, 0x03, 0x42, 0x00,
+];
+
+/// The two KMS calls the signer needs, so tests can stand in for AWS.
+pub trait KmsClient {
+    /// `GetPublicKey`: the DER `SubjectPublicKeyInfo` of the key.
+    fn get_public_key(&self, key_id: &str)
+        -> impl Future<Output = Result<Vec<u8>, TxError>> + Send;
+
+    /// `Sign` with `MessageType=DIGEST` and `ECDSA_SHA_256`: a DER signature
+    /// over `digest` as given, without hashing it again.
+    fn sign_digest(
+        &self,
+        key_id: &str,
+        digest: &B256,
+    ) -> impl Future<Output = Result<Vec<u8>, TxError>> + Send;
+}
+
+pub struct KmsSigner<C> {
+    client: C,
+    key_id: String,
+    address: Address,
+}
+
+impl<C: KmsClient> KmsSigner<C> {
+    /// Looks up the key's public half to learn the address it signs for.
+    pub async fn new(client: C, key_id: impl Into<String>) -> Result<Self, TxError> {
+        let key_id = key_id.into();
+        let spki = client.get_public_key(&key_id).await?;
+        let point = spki
+            .strip_prefix(&SECP256K1_SPKI_PREFIX[..])
+            .ok_or_else(|| TxError::Signer(format!("{key_id} is not a secp256k1 key")))?;
+        let pubkey = PublicKey::from_slice(point)?;
+        Ok(Self {
+            address: address_from_pubkey(&pubkey),
+            client,
+            key_id,
+        })
+    }
+}
+
+impl<C: KmsClient + Sync> Signer for KmsSigner<C> {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<(U256, U256, u8), TxError> {
+        let der = self.client.sign_digest(&self.key_id, hash).await?;
+        let mut signature = Signature::from_der(&der)?;
+        // KMS may return either s; Ethereum only accepts the low one
+        signature.normalize_s();
+        let compact = signature.serialize_compact();
+        let r = bytes32_to_u256(&compact[..32]);
+        let s = bytes32_to_u256(&compact[32..]);
+
+        let y_parity = find_recovery_id(hash, r, s, self.address).ok_or_else(|| {
+            TxError::Signer(format!("KMS signature doesn't recover to {}", self.address))
+        })?;
+        Ok((r, s, y_parity))
+    }
+}
+
+/// Talks to the KMS JSON API directly, signing requests with SigV4.
+pub struct AwsKmsClient {
+    client: Client,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    session_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PublicKeyResponse {
+    public_key: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SignResponse {
+    signature: String,
+}
+
+impl AwsKmsClient {
+    /// Reads `AWS_REGION`, `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and
+    /// the optional `AWS_SESSION_TOKEN`.
+    pub fn from_env() -> Result<Self, TxError> {
+        let var = |name: &str| {
+            std::env::var(name).map_err(|_| TxError::Signer(format!("{name} is not set")))
+        };
+        Ok(Self {
+            client: Client::new(),
+            region: var("AWS_REGION")?,
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+
+    async fn call<R: serde::de::DeserializeOwned>(
+        &self,
+        action: &str,
+        body: serde_json::Value,
+    ) -> Result<R, TxError> {
+        let host = format!("kms.{}.amazonaws.com", self.region);
+        let target = format!("TrentService.{action}");
+        let body = body.to_string();
+        let amz_date = amz_date(SystemTime::now());
+
+        let mut headers = vec![
+            ("content-type", "application/x-amz-json-1.1".to_string()),
+            ("host", host.clone()),
+            ("x-amz-date", amz_date.clone()),
+        ];
+        if let Some(token) = &self.session_token {
+            headers.push(("x-amz-security-token", token.clone()));
+        }
+        headers.push(("x-amz-target", target.clone()));
+        let authorization = self.authorization(&headers, &amz_date, &body);
+
+        let mut req = self.client.post(format!("https://{host}/")).body(body);
+        for (name, value) in headers.into_iter().filter(|(name, _)| *name != "host") {
+            req = req.header(name, value);
+        }
+        let resp = req.header("authorization", authorization).send().await?;
+
+        let status = resp.status();
+        if !status.is_success() {
+            let text = resp.text().await.unwrap_or_default();
+            return Err(TxError::Signer(format!(
+                "KMS {action} failed with HTTP {status}: {text}"
+            )));
+        }
+        Ok(resp.json().await?)
+    }
+
+    /// SigV4 `Authorization` header; `headers` must be sorted by name.
+    fn authorization(&self, headers: &[(&str, String)], amz_date: &str, body: &str) -> String {
+        let date = &amz_date[..8];
+        let scope = format!("{date}/{}/kms/aws4_request", self.region);
+        let signed_headers = headers
+            .iter()
+            .map(|(name, _)| *name)
+            .collect::<Vec<_>>()
+            .join(";");
+        let canonical_headers: String = headers
+            .iter()
+            .map(|(name, value)| format!("{name}:{}\n", value.trim()))
+            .collect();
+        let canonical_request = format!(
+            "POST\n/\n\n{canonical_headers}\n{signed_headers}\n{}",
+            hex::encode(Sha256::digest(body))
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{amz_date}\n{scope}\n{}",
+            hex::encode(Sha256::digest(canonical_request))
+        );
+
+        let key = [date, self.region.as_str(), "kms", "aws4_request"]
+            .iter()
+            .fold(
+                format!("AWS4{}", self.secret_access_key).into_bytes(),
+                |key, part| hmac_sha256(&key, part.as_bytes()),
+            );
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{scope}, SignedHeaders={signed_headers}, Signature={signature}",
+            self.access_key_id
+        )
+    }
+}
+
+impl KmsClient for AwsKmsClient {
+    async fn get_public_key(&self, key_id: &str) -> Result<Vec<u8>, TxError> {
+        let resp: PublicKeyResponse = self
+            .call("GetPublicKey", json!({ "KeyId": key_id }))
+            .await?;
+        decode_base64(&resp.public_key)
+    }
+
+    async fn sign_digest(&self, key_id: &str, digest: &B256) -> Result<Vec<u8>, TxError> {
+        let body = json!({
+            "KeyId": key_id,
+            "Message": BASE64.encode(digest),
+            "MessageType": "DIGEST",
+            "SigningAlgorithm": "ECDSA_SHA_256",
+        });
+        let resp: SignResponse = self.call("Sign", body).await?;
+        decode_base64(&resp.signature)
+    }
+}
+
+fn decode_base64(value: &str) -> Result<Vec<u8>, TxError> {
+    BASE64
+        .decode(value)
+        .map_err(|err| TxError::Signer(format!("invalid base64 from KMS: {err}")))
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// `YYYYMMDD'T'HHMMSS'Z'` in UTC.
+fn amz_date(now: SystemTime) -> String {
+    let secs = now.duration_since(UNIX_EPOCH).unwrap_or_default().as_secs();
+    let (days, rem) = (secs / 86_400, secs % 86_400);
+
+    // days since 1970-01-01 to a civil date (Howard Hinnant's algorithm)
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!(
+        "{year:04}{month:02}{day:02}T{:02}{:02}{:02}Z",
+        rem / 3_600,
+        rem % 3_600 / 60,
+        rem % 60
+    )
+}
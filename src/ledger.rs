@@ -0,0 +1,192 @@
+//! Signing with the Ethereum app on a Ledger device.
+//!
+//! Only the APDU layer lives here; the bytes travel over a
+//! [`LedgerTransport`] the caller supplies (USB HID, a Speculos emulator,
+//! ...). The app never signs a bare hash, so [`LedgerSigner`] signs through
+//! [`Signer::sign_transaction`] and hands the device the whole unsigned tx.
+
+use std::future::Future;
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use secp256k1::PublicKey;
+
+use crate::{
+    error::TxError,
+    signing::{address_from_pubkey, bytes32_to_u256, find_recovery_id, Signer},
+};
+
+const CLA: u8 = 0xe0;
+const INS_GET_ADDRESS: u8 = 0x02;
+const INS_SIGN_TRANSACTION: u8 = 0x04;
+/// P1 of the first `SIGN_TRANSACTION` chunk, and of every later one.
+const P1_FIRST_CHUNK: u8 = 0x00;
+const P1_MORE_CHUNKS: u8 = 0x80;
+/// An APDU carries at most this many data bytes.
+const MAX_CHUNK: usize = 255;
+const SW_OK: u16 = 0x9000;
+
+/// `m/44'/60'/0'/0/0`, the first Ledger Live account.
+pub const DEFAULT_PATH: &str = "m/44'/60'/0'/0/0";
+const HARDENED: u32 = 0x8000_0000;
+
+/// Moves raw APDUs to and from the device.
+pub trait LedgerTransport {
+    /// Sends one command APDU and returns the full response, status word
+    /// included.
+    fn exchange(&self, apdu: &[u8]) -> impl Future<Output = Result<Vec<u8>, TxError>> + Send;
+}
+
+/// Parses a BIP-32 path such as `m/44'/60'/0'/0/0`.
+pub fn parse_path(path: &str) -> Result<Vec<u32>, TxError> {
+    let invalid = || TxError::Signer(format!("invalid BIP-32 path {path}"));
+    let components = path.strip_prefix("m/").ok_or_else(invalid)?;
+    let indices = components
+        .split('/')
+        .map(|part| {
+            let (index, hardened) = match part.strip_suffix('\'') {
+                Some(index) => (index, true),
+                None => (part, false),
+            };
+            let index: u32 = index.parse().map_err(|_| invalid())?;
+            if index >= HARDENED {
+                return Err(invalid());
+            }
+            Ok(if hardened { index | HARDENED } else { index })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    // the app accepts up to 10 levels
+    if indices.len() > 10 {
+        return Err(invalid());
+    }
+    Ok(indices)
+}
+
+/// `GET ETH PUBLIC ADDRESS` without on-screen confirmation or chain code.
+pub fn get_address_apdu(path: &[u32]) -> Vec<u8> {
+    apdu(INS_GET_ADDRESS, 0x00, 0x00, &encode_path(path))
+}
+
+/// `SIGN ETH TRANSACTION`, split into as many APDUs as it takes. The path
+/// goes at the front of the first chunk.
+pub fn sign_transaction_apdus(path: &[u32], unsigned_rlp: &[u8]) -> Vec<Vec<u8>> {
+    let payload = [encode_path(path), unsigned_rlp.to_vec()].concat();
+    payload
+        .chunks(MAX_CHUNK)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let p1 = if i == 0 {
+                P1_FIRST_CHUNK
+            } else {
+                P1_MORE_CHUNKS
+            };
+            apdu(INS_SIGN_TRANSACTION, p1, 0x00, chunk)
+        })
+        .collect()
+}
+
+fn encode_path(path: &[u32]) -> Vec<u8> {
+    let mut data = Vec::with_capacity(1 + 4 * path.len());
+    data.push(path.len() as u8);
+    for index in path {
+        data.extend_from_slice(&index.to_be_bytes());
+    }
+    data
+}
+
+fn apdu(ins: u8, p1: u8, p2: u8, data: &[u8]) -> Vec<u8> {
+    let mut apdu = Vec::with_capacity(5 + data.len());
+    apdu.extend_from_slice(&[CLA, ins, p1, p2, data.len() as u8]);
+    apdu.extend_from_slice(data);
+    apdu
+}
+
+/// Strips and checks the trailing status word.
+fn response_data(resp: &[u8]) -> Result<&[u8], TxError> {
+    let Some((data, sw)) = resp.split_last_chunk::<2>() else {
+        return Err(TxError::Signer("short response from Ledger".to_string()));
+    };
+    match u16::from_be_bytes(*sw) {
+        SW_OK => Ok(data),
+        0x6985 => Err(TxError::Signer("rejected on the Ledger".to_string())),
+        0x6d00 | 0x6e00 | 0x6511 => Err(TxError::Signer(
+            "open the Ethereum app on the Ledger".to_string(),
+        )),
+        0x6a80 => Err(TxError::Signer(
+            "the Ledger app refused the data (unsupported tx type?)".to_string(),
+        )),
+        sw => Err(TxError::Signer(format!(
+            "Ledger returned status 0x{sw:04x}"
+        ))),
+    }
+}
+
+pub struct LedgerSigner<T> {
+    transport: T,
+    path: Vec<u32>,
+    address: Address,
+}
+
+impl<T: LedgerTransport> LedgerSigner<T> {
+    /// Asks the device for the public key at `path` (e.g. [`DEFAULT_PATH`]).
+    pub async fn new(transport: T, path: &str) -> Result<Self, TxError> {
+        let path = parse_path(path)?;
+        let resp = transport.exchange(&get_address_apdu(&path)).await?;
+        let data = response_data(&resp)?;
+
+        // pubkey length, uncompressed pubkey, then the address as ASCII hex
+        let pubkey = match data.split_first() {
+            Some((&len, rest)) if rest.len() >= len as usize => &rest[..len as usize],
+            _ => {
+                return Err(TxError::Signer(
+                    "malformed get-address response".to_string(),
+                ))
+            }
+        };
+        let address = address_from_pubkey(&PublicKey::from_slice(pubkey)?);
+        Ok(Self {
+            transport,
+            path,
+            address,
+        })
+    }
+}
+
+impl<T: LedgerTransport + Sync> Signer for LedgerSigner<T> {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, _hash: &B256) -> Result<(U256, U256, u8), TxError> {
+        Err(TxError::Signer(
+            "the Ledger Ethereum app only signs whole transactions".to_string(),
+        ))
+    }
+
+    async fn sign_transaction(&self, unsigned_rlp: &[u8]) -> Result<(U256, U256, u8), TxError> {
+        let mut resp = Vec::new();
+        for apdu in sign_transaction_apdus(&self.path, unsigned_rlp) {
+            resp = self.transport.exchange(&apdu).await?;
+            response_data(&resp)?;
+        }
+        // only the last chunk is answered with `v || r || s`
+        let data = response_data(&resp)?;
+        if data.len() != 65 {
+            return Err(TxError::Signer(
+                "malformed signature from Ledger".to_string(),
+            ));
+        }
+        let r = bytes32_to_u256(&data[1..33]);
+        let s = bytes32_to_u256(&data[33..65]);
+
+        // `v` is 27/28, a parity, or a truncated EIP-155 value depending on the
+        // tx type and app version, so recover the parity instead of decoding it
+        let hash = keccak256(unsigned_rlp);
+        let y_parity = find_recovery_id(&hash, r, s, self.address).ok_or_else(|| {
+            TxError::Signer(format!(
+                "Ledger signature doesn't recover to {}",
+                self.address
+            ))
+        })?;
+        Ok((r, s, y_parity))
+    }
+}
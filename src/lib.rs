@@ -0,0 +1,23 @@
+pub mod abi;
+pub mod batch;
+pub mod calldata;
+pub mod config;
+pub mod counter;
+pub mod decode;
+pub mod deploy;
+pub mod ens;
+pub mod era;
+pub mod error;
+pub mod gas;
+pub mod hexutil;
+pub mod keys;
+#[cfg(feature = "kms")]
+pub mod kms;
+#[cfg(feature = "ledger")]
+pub mod ledger;
+pub mod rpc;
+pub mod safe;
+pub mod signing;
+pub mod summary;
+pub mod tx;
+pub mod units;
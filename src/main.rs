@@ -1,563 +1,1717 @@
 use alloy::{
-    hex::FromHex,
-    primitives::{keccak256, Address, U256},
+    dyn_abi::DynSolValue,
+    primitives::{address, keccak256, Address, Bytes, B256, U256},
 };
-use alloy_rlp::Encodable;
 use clap::Parser;
-use hex::decode as hex_decode;
-use reqwest::Client;
-use secp256k1::{Secp256k1, SecretKey};
-use serde::{Deserialize, Serialize};
-use std::error::Error;
+#[cfg(feature = "kms")]
+use example_7702::kms::{AwsKmsClient, KmsSigner};
+use example_7702::{
+    abi::revert_reason,
+    batch::{read_recipients, send_batch, NonceManager, Recipient, RowOutcome, TransferTemplate},
+    calldata::DataInput,
+    config::{read_env_file, Config, ConfigOverrides},
+    decode::{self, decode_authorization, decode_signed_tx, explain, SignedTx},
+    deploy::{create_address, deploy_calldata, parse_constructor_arg},
+    ens::{resolve_recipient, EnsResolver},
+    era::{check_gas_per_pubdata, EraTransaction, DEFAULT_GAS_PER_PUBDATA},
+    error::TxError,
+    gas::{
+        access_list_gas, base_fee_headroom_warning, check_fees, clamp_fee, creation_intrinsic_gas,
+        effective_gas_price, estimate_cost, intrinsic_gas, CostEstimate, GasMultiplier,
+        PER_AUTHORIZATION_GAS,
+    },
+    hexutil::{parse_address, parse_hex, OutputFormat},
+    keys::{parse_private_key, prompt_private_key},
+    rpc::{
+        code_kind, create_access_list, estimate_call_gas, estimate_deploy_gas, estimate_gas,
+        fetch_chain_id, get_balance, get_block, get_logs, get_nonce, get_nonce_at, get_nonces_at,
+        get_transaction_access_list, oracle_fees, resolve_nonce, send_raw, send_transaction,
+        simulate_bundle, strategy_fees, wait_for_delegation, wait_for_receipt, BlockTag,
+        FeeStrategy, LogFilter, NonceStrategy, Provider, Receipt, RpcErrorKind, SendStrategy,
+        TipSource, TransactionRequest, TxStatus,
+    },
+    safe::safe_message_hash,
+    signing::{
+        address_from_key, address_from_pkey, resign_with_nonce, sign_hash, signature_bytes,
+        LocalSigner, Signer,
+    },
+    summary::{describe_authorization, describe_signatures, diff_tx, summary},
+    tx::{
+        parse_access_list, Authorization7702, CodeKind, Eip1559Transaction, Eip7702Transaction,
+        LegacyTransaction, PreEip155, SignableTransaction,
+    },
+    units::{format_ether, format_gwei},
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Write},
+    future::Future,
+    path::Path,
+    sync::atomic::{AtomicBool, Ordering},
+    time::Duration,
+};
 use tokio::main;
+use tracing::{
+    field::{Field, Visit},
+    span,
+};
 
-#[derive(Serialize)]
-struct JsonRpcRequest<'a, T> {
-    jsonrpc: &'static str,
-    method: &'a str,
-    params: T,
-    id: u64,
+/// Progress for humans; with `--json` it goes to stderr instead, so stdout
+/// carries nothing but the JSON object.
+macro_rules! say {
+    ($json:expr, $($arg:tt)*) => {
+        if $json {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
 }
 
-#[derive(Deserialize)]
-struct JsonRpcResponse<T> {
-    #[serde(default)]
-    result: Option<T>,
-    #[serde(default)]
-    error: Option<serde_json::Value>,
+/// The kinds of tx `--tx-type` builds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum TxType {
+    /// Pre-1559, with a single gas price
+    Legacy,
+    /// EIP-1559 fee market tx
+    #[value(name = "1559")]
+    Eip1559,
+    /// EIP-7702 tx that delegates the authority's code
+    #[value(name = "7702")]
+    Eip7702,
+    /// zkSync Era EIP-712 tx
+    Era,
 }
 
-/// A simple “legacy” transaction container
-#[derive(Debug)]
-struct LegacyTransaction {
-    nonce: U256,
-    gas_price: U256,
-    gas_limit: U256,
-    to: Option<Address>,
-    value: U256,
-    // data must be string (if vec<u8> then it gets encoded as list..)
-    data: Vec<u8>,
-    v: u64,
-    r: U256,
-    s: U256,
+impl fmt::Display for TxType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TxType::Legacy => "legacy",
+            TxType::Eip1559 => "1559",
+            TxType::Eip7702 => "7702",
+            TxType::Era => "era",
+        })
+    }
 }
 
-/// RLP for the “unsigned” portion and for the “signed” portion
-impl LegacyTransaction {
-    fn rlp_encode_unsigned(&self, chain_id: u64) -> Vec<u8> {
-        let mut buffer = Vec::<u8>::new();
-        self.nonce.encode(&mut buffer);
-        self.gas_price.encode(&mut buffer);
-        self.gas_limit.encode(&mut buffer);
-        self.to.unwrap_or_default().encode(&mut buffer);
-        self.value.encode(&mut buffer);
-        self.data.as_slice().encode(&mut buffer);
-
-        // EIP-155 includes chain_id, 0, 0 at the end
-
-        chain_id.encode(&mut buffer);
-        0u8.encode(&mut buffer);
-        0u8.encode(&mut buffer);
-
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
+#[derive(clap::Parser)]
+#[command(subcommand_negates_reqs = true)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Which kind of tx to build
+    #[arg(short, long, value_enum)]
+    tx_type: Option<TxType>,
+
+    /// Send one 1559 transfer per `address,value` (in wei) row of this CSV instead
+    #[arg(
+        long,
+        value_name = "CSV",
+        conflicts_with_all = ["tx_type", "delegate_to", "data", "data_file", "call", "access_list", "access_list_from_tx", "auto_access_list", "auto_gas"]
+    )]
+    recipients: Option<String>,
+
+    /// With --recipients, skip rows that don't parse instead of sending nothing
+    #[arg(long, requires = "recipients")]
+    skip_bad_rows: bool,
+
+    #[arg(short, long, value_parser = parse_address_arg)]
+    delegate_to: Option<Address>,
+
+    /// Send to this address or ENS name instead of the built-in recipient
+    #[arg(long, value_name = "ADDRESS|NAME", conflicts_with = "recipients")]
+    to: Option<String>,
+
+    /// TOML file with rpc_url, chain_id, gas, [fees] and [keys]; flags below override it
+    #[arg(long)]
+    config: Option<String>,
+
+    /// Read RPC_URL and PRIVATE_KEY from a dotenv file (`.env` if no path is given)
+    #[arg(long, value_name = "PATH")]
+    env_file: Option<Option<String>>,
+
+    #[command(flatten)]
+    overrides: ConfigOverrides,
+
+    /// Calldata for the tx, as hex (the `0x` prefix is optional)
+    #[arg(long)]
+    data: Option<String>,
+
+    /// Read the calldata, as hex, from this file
+    #[arg(long, value_name = "PATH")]
+    data_file: Option<String>,
+
+    /// Call this function without arguments, e.g. 'increment()'
+    #[arg(long, value_name = "SIGNATURE")]
+    call: Option<String>,
+
+    /// Set the gas limit from the node's eth_estimateGas instead of the defaults, never below the
+    /// calldata and authorization costs
+    #[arg(long)]
+    auto_gas: bool,
+
+    /// Access list for 1559/7702 txs: `[{"address": "0x..", "storageKeys": ["0x.."]}]`, or a file with it
+    #[arg(long, value_name = "JSON|PATH", conflicts_with = "auto_access_list")]
+    access_list: Option<String>,
+
+    /// Reuse the access list of an earlier tx, given as its raw signed hex or its hash
+    #[arg(
+        long,
+        value_name = "RAW_TX|TX_HASH",
+        conflicts_with_all = ["access_list", "auto_access_list"]
+    )]
+    access_list_from_tx: Option<String>,
+
+    /// Pre-declare the slots the 7702 tx touches, from eth_createAccessList run as delegated
+    #[arg(long)]
+    auto_access_list: bool,
+
+    /// Scale the --auto-gas estimate by this factor (e.g. 1.2), rounding up
+    #[arg(long, requires = "auto_gas")]
+    gas_multiplier: Option<GasMultiplier>,
+
+    /// Ask the node for fees (fee history, priority fee, gas price) instead of using the config
+    #[arg(long)]
+    fee_oracle: bool,
+
+    /// Where --fee-oracle takes the tip from
+    #[arg(long, value_enum, default_value_t = TipSource::Node, requires = "fee_oracle")]
+    tip_source: TipSource,
+
+    /// Price the tx from the node's fee history with a preset instead of the config
+    #[arg(long, value_enum, conflicts_with = "fee_oracle")]
+    fee_strategy: Option<FeeStrategy>,
+
+    /// Never pay more than this many wei per gas (max fee, or gas price for legacy)
+    #[arg(long)]
+    max_fee_cap: Option<U256>,
+
+    /// Never tip more than this many wei per gas
+    #[arg(long)]
+    max_priority_cap: Option<U256>,
+
+    /// Sign a legacy tx without a chain id (v = 27/28), so it replays on any chain
+    #[arg(long)]
+    no_eip155: bool,
+
+    /// Era only: the most the tx pays in gas per byte of pubdata [default: 50000]
+    #[arg(long)]
+    gas_per_pubdata: Option<u64>,
+
+    /// Use this sender nonce instead of asking the node (offline signing, replacing a tx);
+    /// short for --nonce-strategy fixed:N
+    #[arg(long, conflicts_with = "nonce_strategy")]
+    nonce: Option<U256>,
+
+    /// Where the sender nonce comes from: latest, pending, txpool or fixed:N
+    #[arg(long, default_value = "pending")]
+    nonce_strategy: NonceStrategy,
+
+    /// If the node answers "nonce too low", re-sign with the pending nonce and send once more
+    #[arg(long, conflicts_with_all = ["no_eip155", "node_sign"])]
+    auto_nonce_retry: bool,
+
+    /// Use this nonce in the 7702 authorization instead of asking the node
+    #[arg(long)]
+    auth_nonce: Option<U256>,
+
+    /// Chain id to sign the 7702 authorization for; 0 (the default) makes it valid on any chain
+    #[arg(long, default_value_t = 0)]
+    auth_chain_id: u64,
+
+    /// Once the 7702 tx is mined, wait until the authority's code shows the new delegation
+    #[arg(long)]
+    verify_delegation: bool,
+
+    /// Once the 7702 tx is mined, print the events it emitted, including those the delegated
+    /// code emitted from the authority
+    #[arg(long)]
+    show_logs: bool,
+
+    /// Also include this authorization, signed elsewhere with `sign-authorization` (7702
+    /// only, repeatable)
+    #[arg(long, value_name = "RLP")]
+    authorization_hex: Vec<String>,
+
+    /// Abort instead of warning if the 7702 authority already delegates somewhere
+    #[arg(long)]
+    fail_if_delegated: bool,
+
+    /// Read the sender's private key from stdin (without echo) instead of using the built-in one
+    #[arg(long)]
+    prompt_key: bool,
+
+    /// Sign the tx with this AWS KMS key instead of a local private key
+    #[cfg(feature = "kms")]
+    #[arg(long, conflicts_with = "prompt_key")]
+    kms_key_id: Option<String>,
+
+    /// Also dump every field of the built tx, and log every RPC request and response to stderr
+    #[arg(short, long)]
+    verbose: bool,
+
+    /// Print each 7702 authorization's signed bytes, their hash and the recovered signer
+    #[arg(long)]
+    debug_auth: bool,
+
+    /// Print the tx's type byte and its RLP as a tree of named fields
+    #[arg(long)]
+    explain: bool,
+
+    /// How to print the signed tx, type byte included
+    #[arg(long, value_enum, default_value_t = OutputFormat::Hex)]
+    output_format: OutputFormat,
+
+    /// Run the signed tx through trace_callMany first, authorizations included, and stop if it fails
+    #[arg(long)]
+    simulate: bool,
+
+    /// Print the hash the sender would sign and stop: nothing is signed or sent
+    #[arg(long, conflicts_with = "recipients")]
+    print_hash: bool,
+
+    /// Print one JSON object (from, to, nonce, raw_tx, tx_hash, receipt, status) on stdout
+    #[arg(long, conflicts_with = "recipients")]
+    json: bool,
+
+    /// What to do with the signed tx: print it, send it, send and wait for the receipt, or
+    /// send and wait until it is N blocks deep (confirm:N)
+    #[arg(
+        long,
+        value_name = "print|send|wait|confirm:N",
+        default_value = "wait",
+        conflicts_with = "recipients"
+    )]
+    strategy: SendStrategy,
+
+    /// How often to poll for the receipt, e.g. `500ms` or `2s`
+    #[arg(long, value_parser = parse_duration, default_value = "1s")]
+    poll_interval: Duration,
+
+    /// Give up waiting once nothing has moved for this long (no receipt, no new block)
+    #[arg(long, value_parser = parse_duration, default_value = "120s")]
+    wait_timeout: Duration,
+
+    /// Sign even if the node reports a different chain id
+    #[arg(long)]
+    force: bool,
+
+    /// The account the tx is meant to come from; warns if the key signs for another
+    #[arg(long)]
+    from: Option<Address>,
+
+    /// Have the node sign with its unlocked --from account (eth_sendTransaction) instead of
+    /// signing locally; 7702 authorizations are still signed locally
+    #[arg(long, requires = "from", conflicts_with_all = ["no_eip155", "explain", "simulate"])]
+    node_sign: bool,
+
+    /// With --from, refuse to sign instead of warning when the key doesn't match it
+    #[arg(long, requires = "from")]
+    strict: bool,
+
+    /// Treat a `null` nonce from the node as 0 instead of an error
+    #[arg(long)]
+    null_nonce_as_zero: bool,
+}
 
-        let mut new_buffer = Vec::<u8>::new();
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
-    }
-
-    fn rlp_encode_signed(&self) -> Vec<u8> {
-        let mut buffer = Vec::<u8>::new();
-        self.nonce.encode(&mut buffer);
-        self.gas_price.encode(&mut buffer);
-        self.gas_limit.encode(&mut buffer);
-        self.to.unwrap_or_default().encode(&mut buffer);
-        self.value.encode(&mut buffer);
-        self.data.as_slice().encode(&mut buffer);
-        self.v.encode(&mut buffer);
-        self.r.encode(&mut buffer);
-        self.s.encode(&mut buffer);
-
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
+fn parse_data(s: &str) -> Result<Bytes, String> {
+    parse_hex(s).map(Bytes::from).map_err(|err| err.to_string())
+}
 
-        let mut new_buffer = Vec::<u8>::new();
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
+fn parse_address_arg(s: &str) -> Result<Address, String> {
+    parse_address(s).map_err(|err| err.to_string())
+}
+
+/// `<n>ms`, `<n>s` or `<n>m`; a bare number is seconds.
+fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration {s}, expected e.g. 500ms, 2s or 1m");
+    let (number, unit) = s
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or((s, ""), |i| s.split_at(i));
+    let number: u64 = number.parse().map_err(|_| invalid())?;
+    match unit {
+        "ms" => Ok(Duration::from_millis(number)),
+        "" | "s" => Ok(Duration::from_secs(number)),
+        "m" => Ok(Duration::from_secs(
+            number.checked_mul(60).ok_or_else(invalid)?,
+        )),
+        _ => Err(invalid()),
     }
 }
 
-/// Minimal EIP-1559 transaction (type=2), ignoring error checks/optional fields
-#[derive(Debug)]
-struct Eip1559Transaction {
-    chain_id: u64,
-    nonce: U256,
-    max_priority_fee_per_gas: U256,
-    max_fee_per_gas: U256,
-    gas_limit: U256,
-    to: Option<Address>,
-    value: U256,
-    data: Vec<u8>,
-    #[allow(dead_code)]
-    access_list: Vec<(Address, Vec<U256>)>, // or a custom struct
-
-    // Signature
-    y_parity: u8, // 0 or 1
-    r: U256,
-    s: U256,
+#[derive(clap::Subcommand)]
+enum Command {
+    /// Decode a raw signed transaction and recover its signer(s)
+    Decode {
+        raw_tx: String,
+        /// Decode even if a signature is high-s (invalid since EIP-2)
+        #[arg(long)]
+        lenient: bool,
+    },
+    /// Show which fields of a replacement 1559 tx differ from the original's
+    Diff {
+        /// The original raw signed tx
+        original: String,
+        /// The raw signed tx meant to replace it
+        replacement: String,
+    },
+    /// Resubmit a raw signed transaction byte for byte, e.g. to another chain
+    Replay { raw_tx: String },
+    /// Sign a 7702 authorization with the authority key and print it as RLP hex, for
+    /// --authorization-hex
+    SignAuthorization {
+        /// 0 makes it valid on every chain
+        #[arg(long, default_value_t = 0)]
+        chain_id: u64,
+        /// The contract the authority delegates to
+        #[arg(long, value_parser = parse_address_arg)]
+        delegate: Address,
+        /// The authority's nonce when the authorization is applied
+        #[arg(long)]
+        nonce: U256,
+    },
+    /// Sign a message for a Safe with the sender key, as one owner's signature (r || s || v)
+    SignSafeMessage {
+        /// The Safe the message is for
+        #[arg(long, value_parser = parse_address_arg)]
+        safe: Address,
+        /// The message, as hex
+        #[arg(long, value_parser = parse_data)]
+        message: Bytes,
+        /// The chain the Safe is on [default: the config's chain id]
+        #[arg(long)]
+        chain_id: Option<u64>,
+    },
+    /// Print the addresses the next contracts an account deploys with CREATE will land at
+    PredictAddress {
+        /// The deploying account
+        #[arg(long, value_parser = parse_address_arg)]
+        address: Address,
+        /// How many nonces to predict, starting at the account's next one
+        #[arg(long, default_value_t = 5)]
+        count: u64,
+    },
+    /// Make the authority delegate to a contract, sending a 7702 tx only if it doesn't already
+    EnsureDelegation {
+        /// The contract the authority should delegate to
+        #[arg(long, value_parser = parse_address_arg)]
+        delegate: Address,
+    },
+    /// Estimate what the 7702 tx the flags describe would cost, without sending it
+    CostEstimate,
+    /// Deploy a contract in a 1559 tx and print the address it will land at
+    Deploy {
+        /// Creation code, as hex
+        #[arg(long, value_parser = parse_data)]
+        bytecode: Bytes,
+        /// Constructor arguments in order, each `<type>:<value>` (e.g. `uint256:42`)
+        #[arg(long = "args", num_args = 1.., value_parser = parse_arg)]
+        constructor_args: Vec<DynSolValue>,
+    },
 }
 
-impl Eip1559Transaction {
-    fn rlp_internal(&self) -> Vec<u8> {
-        let mut buffer = Vec::<u8>::new();
+fn parse_arg(s: &str) -> Result<DynSolValue, String> {
+    parse_constructor_arg(s).map_err(|err| err.to_string())
+}
 
-        self.chain_id.encode(&mut buffer);
-        self.nonce.encode(&mut buffer);
-        self.max_priority_fee_per_gas.encode(&mut buffer);
-        self.max_fee_per_gas.encode(&mut buffer);
-        self.gas_limit.encode(&mut buffer);
+/// Gas for a deployment when the config sets none: enough for a small
+/// contract, whose code costs 200 gas a byte to store on top of the constructor.
+const DEPLOY_GAS_LIMIT: u64 = 1_000_000;
 
-        // If `to` is `None`, encode as empty bytes
-        match self.to {
-            Some(to_addr) => to_addr.encode(&mut buffer),
-            None => (&[] as &[u8]).encode(&mut buffer),
-        };
-        self.value.encode(&mut buffer);
-        self.data.as_slice().encode(&mut buffer);
+/// Gas for an Era tx when the config sets none. L2 gas also pays for the
+/// tx's share of L1 publishing, so even a transfer needs far more than 21000.
+const ERA_GAS_LIMIT: u64 = 300_000;
 
-        // access list - TODO.
-
-        let aa1 = alloy_rlp::Header {
-            list: true,
-            payload_length: 0,
-        };
-        aa1.encode(&mut buffer);
+/// Whichever signer the flags picked for the tx itself.
+enum TxSigner {
+    Local(LocalSigner),
+    #[cfg(feature = "kms")]
+    Kms(KmsSigner<AwsKmsClient>),
+}
 
-        buffer
+impl Signer for TxSigner {
+    fn address(&self) -> Address {
+        match self {
+            TxSigner::Local(signer) => signer.address(),
+            #[cfg(feature = "kms")]
+            TxSigner::Kms(signer) => signer.address(),
+        }
     }
 
-    /// RLP for the *unsigned* portion, which you then keccak256 and sign
-    fn rlp_encode_unsigned(&self) -> Vec<u8> {
-        let mut buffer = self.rlp_internal();
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        let mut new_buffer = Vec::<u8>::new();
-        // this is crucial here.
-        new_buffer.push(0x02);
-
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
+    async fn sign_hash(&self, hash: &B256) -> Result<(U256, U256, u8), TxError> {
+        match self {
+            TxSigner::Local(signer) => signer.sign_hash(hash).await,
+            #[cfg(feature = "kms")]
+            TxSigner::Kms(signer) => signer.sign_hash(hash).await,
+        }
     }
+}
 
-    fn rlp_encode_signed(&self) -> Vec<u8> {
-        let mut buffer = self.rlp_internal();
-        self.y_parity.encode(&mut buffer);
-        self.r.encode(&mut buffer);
-        self.s.encode(&mut buffer);
+/// Spells out how far short the sender is, instead of the node's terse error.
+async fn insufficient_funds(
+    provider: &Provider,
+    from: Address,
+    tx: &SignedTx,
+) -> Result<String, TxError> {
+    let balance = get_balance(provider, from).await?;
+    let Some(cost) = tx.max_cost() else {
+        return Ok(format!(
+            "insufficient funds: value + gas limit * max fee doesn't fit in 256 bits, \
+             and {from} has {balance} wei"
+        ));
+    };
+    let shortfall = cost.saturating_sub(balance);
+    Ok(format!(
+        "insufficient funds: {from} has {balance} wei but the tx may cost up to {cost} wei \
+         (value + gas limit * max fee), {shortfall} wei ({} ETH) short",
+        format_ether(shortfall)
+    ))
+}
 
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        let mut new_buffer = Vec::<u8>::new();
-        new_buffer.push(0x02);
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
+/// `--access-list-from-tx`: a 32-byte argument is a tx hash to look up,
+/// anything longer a raw signed tx to decode.
+async fn previous_access_list(
+    provider: &Provider,
+    tx: &str,
+) -> Result<Vec<(Address, Vec<U256>)>, TxError> {
+    let bytes = parse_hex(tx)?;
+    if bytes.len() == 32 {
+        return get_transaction_access_list(provider, B256::from_slice(&bytes)).await;
     }
+    Ok(decode_signed_tx(&bytes)?.access_list().to_vec())
 }
 
-#[derive(Debug)]
-struct Eip7702Transaction {
-    chain_id: u64,
-    nonce: U256,
-    max_priority_fee_per_gas: U256,
-    max_fee_per_gas: U256,
-    gas_limit: U256,
-    to: Option<Address>,
-    value: U256,
-    data: Vec<u8>,
-    #[allow(dead_code)]
-    access_list: Vec<(Address, Vec<U256>)>, // or a custom struct
-    authorization_list: Vec<Authorization7702>,
-
-    // Signature
-    y_parity: u8, // 0 or 1
-    r: U256,
-    s: U256,
+/// `--print-hash`: the hand-off point for signing somewhere else.
+fn print_signing_hash(hash: B256, json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!("{}", serde_json::json!({ "signing_hash": hash }));
+    } else {
+        println!("Signing hash: {hash}");
+    }
+    Ok(())
 }
 
-impl Eip7702Transaction {
-    fn rlp_internal(&self) -> Vec<u8> {
-        let mut buffer = Vec::<u8>::new();
-
-        self.chain_id.encode(&mut buffer);
-        self.nonce.encode(&mut buffer);
-        self.max_priority_fee_per_gas.encode(&mut buffer);
-        self.max_fee_per_gas.encode(&mut buffer);
-        self.gas_limit.encode(&mut buffer);
+/// `cost-estimate`'s report; `null` stands for a cost that overflows.
+fn print_cost_estimate(cost: &CostEstimate, json: bool) -> Result<(), Box<dyn Error>> {
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({
+                "gas": cost.gas,
+                "authorization_gas": cost.authorization_gas,
+                "authorization_count": cost.authorization_count,
+                "per_authorization_gas": PER_AUTHORIZATION_GAS,
+                "max_fee": cost.max_fee,
+                "price": cost.price,
+                "worst_case": cost.worst_case,
+                "expected": cost.expected,
+            })
+        );
+    } else {
+        println!("{cost}");
+    }
+    Ok(())
+}
 
-        // If `to` is `None`, encode as empty bytes
-        match self.to {
-            Some(to_addr) => to_addr.encode(&mut buffer),
-            None => (&[] as &[u8]).encode(&mut buffer),
-        };
-        self.value.encode(&mut buffer);
-        self.data.as_slice().encode(&mut buffer);
+/// `--auto-gas`: the node's `estimate`, never below the tx's intrinsic gas
+/// (`floor`), plus what the access list costs up front, scaled by
+/// `--gas-multiplier`.
+fn auto_gas_limit(
+    estimate: u64,
+    floor: u64,
+    access_list: &[(Address, Vec<U256>)],
+    multiplier: Option<GasMultiplier>,
+) -> U256 {
+    let gas = U256::from(estimate.max(floor) + access_list_gas(access_list));
+    multiplier.map_or(gas, |multiplier| multiplier.apply(gas))
+}
 
-        // access list - TODO.
-        let aa1 = alloy_rlp::Header {
-            list: true,
-            payload_length: 0,
-        };
-        aa1.encode(&mut buffer);
+/// The effective gas price of a 1559 or 7702 tx mined in `block`, from
+/// that block's base fee; `None` before London, when blocks have none.
+async fn mined_gas_price(
+    provider: &Provider,
+    block: u64,
+    max_fee: U256,
+    max_priority: U256,
+) -> Result<Option<U256>, TxError> {
+    let base_fee = get_block(provider, BlockTag::Number(block))
+        .await?
+        .base_fee_per_gas;
+    Ok(base_fee.map(|base_fee| effective_gas_price(base_fee, max_fee, max_priority)))
+}
 
-        // authorization list - TODO.
-        //let aa1 = alloy_rlp::Header {
-        //    list: true,
-        //    payload_length: 0,
-        //};
-        //aa1.encode(&mut buffer);
+/// How many times `--auto-nonce-retry` re-signs a tx, so two senders
+/// racing for the same nonces can't keep it going.
+const MAX_NONCE_RETRIES: u32 = 1;
+
+/// Hands the tx to the node, explaining an insufficient-funds rejection.
+/// With `nonce_retries`, a "nonce too low" answer moves the tx to the
+/// sender's pending nonce and sends it again, updating `signed` and
+/// `raw_tx` to what was sent.
+async fn submit(
+    provider: &Provider,
+    signer: &impl Signer,
+    raw_tx: &mut Vec<u8>,
+    from: Address,
+    signed: &mut SignedTx,
+    mut nonce_retries: u32,
+    json: bool,
+) -> Result<B256, Box<dyn Error>> {
+    loop {
+        match send_raw(provider, raw_tx).await {
+            Ok(tx_hash) => return Ok(tx_hash),
+            Err(TxError::Rpc(msg))
+                if RpcErrorKind::classify(&msg) == RpcErrorKind::InsufficientFunds =>
+            {
+                return Err(insufficient_funds(provider, from, signed).await?.into())
+            }
+            Err(TxError::Rpc(msg))
+                if nonce_retries > 0
+                    && RpcErrorKind::classify(&msg) == RpcErrorKind::NonceTooLow =>
+            {
+                nonce_retries -= 1;
+                let nonce = get_nonce_at(provider, from, BlockTag::Pending).await?;
+                say!(
+                    json,
+                    "Nonce {} is too low; re-signing with the pending nonce {nonce}",
+                    signed.nonce()
+                );
+                resign_with_nonce(signer, signed, nonce).await?;
+                *raw_tx = signed.rlp_encode_signed();
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
 
-        self.authorization_list.encode(&mut buffer);
+/// Takes a tx as far as `--strategy` says: `submit` hands it to the node
+/// and returns its hash, then the receipt is awaited if the strategy waits.
+/// `--strategy print` drops `submit` unpolled, so nothing is sent.
+async fn dispatch<E: Into<Box<dyn Error>>>(
+    provider: &Provider,
+    strategy: SendStrategy,
+    submit: impl Future<Output = Result<B256, E>>,
+    json: bool,
+) -> Result<(Option<B256>, Option<Receipt>), Box<dyn Error>> {
+    if strategy == SendStrategy::Print {
+        say!(json, "Not sent (--strategy print)");
+        return Ok((None, None));
+    }
+    let tx_hash = submit.await.map_err(Into::into)?;
+    say!(json, "TX submitted! Hash: {tx_hash}");
+    let receipt = match strategy.confirmations() {
+        None => None,
+        Some(confirmations) => {
+            if confirmations > 0 {
+                say!(json, "Waiting for {confirmations} confirmations");
+            }
+            Some(wait_for_receipt(provider, tx_hash, confirmations).await?)
+        }
+    };
+    Ok((Some(tx_hash), receipt))
+}
 
-        buffer
+/// Prints how a mined tx went and where it landed.
+fn print_receipt(receipt: &Receipt) {
+    match receipt.status {
+        Some(TxStatus::Success) => println!("SUCCESS"),
+        Some(TxStatus::Reverted) => println!("REVERTED"),
+        None => println!("Pre-Byzantium receipt, state root: {:?}", receipt.root),
     }
+    println!(
+        "Block: {}, gas used: {}",
+        receipt.block_number, receipt.gas_used
+    );
+}
 
-    /// RLP for the *unsigned* portion, which you then keccak256 and sign
-    fn rlp_encode_unsigned(&self) -> Vec<u8> {
-        let mut buffer = self.rlp_internal();
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        let mut new_buffer = Vec::<u8>::new();
-        // this is crucial here.
-        new_buffer.push(0x04);
+/// `--tx-type era`: sends the signed Era tx as `--strategy` says. Era hashes
+/// txs its own way, so the hash is only known once the node returns it.
+async fn send_era(
+    provider: &Provider,
+    tx: &EraTransaction,
+    strategy: SendStrategy,
+    output_format: OutputFormat,
+    json: bool,
+) -> Result<(), Box<dyn Error>> {
+    say!(
+        json,
+        "Era tx from 0x{:x}, nonce {}, gas per pubdata {}",
+        tx.from,
+        tx.nonce,
+        tx.gas_per_pubdata
+    );
+    let raw_tx = tx.rlp_encode_signed()?;
+    say!(json, "Raw signed TX: {}", output_format.render(&raw_tx));
+    let (tx_hash, receipt) =
+        dispatch(provider, strategy, send_raw(provider, &raw_tx), json).await?;
+    if json {
+        let output = serde_json::json!({
+            "from": tx.from,
+            "raw_tx": output_format.render(&raw_tx),
+            "tx_hash": tx_hash,
+            "receipt": receipt,
+        });
+        println!("{output}");
+    } else if let Some(receipt) = &receipt {
+        print_receipt(receipt);
+    }
+    Ok(())
+}
 
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
+/// `--node-sign`: hands the unsigned tx to the node's `eth_sendTransaction`
+/// as `--strategy` says, for the node to sign with its unlocked `from`.
+async fn send_node_signed(
+    provider: &Provider,
+    from: Address,
+    tx: &SignedTx,
+    strategy: SendStrategy,
+    json: bool,
+) -> Result<(), Box<dyn Error>> {
+    let request = TransactionRequest::new(from, tx);
+    say!(
+        json,
+        "eth_sendTransaction request: {}",
+        serde_json::to_string(&request)?
+    );
+    let submit = send_transaction(provider, &request);
+    let (tx_hash, receipt) = dispatch(provider, strategy, submit, json).await?;
+    if json {
+        let output = serde_json::json!({
+            "from": from,
+            "request": request,
+            "tx_hash": tx_hash,
+            "receipt": receipt,
+        });
+        println!("{output}");
+    } else if let Some(receipt) = &receipt {
+        print_receipt(receipt);
     }
+    Ok(())
+}
 
-    fn rlp_encode_signed(&self) -> Vec<u8> {
-        let mut buffer = self.rlp_internal();
-        self.y_parity.encode(&mut buffer);
-        self.r.encode(&mut buffer);
-        self.s.encode(&mut buffer);
+/// `replay`: sends a raw tx exactly as it was signed, after warning about
+/// whatever will make this node reject it or hold on to it.
+async fn replay(provider: &Provider, raw_hex: &str, json: bool) -> Result<(), Box<dyn Error>> {
+    let raw_tx = parse_hex(raw_hex)?;
+    let tx = decode_signed_tx(&raw_tx)?;
+    let sender = tx.sender()?;
+    say!(json, "Sender: {sender}, nonce {}", tx.nonce());
+
+    let node_chain_id = fetch_chain_id(provider).await?;
+    match tx.chain_id() {
+        Some(chain_id) if chain_id != node_chain_id => say!(
+            json,
+            "Warning: the tx is signed for chain {chain_id} but the node is on {node_chain_id}, so it will be rejected"
+        ),
+        Some(_) => {}
+        None => say!(json, "No chain id (pre-EIP-155): valid on any chain that still accepts it"),
+    }
+    let next_nonce = get_nonce(provider, sender).await?;
+    if tx.nonce() < next_nonce {
+        say!(
+            json,
+            "Warning: nonce {} is already used here (the next is {next_nonce}), so it will be rejected",
+            tx.nonce()
+        );
+    } else if tx.nonce() > next_nonce {
+        say!(
+            json,
+            "Warning: nonce {} is ahead of the next one, {next_nonce}; it won't be mined until the gap is filled",
+            tx.nonce()
+        );
+    }
 
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        let mut new_buffer = Vec::<u8>::new();
-        new_buffer.push(0x04);
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-        new_buffer
+    let tx_hash = send_raw(provider, &raw_tx).await?;
+    say!(json, "TX submitted! Hash: {tx_hash}");
+    if json {
+        println!(
+            "{}",
+            serde_json::json!({ "from": sender, "tx_hash": tx_hash })
+        );
     }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct Authorization7702 {
-    chain_id: u64,
+/// `predict-address`: the CREATE address for each of the account's next
+/// `count` nonces, e.g. to fund a contract before deploying it.
+async fn predict_addresses(
+    provider: &Provider,
     address: Address,
-    nonce: U256,
-    y_parity: u8, // 0 or 1
-    r: U256,
-    s: U256,
+    count: u64,
+    json: bool,
+) -> Result<(), Box<dyn Error>> {
+    let nonce: u64 = get_nonce(provider, address)
+        .await?
+        .try_into()
+        .map_err(|_| "the account's nonce doesn't fit in 64 bits")?;
+    let predicted: Vec<_> = (nonce..nonce.saturating_add(count))
+        .map(|nonce| (nonce, create_address(address, nonce)))
+        .collect();
+    if json {
+        let predicted: Vec<_> = predicted
+            .iter()
+            .map(|(nonce, contract)| serde_json::json!({ "nonce": nonce, "address": contract }))
+            .collect();
+        println!("{}", serde_json::Value::from(predicted));
+    } else {
+        for (nonce, contract) in predicted {
+            println!("Nonce {nonce}: {contract}");
+        }
+    }
+    Ok(())
 }
 
-impl Authorization7702 {
-    pub fn new(chain_id: u64, address: Address, nonce: U256, private_key_hex: String) -> Self {
-        let mut buffer = Vec::<u8>::new();
-        chain_id.encode(&mut buffer);
-        address.encode(&mut buffer);
-        nonce.encode(&mut buffer);
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        let mut new_buffer = Vec::<u8>::new();
-        // this is crucial here - this is 'MAGIC' part.
-        new_buffer.push(0x05);
-
-        aa.encode(&mut new_buffer);
-        new_buffer.append(&mut buffer);
-
-        let message_hash = keccak256(&new_buffer);
-        let msg = secp256k1::Message::from_digest_slice(&message_hash.as_slice()).unwrap();
-        let secret_key =
-            SecretKey::from_slice(&hex_decode(private_key_hex.trim_start_matches("0x")).unwrap())
-                .expect("invalid private key bytes");
-        let signature = Secp256k1::new().sign_ecdsa_recoverable(&msg, &secret_key);
-
-        let (recovery_id, rsig) = signature.serialize_compact();
-
-        let r = bytes32_to_u256(&rsig[..32]);
-        let s = bytes32_to_u256(&rsig[32..64]);
-        let y_parity = recovery_id.to_i32() as u8; // 0 or 1
-        Self {
-            chain_id,
-            address,
-            nonce,
-            y_parity,
-            r,
-            s,
+/// `--recipients`: sends a transfer per good row, then reports every row.
+/// Ctrl-C stops it after the tx in flight, and the report says which rows
+/// were sent and which nonce to continue from.
+async fn send_recipients(
+    provider: &Provider,
+    signer: &impl Signer,
+    rows: Vec<Result<Recipient, TxError>>,
+    template: &TransferTemplate,
+    mut nonces: NonceManager,
+) -> Result<(), Box<dyn Error>> {
+    install_interrupt_handler();
+    let total = rows.len();
+    let outcomes = send_batch(provider, signer, rows, template, &mut nonces, &INTERRUPTED).await;
+
+    let mut failed = 0;
+    let mut not_sent = 0;
+    let mut unknown = 0;
+    println!("Recipients:");
+    for outcome in &outcomes {
+        match outcome {
+            RowOutcome::Sent {
+                recipient,
+                nonce,
+                tx_hash,
+            } => println!(
+                "  line {}: {} wei to {}, nonce {nonce}: {tx_hash}",
+                recipient.line, recipient.value, recipient.to
+            ),
+            RowOutcome::Failed { recipient, error } => {
+                failed += 1;
+                println!(
+                    "  line {}: {} failed: {error}",
+                    recipient.line, recipient.to
+                );
+            }
+            RowOutcome::Unknown {
+                recipient,
+                nonce,
+                error,
+            } => {
+                unknown += 1;
+                println!(
+                    "  line {}: {} unknown, nonce {nonce} may be used: {error}",
+                    recipient.line, recipient.to
+                );
+            }
+            RowOutcome::Invalid(err) => {
+                failed += 1;
+                println!("  {err}, skipped");
+            }
+            RowOutcome::NotSent(recipient) => {
+                not_sent += 1;
+                println!("  line {}: {} not sent", recipient.line, recipient.to);
+            }
         }
     }
+    println!(
+        "{} sent, {failed} failed",
+        total - failed - not_sent - unknown
+    );
+    if unknown > 0 {
+        println!(
+            "{unknown} rows got no answer from the node; check their nonces before sending them again"
+        );
+    }
+    if not_sent > 0 {
+        let next_nonce = nonces.next_nonce();
+        println!("Interrupted: {not_sent} rows not sent; the next unused nonce is {next_nonce}");
+        return Err(format!("interrupted with {not_sent} of {total} rows not sent").into());
+    }
+    if failed + unknown > 0 {
+        return Err(format!("{} of {total} rows failed", failed + unknown).into());
+    }
+    Ok(())
 }
 
-impl Encodable for Authorization7702 {
-    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
-        let mut buffer = Vec::<u8>::new();
-
-        self.chain_id.encode(&mut buffer);
-        self.address.encode(&mut buffer);
-        self.nonce.encode(&mut buffer);
-        self.y_parity.encode(&mut buffer);
-        self.r.encode(&mut buffer);
-        self.s.encode(&mut buffer);
-        let aa = alloy_rlp::Header {
-            list: true,
-            payload_length: buffer.len(),
-        };
-        aa.encode(out);
-        out.put_slice(&buffer);
+/// Set by the first Ctrl-C during a `--recipients` batch.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes the first Ctrl-C set [`INTERRUPTED`] so the batch can report what
+/// it sent; a second one exits right away.
+#[cfg(unix)]
+fn install_interrupt_handler() {
+    extern "C" fn on_interrupt(_: libc::c_int) {
+        // only async-signal-safe calls in here
+        if INTERRUPTED.swap(true, Ordering::SeqCst) {
+            unsafe { libc::_exit(130) };
+        }
     }
+    unsafe {
+        libc::signal(
+            libc::SIGINT,
+            on_interrupt as extern "C" fn(libc::c_int) as libc::sighandler_t,
+        )
+    };
 }
 
-fn bytes32_to_u256(bytes: &[u8]) -> U256 {
-    U256::from_be_bytes::<32>(bytes.try_into().expect("slice must be 32 bytes"))
-}
+#[cfg(not(unix))]
+fn install_interrupt_handler() {}
 
-pub async fn get_nonce(url: &str, addr: Address) -> U256 {
-    let client = Client::new();
+/// `--verbose` prints this crate's debug events (every RPC request and
+/// response) to stderr, one `message key=value ...` line each.
+struct StderrLog;
 
-    let params = [format!("0x{:x}", addr), "latest".to_string()];
-    let req = JsonRpcRequest {
-        jsonrpc: "2.0",
-        method: "eth_getTransactionCount",
-        params: &params,
-        id: 1,
-    };
-    let resp: JsonRpcResponse<String> = client
-        .post(url)
-        .json(&req)
-        .send()
-        .await
-        .unwrap()
-        .json()
-        .await
-        .unwrap();
-
-    let nonce_hex = resp
-        .result
-        .ok_or("No result from getTransactionCount")
-        .unwrap();
-    let nonce_value =
-        U256::from_str_radix(nonce_hex.trim_start_matches("0x"), 16).unwrap_or_default();
-
-    println!("Nonce: {}", nonce_value);
-    nonce_value
-}
+impl tracing::Subscriber for StderrLog {
+    fn enabled(&self, metadata: &tracing::Metadata<'_>) -> bool {
+        metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && *metadata.level() <= tracing::Level::DEBUG
+    }
 
-pub fn address_from_pkey(private_key_hex: &str) -> Address {
-    let pk_nostrip = private_key_hex.trim_start_matches("0x");
-    let pk_bytes = hex_decode(pk_nostrip).unwrap();
-    let secret_key = SecretKey::from_slice(&pk_bytes).expect("invalid private key bytes");
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
 
-    let pubkey = secret_key.public_key(&Secp256k1::new());
+    fn event(&self, event: &tracing::Event<'_>) {
+        let mut line = String::new();
+        event.record(&mut LineVisitor(&mut line));
+        eprintln!("{line}");
+    }
 
-    let pubkey_uncompressed = pubkey.serialize_uncompressed(); // 65 bytes, [0x04, x, y]
+    fn enter(&self, _: &span::Id) {}
 
-    let hash = keccak256(&pubkey_uncompressed[1..]); // skip the 0x04
-    let from_addr = Address::from_slice(&hash[12..]); // last 20 bytes
-    from_addr
+    fn exit(&self, _: &span::Id) {}
 }
 
-#[derive(clap::Parser)]
-pub struct Args {
-    #[arg(short, long)]
-    tx_type: String,
+struct LineVisitor<'a>(&'a mut String);
 
-    #[arg(short, long)]
-    delegate_to: Option<String>,
+impl Visit for LineVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        let _ = write!(self.0, " {}={value}", field.name());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let _ = match field.name() {
+            "message" => write!(self.0, "{value:?}"),
+            name => write!(self.0, " {name}={value:?}"),
+        };
+    }
 }
 
 #[main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let args = Args::parse();
+    let json = args.json;
+    if args.verbose {
+        tracing::subscriber::set_global_default(StderrLog)?;
+    }
+    match run(args).await {
+        // scripts get failures in the same shape as results
+        Err(err) if json => {
+            println!("{}", serde_json::json!({ "error": err.to_string() }));
+            std::process::exit(1);
+        }
+        result => result,
+    }
+}
+
+async fn run(args: Args) -> Result<(), Box<dyn Error>> {
+    let json = args.json;
+    if let Some(Command::Decode { raw_tx, lenient }) = &args.command {
+        decode::print_decoded(raw_tx, *lenient)?;
+        return Ok(());
+    }
+    if let Some(Command::Diff {
+        original,
+        replacement,
+    }) = &args.command
+    {
+        let decode_1559 = |raw: &str| match decode_signed_tx(&parse_hex(raw)?)? {
+            SignedTx::Eip1559(tx) => Ok(tx),
+            _ => Err(TxError::Decode("diff compares two 1559 txs".to_string())),
+        };
+        let (original, replacement) = (decode_1559(original)?, decode_1559(replacement)?);
+        let changes = diff_tx(&original, &replacement);
+        if changes.is_empty() {
+            println!("No fields differ");
+        }
+        for (field, old, new) in &changes {
+            println!("{field}: {old} -> {new}");
+        }
+        if original.nonce != replacement.nonce {
+            println!("Warning: the nonce changed, so the replacement won't replace anything");
+        }
+        return Ok(());
+    }
 
     // ------------------------------------------------
     // 1. Parse the private key from hex
     // ------------------------------------------------
-    let private_key_hex = "0x0fad2ca996a24d116097c481c27a59652a3d3611dfed64d8f9bf86568b1f431d";
-    let pk_nostrip = private_key_hex.trim_start_matches("0x");
-    let pk_bytes = hex_decode(pk_nostrip).unwrap();
-    let secret_key = SecretKey::from_slice(&pk_bytes).expect("invalid private key bytes");
-
-    let from_addr = address_from_pkey(private_key_hex);
-    println!("From Address: 0x{:x}", from_addr);
-
-    // 3. Get nonce from local Geth (http://localhost:8545)
-    let client = Client::new();
-    let url = "http://127.0.0.1:8848";
-
-    let nonce_value = get_nonce(url, from_addr).await;
-
-    let tx_type = args.tx_type;
-    let chain_id = 1337;
-
-    let raw_tx_hex = if tx_type == "legacy" {
-        let mut tx = LegacyTransaction {
-            nonce: nonce_value,
-            gas_price: U256::from(1_000_000_000u64), // 1 gwei
-            gas_limit: U256::from(21000u64),
-            to: Some(Address::from_slice(
-                &hex_decode("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa").unwrap(),
-            )),
-            value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei
-            data: vec![],
-            v: 0,
-            r: U256::ZERO,
-            s: U256::ZERO,
-        };
+    let env_file = match &args.env_file {
+        None => HashMap::new(),
+        Some(Some(path)) => read_env_file(path)?,
+        // bare `--env-file` only picks up `.env` if there is one
+        Some(None) if Path::new(".env").exists() => read_env_file(".env")?,
+        Some(None) => HashMap::new(),
+    };
+    // the shell wins over the file
+    let env_var = |key: &str| {
+        std::env::var(key)
+            .ok()
+            .or_else(|| env_file.get(key).cloned())
+    };
 
-        // 5. Sign (EIP-155 Legacy)
-        let unsigned_rlp = tx.rlp_encode_unsigned(chain_id);
-        let message_hash = keccak256(&unsigned_rlp);
-
-        let msg = secp256k1::Message::from_digest_slice(&message_hash.as_slice())?;
-        let signature = Secp256k1::new().sign_ecdsa_recoverable(&msg, &secret_key);
-
-        let (recovery_id, rsig) = signature.serialize_compact();
-        let rid = recovery_id.to_i32() as u64; // 0 or 1
-        tx.r = bytes32_to_u256(&rsig[0..32]);
-        tx.s = bytes32_to_u256(&rsig[32..64]);
-        // EIP-155 => v = rid + 2 * chain_id + 35
-        tx.v = rid + (2 * chain_id) + 35;
-
-        // 6. RLP-encode and send
-        let signed_tx_rlp = tx.rlp_encode_signed();
-        let raw_tx_hex = format!("0x{}", hex::encode(signed_tx_rlp));
-        println!("Raw signed TX: {}", raw_tx_hex);
-        raw_tx_hex
-    } else if tx_type == "1559" {
-        let mut tx = Eip1559Transaction {
-            chain_id: chain_id,
-            nonce: nonce_value,
-            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
-            max_fee_per_gas: U256::from(1_000_000_000u64),
-            gas_limit: U256::from(21000u64),
-            to: Some(Address::from_slice(
-                &hex_decode("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabb").unwrap(),
-            )),
-            value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei,
-            data: vec![],
-            access_list: vec![],
-            y_parity: 0,
-            r: U256::ZERO,
-            s: U256::ZERO,
+    let config = match &args.config {
+        Some(path) => Config::load(path)?,
+        None => Config::default(),
+    }
+    .with_env(env_var)
+    .with_overrides(args.overrides);
+
+    if let Some(Command::SignAuthorization {
+        chain_id,
+        delegate,
+        nonce,
+    }) = &args.command
+    {
+        let key = if args.prompt_key {
+            prompt_private_key()?
+        } else {
+            parse_private_key(&config.keys.authority)?
         };
-        let unsigned_rlp = tx.rlp_encode_unsigned();
-        let message_hash = keccak256(&unsigned_rlp);
-        let msg = secp256k1::Message::from_digest_slice(&message_hash.as_slice())?;
-        let signature = Secp256k1::new().sign_ecdsa_recoverable(&msg, &secret_key);
-
-        let (recovery_id, rsig) = signature.serialize_compact();
-        tx.r = bytes32_to_u256(&rsig[..32]);
-        tx.s = bytes32_to_u256(&rsig[32..64]);
-        tx.y_parity = recovery_id.to_i32() as u8; // 0 or 1
-        let signed_bytes = tx.rlp_encode_signed();
-        let raw_tx_hex = format!("0x{}", hex::encode(signed_bytes));
-        println!("Raw signed TX: {}", raw_tx_hex);
-        raw_tx_hex
-    } else if tx_type == "7702" {
-        let second_pkey = "0x411bdd63dc116ba53e0e3fbe752ba21f869e272d4f544c8d545c617ce43f654e";
-        let second_address = address_from_pkey(&second_pkey);
-        let second_nonce = get_nonce(url, second_address).await;
-
-        let delegate_to = Address::from_hex(
-            args.delegate_to
-                .unwrap_or("0x4F747741EF10551969F9688a8264FC6bb337fA5f".to_string()),
-        )
-        .unwrap();
-
-        // Authorization is 'type 5' object.
-        let authorization = Authorization7702::new(
-            // for all chains.
-            0,
-            delegate_to, // deployed counter contract.
-            second_nonce,
-            second_pkey.to_string(),
+        let authorization = Authorization7702::sign(*chain_id, *delegate, *nonce, &key)?;
+        eprintln!("Authority: {}", address_from_key(&key));
+        println!("0x{}", hex::encode(alloy_rlp::encode(&authorization)));
+        return Ok(());
+    }
+    if let Some(Command::SignSafeMessage {
+        safe,
+        message,
+        chain_id,
+    }) = &args.command
+    {
+        let key = if args.prompt_key {
+            prompt_private_key()?
+        } else {
+            parse_private_key(&config.keys.sender)?
+        };
+        let hash = safe_message_hash(*safe, chain_id.unwrap_or(config.chain_id), message);
+        let (r, s, y_parity) = sign_hash(&hash, &key)?;
+        eprintln!("Owner: {}", address_from_key(&key));
+        eprintln!("Safe message hash: {hash}");
+        println!("0x{}", hex::encode(signature_bytes(r, s, y_parity)?));
+        return Ok(());
+    }
+
+    // 3. Nonces come from local Geth
+    let provider = Provider::new(config.rpc_url.as_str())
+        .with_null_nonce_as_zero(args.null_nonce_as_zero)
+        .with_poll_interval(args.poll_interval)
+        .with_wait_timeout(args.wait_timeout);
+
+    if let Some(Command::Replay { raw_tx }) = &args.command {
+        return replay(&provider, raw_tx, json).await;
+    }
+    if let Some(Command::PredictAddress { address, count }) = &args.command {
+        return predict_addresses(&provider, *address, *count, json).await;
+    }
+
+    let signer = TxSigner::Local(LocalSigner::new(if args.prompt_key {
+        prompt_private_key()?
+    } else {
+        parse_private_key(&config.keys.sender)?
+    }));
+    #[cfg(feature = "kms")]
+    let signer = match &args.kms_key_id {
+        Some(key_id) => {
+            TxSigner::Kms(KmsSigner::new(AwsKmsClient::from_env()?, key_id.as_str()).await?)
+        }
+        None => signer,
+    };
+
+    let from_addr = match args.from {
+        Some(from) if args.node_sign => from,
+        _ => signer.address(),
+    };
+    say!(json, "From Address: 0x{:x}", from_addr);
+    // the key decides the sender, whatever --from says
+    if let Some(intended) = args.from.filter(|&intended| intended != from_addr) {
+        if args.strict {
+            return Err(format!(
+                "the key signs for 0x{from_addr:x}, not --from {intended} (--strict)"
+            )
+            .into());
+        }
+        say!(
+            json,
+            "Warning: the key signs for 0x{from_addr:x}, not --from {intended}; the tx will come from 0x{from_addr:x}"
         );
+    }
 
-        // That is put into type '4' transaction.
-        let mut tx = Eip7702Transaction {
-            chain_id,
-            nonce: nonce_value,
-            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
-            max_fee_per_gas: U256::from(1_000_000_000u64),
-            // more gas.
-            gas_limit: U256::from(46000u64),
-            // It doesn't matter who is the target of this transaction.
-            to: Some(Address::from_slice(
-                &hex_decode("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabb").unwrap(),
-            )),
-            value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei,
-            data: vec![],
-            access_list: vec![],
-            authorization_list: vec![authorization],
-            y_parity: 0,
-            r: U256::ZERO,
-            s: U256::ZERO,
+    let nonce_strategy = args.nonce.map_or(args.nonce_strategy, NonceStrategy::Fixed);
+    let deploy = match &args.command {
+        Some(Command::Deploy {
+            bytecode,
+            constructor_args,
+        }) => Some(deploy_calldata(bytecode, constructor_args)),
+        _ => None,
+    };
+    let data_input = DataInput::from_flags(
+        args.data.as_deref(),
+        args.data_file.as_deref(),
+        args.call.as_deref(),
+    )?;
+    let ensure_delegate = match &args.command {
+        Some(Command::EnsureDelegation { delegate }) => Some(*delegate),
+        _ => None,
+    };
+    let cost_estimate = matches!(args.command, Some(Command::CostEstimate));
+    // the subcommands that only make sense for a 7702 tx
+    let only_7702 = match &args.command {
+        Some(Command::EnsureDelegation { .. }) => Some("ensure-delegation"),
+        Some(Command::CostEstimate) => Some("cost-estimate"),
+        _ => None,
+    };
+    let tx_type = match (&args.recipients, &deploy) {
+        (_, Some(_)) if args.to.is_some() => {
+            return Err("deploy creates a contract; drop --to".into())
+        }
+        (None, Some(_)) if args.tx_type.is_none() && data_input.is_none() => TxType::Eip1559,
+        (_, Some(_)) => {
+            return Err(
+                "deploy builds its own 1559 tx; drop --tx-type, --data/--data-file/--call and --recipients".into(),
+            )
+        }
+        (Some(_), None) if only_7702.is_some() => {
+            return Err(format!("{} builds a 7702 tx; drop --recipients", only_7702.unwrap()).into())
+        }
+        (None, None) if only_7702.is_some() => match args.tx_type {
+            None | Some(TxType::Eip7702) => TxType::Eip7702,
+            Some(_) => {
+                return Err(format!("{} builds a 7702 tx; drop --tx-type", only_7702.unwrap()).into())
+            }
+        },
+        (Some(_), None) => TxType::Eip1559,
+        (None, None) => args.tx_type.ok_or("--tx-type is required")?,
+    };
+    if args.fee_oracle && tx_type == TxType::Legacy {
+        return Err("--fee-oracle prices 1559 and 7702 txs, not legacy".into());
+    }
+    if args.gas_per_pubdata.is_some() && tx_type != TxType::Era {
+        return Err("--gas-per-pubdata needs --tx-type era".into());
+    }
+    let gas_per_pubdata = args.gas_per_pubdata.unwrap_or(DEFAULT_GAS_PER_PUBDATA);
+    check_gas_per_pubdata(gas_per_pubdata)?;
+    if args.auto_nonce_retry && (tx_type == TxType::Era || args.recipients.is_some()) {
+        return Err("--auto-nonce-retry only retries a single legacy, 1559 or 7702 tx".into());
+    }
+    if args.no_eip155 && tx_type != TxType::Legacy {
+        return Err("--no-eip155 needs --tx-type legacy".into());
+    }
+    if args.auto_access_list && tx_type != TxType::Eip7702 {
+        return Err("--auto-access-list needs --tx-type 7702".into());
+    }
+    if args.debug_auth && tx_type != TxType::Eip7702 {
+        return Err("--debug-auth needs --tx-type 7702".into());
+    }
+    if !args.authorization_hex.is_empty() && tx_type != TxType::Eip7702 {
+        return Err("--authorization-hex needs --tx-type 7702".into());
+    }
+    let extra_authorizations = args
+        .authorization_hex
+        .iter()
+        .map(|raw| decode_authorization(&parse_hex(raw)?))
+        .collect::<Result<Vec<_>, _>>()?;
+    if args.verify_delegation && tx_type != TxType::Eip7702 {
+        return Err("--verify-delegation needs --tx-type 7702".into());
+    }
+    if args.verify_delegation && args.strategy.confirmations().is_none() {
+        return Err("--verify-delegation needs a --strategy that waits for the receipt".into());
+    }
+    let to = match &args.to {
+        Some(to) => Some(resolve_recipient(&EnsResolver::new(&provider), to).await?),
+        None => None,
+    };
+    let access_list = match &args.access_list {
+        None => vec![],
+        Some(_) if matches!(tx_type, TxType::Legacy | TxType::Era) => {
+            return Err(format!("{tx_type} txs have no access list").into());
+        }
+        Some(list) if list.trim_start().starts_with('[') => parse_access_list(list)?,
+        Some(path) => {
+            let json = std::fs::read_to_string(path).map_err(|err| format!("{path}: {err}"))?;
+            parse_access_list(&json)?
+        }
+    };
+    let access_list = match &args.access_list_from_tx {
+        None => access_list,
+        Some(_) if matches!(tx_type, TxType::Legacy | TxType::Era) => {
+            return Err(format!("{tx_type} txs have no access list").into());
+        }
+        Some(tx) => previous_access_list(&provider, tx).await?,
+    };
+    let chain_id = config.chain_id;
+
+    // A mismatch here means the signed tx could be replayed on (or was meant
+    // for) another network - don't sign it unless explicitly told to.
+    let node_chain_id = fetch_chain_id(&provider).await?;
+    if node_chain_id != chain_id {
+        let err = TxError::ChainIdMismatch {
+            node: node_chain_id,
+            signing: chain_id,
         };
-        let unsigned_rlp = tx.rlp_encode_unsigned();
-        let message_hash = keccak256(&unsigned_rlp);
-        let msg = secp256k1::Message::from_digest_slice(&message_hash.as_slice())?;
-        let signature = Secp256k1::new().sign_ecdsa_recoverable(&msg, &secret_key);
-
-        let (recovery_id, rsig) = signature.serialize_compact();
-        tx.r = bytes32_to_u256(&rsig[..32]);
-        tx.s = bytes32_to_u256(&rsig[32..64]);
-        tx.y_parity = recovery_id.to_i32() as u8; // 0 or 1
-        let signed_bytes = tx.rlp_encode_signed();
-        let raw_tx_hex = format!("0x{}", hex::encode(signed_bytes));
-        println!("Raw signed TX: {}", raw_tx_hex);
-        raw_tx_hex
+        if !args.force {
+            return Err(format!("{err} (use --force to sign anyway)").into());
+        }
+        say!(json, "Warning: {err}");
+    }
+
+    let data = match &deploy {
+        Some(calldata) => calldata.clone(),
+        None => match &data_input {
+            Some(input) => input.resolve()?,
+            None => vec![],
+        },
+    };
+    // the defaults only cover the intrinsic cost of an empty call; --auto-gas replaces them
+    // with the node's estimate once the tx is built
+    let gas_limit = |default: u64, access_list: &[(Address, Vec<U256>)]| {
+        U256::from(config.gas.unwrap_or(default + access_list_gas(access_list)))
+    };
+    let (max_fee_per_gas, max_priority_fee_per_gas) = if args.fee_oracle {
+        let (max_fee, priority_fee) = oracle_fees(&provider, args.tip_source).await?;
+        say!(
+            json,
+            "Fee oracle: max {max_fee} wei, priority {priority_fee} wei"
+        );
+        (max_fee, priority_fee)
+    } else if let Some(strategy) = args.fee_strategy {
+        let (max_fee, priority_fee) = strategy_fees(&provider, strategy).await?;
+        say!(
+            json,
+            "Fee strategy {strategy:?}: max {max_fee} wei, priority {priority_fee} wei"
+        );
+        (max_fee, priority_fee)
     } else {
-        panic!("bad");
+        (
+            U256::from(config.fees.max_fee_per_gas),
+            U256::from(config.fees.max_priority_fee_per_gas),
+        )
+    };
+    // the caps win over whatever the config or the oracle asked for
+    let max_fee_per_gas = clamp_fee(max_fee_per_gas, args.max_fee_cap);
+    let max_priority_fee_per_gas = clamp_fee(max_priority_fee_per_gas, args.max_priority_cap);
+    let gas_price = clamp_fee(U256::from(config.fees.gas_price), args.max_fee_cap);
+    let base_fee = if tx_type != TxType::Legacy {
+        let latest = get_block(&provider, BlockTag::Latest).await?;
+        check_fees(
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            latest.base_fee_per_gas,
+        )?;
+        if let Some(warning) = latest
+            .base_fee_per_gas
+            .and_then(|base_fee| base_fee_headroom_warning(max_fee_per_gas, base_fee))
+        {
+            say!(
+                json,
+                "Warning: {warning}; the tx may wait for a cheaper block"
+            );
+        }
+        // a node from before Prague would only reject the tx once it's signed
+        if tx_type == TxType::Eip7702 && latest.requests_hash.is_none() {
+            say!(
+                json,
+                "Warning: the latest block has no requestsHash, so the node may predate Prague and reject 7702 txs"
+            );
+        }
+        latest.base_fee_per_gas
+    } else {
+        None
     };
 
-    let send_params = [raw_tx_hex];
-    let send_req = JsonRpcRequest {
-        jsonrpc: "2.0",
-        method: "eth_sendRawTransaction",
-        params: &send_params,
-        id: 2,
+    if let Some(path) = &args.recipients {
+        let template = TransferTemplate {
+            chain_id,
+            max_priority_fee_per_gas,
+            max_fee_per_gas,
+            gas_limit: gas_limit(21000, &[]),
+        };
+        let nonce = resolve_nonce(&provider, from_addr, nonce_strategy).await?;
+        let rows = read_recipients(path)?;
+        if !args.skip_bad_rows {
+            if let Some(Err(err)) = rows.iter().find(|row| row.is_err()) {
+                return Err(format!("{path}: {err} (use --skip-bad-rows to send the rest)").into());
+            }
+        }
+        return send_recipients(
+            &provider,
+            &signer,
+            rows,
+            &template,
+            NonceManager::new(nonce),
+        )
+        .await;
+    }
+
+    let mut signed = match tx_type {
+        TxType::Era => {
+            if args.node_sign {
+                return Err("--node-sign doesn't support era txs".into());
+            }
+            let mut tx = EraTransaction {
+                chain_id,
+                nonce: resolve_nonce(&provider, from_addr, nonce_strategy).await?,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit: gas_limit(ERA_GAS_LIMIT, &[]),
+                to: Some(to.unwrap_or(address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"))),
+                value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei
+                data: data.clone(),
+                from: from_addr,
+                gas_per_pubdata: U256::from(gas_per_pubdata),
+                paymaster_params: None,
+                y_parity: 0,
+                r: U256::ZERO,
+                s: U256::ZERO,
+            };
+            if args.auto_gas {
+                let estimate =
+                    estimate_call_gas(&provider, from_addr, tx.to, tx.value, &tx.data).await?;
+                tx.gas_limit = auto_gas_limit(
+                    estimate,
+                    intrinsic_gas(&tx.data, 0),
+                    &[],
+                    args.gas_multiplier,
+                );
+            }
+            // signed over its EIP-712 hash, not its RLP
+            if args.print_hash {
+                return print_signing_hash(tx.signing_hash(), json);
+            }
+            (tx.r, tx.s, tx.y_parity) = signer.sign_hash(&tx.signing_hash()).await?;
+            return send_era(&provider, &tx, args.strategy, args.output_format, json).await;
+        }
+        TxType::Legacy => {
+            let mut tx = LegacyTransaction {
+                nonce: resolve_nonce(&provider, from_addr, nonce_strategy).await?,
+                gas_price,
+                gas_limit: gas_limit(21000, &[]),
+                to: Some(to.unwrap_or(address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"))),
+                value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei
+                data: data.clone(),
+                // 5. Sign (EIP-155 Legacy): the chain id until there is a signature
+                v: chain_id,
+                r: U256::ZERO,
+                s: U256::ZERO,
+            };
+            if args.auto_gas {
+                let estimate =
+                    estimate_call_gas(&provider, from_addr, tx.to, tx.value, &tx.data).await?;
+                tx.gas_limit = auto_gas_limit(
+                    estimate,
+                    intrinsic_gas(&tx.data, 0),
+                    &[],
+                    args.gas_multiplier,
+                );
+            }
+            if args.no_eip155 {
+                let mut tx = PreEip155(tx);
+                if args.print_hash {
+                    return print_signing_hash(tx.signature_hash(), json);
+                }
+                signer.finalize(&mut tx).await?;
+                SignedTx::Legacy(tx.0)
+            } else {
+                if args.print_hash {
+                    return print_signing_hash(tx.signature_hash(), json);
+                }
+                if !args.node_sign {
+                    signer.finalize(&mut tx).await?;
+                }
+                SignedTx::Legacy(tx)
+            }
+        }
+        TxType::Eip1559 => {
+            let nonce = resolve_nonce(&provider, from_addr, nonce_strategy).await?;
+            let (default_gas, to, value) = match deploy {
+                Some(_) => (DEPLOY_GAS_LIMIT, None, U256::ZERO),
+                None => (
+                    21000,
+                    Some(to.unwrap_or(address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabb"))),
+                    U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei,
+                ),
+            };
+            let mut tx = Eip1559Transaction {
+                chain_id,
+                nonce,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                gas_limit: gas_limit(default_gas, &access_list),
+                to,
+                value,
+                data: data.clone(),
+                access_list: access_list.clone(),
+                y_parity: 0,
+                r: U256::ZERO,
+                s: U256::ZERO,
+            };
+            if args.auto_gas {
+                let (estimate, floor) = if deploy.is_some() {
+                    (
+                        estimate_deploy_gas(&provider, from_addr, &tx.data).await?,
+                        creation_intrinsic_gas(&tx.data),
+                    )
+                } else {
+                    (
+                        estimate_call_gas(&provider, from_addr, tx.to, tx.value, &tx.data).await?,
+                        intrinsic_gas(&tx.data, 0),
+                    )
+                };
+                tx.gas_limit =
+                    auto_gas_limit(estimate, floor, &tx.access_list, args.gas_multiplier);
+            }
+            if args.print_hash {
+                return print_signing_hash(tx.signing_hash(), json);
+            }
+            if !args.node_sign {
+                signer.finalize(&mut tx).await?;
+            }
+            if deploy.is_some() {
+                say!(
+                    json,
+                    "Contract address: {}",
+                    create_address(from_addr, nonce.saturating_to())
+                );
+            }
+            SignedTx::Eip1559(tx)
+        }
+        TxType::Eip7702 => {
+            let second_pkey = config.keys.authority.as_str();
+            let second_address = address_from_pkey(second_pkey)?;
+            let (nonce_value, second_nonce) = match (nonce_strategy.block_tag(), args.auth_nonce) {
+                (_, Some(auth_nonce)) => (
+                    resolve_nonce(&provider, from_addr, nonce_strategy).await?,
+                    auth_nonce,
+                ),
+                (Some(tag), None) => {
+                    // sender and authority nonces in one round trip
+                    let nonces =
+                        get_nonces_at(&provider, &[from_addr, second_address], tag).await?;
+                    (nonces[0], nonces[1])
+                }
+                (None, None) => (
+                    resolve_nonce(&provider, from_addr, nonce_strategy).await?,
+                    get_nonce(&provider, second_address).await?,
+                ),
+            };
+
+            let delegate_to =
+                match ensure_delegate {
+                    Some(_) if args.delegate_to.is_some() => return Err(
+                        "ensure-delegation takes the delegate from --delegate; drop --delegate-to"
+                            .into(),
+                    ),
+                    Some(delegate) => delegate,
+                    None => args
+                        .delegate_to
+                        .unwrap_or(address!("4F747741EF10551969F9688a8264FC6bb337fA5f")),
+                };
+
+            // an authorization silently replaces whatever the authority delegated to before
+            match code_kind(&provider, second_address).await? {
+                CodeKind::Eoa => say!(json, "Current delegate of 0x{:x}: none", second_address),
+                CodeKind::Contract => say!(
+                    json,
+                    "Warning: 0x{:x} is a contract, so nodes will skip its authorization",
+                    second_address
+                ),
+                CodeKind::Delegated(current) => {
+                    say!(
+                        json,
+                        "Current delegate of 0x{:x}: {current}",
+                        second_address
+                    );
+                    if ensure_delegate == Some(current) {
+                        say!(
+                            json,
+                            "0x{:x} is already delegated to {current}; nothing to send",
+                            second_address
+                        );
+                        if json {
+                            let output = serde_json::json!({
+                                "authority": second_address,
+                                "delegate": current,
+                                "already_delegated": true,
+                            });
+                            println!("{output}");
+                        }
+                        return Ok(());
+                    }
+                    if args.fail_if_delegated {
+                        return Err(format!(
+                            "0x{:x} already delegates to {current} (--fail-if-delegated)",
+                            second_address
+                        )
+                        .into());
+                    }
+                    if current != delegate_to {
+                        say!(
+                            json,
+                            "Warning: this authorization replaces the delegation to {current}"
+                        );
+                    }
+                }
+            }
+
+            // delegations aren't followed, so the delegate has to be the code itself
+            match code_kind(&provider, delegate_to).await? {
+            CodeKind::Contract => {}
+            CodeKind::Eoa => say!(
+                json,
+                "Warning: {delegate_to} has no code, so calls to the authority will do nothing"
+            ),
+            CodeKind::Delegated(target) => say!(
+                json,
+                "Warning: {delegate_to} is itself delegated to {target}, and 7702 doesn't follow that"
+            ),
+        }
+
+            // Authorization is 'type 5' object.
+            let authorization = Authorization7702::new(
+                // 0 is valid on every chain.
+                args.auth_chain_id,
+                delegate_to, // deployed counter contract.
+                second_nonce,
+                second_pkey.to_string(),
+            )?;
+
+            let mut authorization_list = vec![authorization];
+            authorization_list.extend(extra_authorizations);
+            // 25000 gas per authorization on top of the 21000
+            let auth_count = authorization_list.len();
+            let default_gas = 21000 + 25000 * auth_count as u64;
+
+            // That is put into type '4' transaction.
+            let mut tx = Eip7702Transaction {
+                chain_id,
+                nonce: nonce_value,
+                max_priority_fee_per_gas,
+                max_fee_per_gas,
+                // more gas.
+                gas_limit: gas_limit(default_gas, &access_list),
+                // It doesn't matter who is the target of this transaction.
+                to: Some(to.unwrap_or(address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabb"))),
+                value: U256::from(1_000_000_000_000_000_000u64), // 1 ETH in wei,
+                data: data.clone(),
+                access_list: access_list.clone(),
+                authorization_list,
+                y_parity: 0,
+                r: U256::ZERO,
+                s: U256::ZERO,
+            };
+            if args.auto_access_list {
+                tx.access_list = create_access_list(
+                    &provider,
+                    from_addr,
+                    tx.to,
+                    tx.value,
+                    &tx.data,
+                    second_address,
+                    delegate_to,
+                )
+                .await?;
+                // the list is paid for up front
+                tx.gas_limit = gas_limit(default_gas, &tx.access_list);
+            }
+            if args.auto_gas {
+                // the delegated code runs too, which only the node can price
+                let estimate = estimate_gas(&provider, from_addr, &tx).await?;
+                let floor = intrinsic_gas(&tx.data, auth_count);
+                tx.gas_limit =
+                    auto_gas_limit(estimate, floor, &tx.access_list, args.gas_multiplier);
+            }
+            if cost_estimate {
+                let gas = estimate_gas(&provider, from_addr, &tx).await?;
+                // no base fee means no London, where the max fee is the price
+                let price = base_fee.map_or(max_fee_per_gas, |base_fee| {
+                    effective_gas_price(base_fee, max_fee_per_gas, max_priority_fee_per_gas)
+                });
+                let cost = estimate_cost(gas, auth_count, tx.value, max_fee_per_gas, price);
+                return print_cost_estimate(&cost, json);
+            }
+            if args.print_hash {
+                return print_signing_hash(tx.signing_hash(), json);
+            }
+            if !args.node_sign {
+                signer.finalize(&mut tx).await?;
+            }
+            SignedTx::Eip7702(tx)
+        }
     };
-    let send_resp: JsonRpcResponse<String> = client
-        .post(url)
-        .json(&send_req)
-        .send()
-        .await?
-        .json()
-        .await?;
 
-    match send_resp.result {
-        Some(tx_hash) => println!("TX submitted! Hash: {tx_hash}"),
-        None => println!("Error: {:?}", send_resp.error),
+    say!(json, "{}", summary(from_addr, &signed));
+    if args.verbose {
+        say!(json, "{signed:#?}");
+    }
+    if (args.verbose || args.explain) && !args.node_sign {
+        say!(json, "{}", describe_signatures(&signed).trim_end());
+    }
+    if let (true, SignedTx::Eip7702(tx)) = (args.debug_auth, &signed) {
+        for (i, auth) in tx.authorization_list.iter().enumerate() {
+            say!(json, "{}", describe_authorization(i, auth));
+        }
+    }
+    if args.node_sign {
+        return send_node_signed(&provider, from_addr, &signed, args.strategy, json).await;
+    }
+
+    // 6. RLP-encode and send
+    let mut raw_tx = signed.rlp_encode_signed();
+    say!(
+        json,
+        "Raw signed TX: {}",
+        args.output_format.render(&raw_tx)
+    );
+    if args.explain {
+        say!(json, "{}", explain(&raw_tx)?);
+    }
+
+    if args.simulate {
+        let result = simulate_bundle(&provider, std::slice::from_ref(&signed))
+            .await?
+            .remove(0);
+        if let Some(error) = result.error {
+            let reason = revert_reason(&result.output).unwrap_or(error);
+            return Err(format!("simulation failed: {reason}").into());
+        }
+        say!(json, "Simulation: success, {} gas used", result.gas_used);
+        if !result.output.is_empty() {
+            say!(json, "Simulation output: 0x{}", hex::encode(&result.output));
+        }
+    }
+
+    let nonce_retries = if args.auto_nonce_retry {
+        MAX_NONCE_RETRIES
+    } else {
+        0
+    };
+    let submit = submit(
+        &provider,
+        &signer,
+        &mut raw_tx,
+        from_addr,
+        &mut signed,
+        nonce_retries,
+        json,
+    );
+    let (tx_hash, receipt) = dispatch(&provider, args.strategy, submit, json).await?;
+    // not sent, but the hash is already fixed by the signed bytes
+    let tx_hash = tx_hash.unwrap_or_else(|| {
+        let tx_hash = keccak256(&raw_tx);
+        say!(json, "Hash: {tx_hash}");
+        tx_hash
+    });
+    // a mined 7702 tx skips authorizations that don't check out, so look
+    if let (true, Some(TxStatus::Success), SignedTx::Eip7702(tx)) = (
+        args.verify_delegation,
+        receipt.as_ref().and_then(|receipt| receipt.status),
+        &signed,
+    ) {
+        for auth in &tx.authorization_list {
+            let authority = auth.recover_authority()?;
+            wait_for_delegation(&provider, authority, auth.address, args.wait_timeout).await?;
+            say!(json, "Delegation active: {authority} -> {}", auth.address);
+        }
+    }
+    // delegated code runs as the authority, so its events carry the authority's address
+    // rather than `to`'s: take every log of the tx, whichever account emitted it
+    if let (true, Some(receipt), SignedTx::Eip7702(_)) = (args.show_logs, &receipt, &signed) {
+        let filter = LogFilter {
+            from_block: BlockTag::Number(receipt.block_number),
+            to_block: BlockTag::Number(receipt.block_number),
+            address: None,
+            topics: vec![],
+        };
+        let logs = get_logs(&provider, &filter).await?;
+        let logs: Vec<_> = logs
+            .iter()
+            .filter(|log| log.transaction_hash == Some(tx_hash))
+            .collect();
+        say!(json, "Logs: {}", logs.len());
+        for log in logs {
+            say!(json, "  {} data 0x{}", log.address, hex::encode(&log.data));
+            for topic in &log.topics {
+                say!(json, "    topic {topic}");
+            }
+        }
+    }
+    if json {
+        let (to, nonce) = match &signed {
+            SignedTx::Legacy(tx) => (tx.to, tx.nonce),
+            SignedTx::Eip1559(tx) => (tx.to, tx.nonce),
+            SignedTx::Eip7702(tx) => (tx.to, tx.nonce),
+        };
+        let status = match receipt.as_ref().and_then(|receipt| receipt.status) {
+            Some(TxStatus::Success) => Some("success"),
+            Some(TxStatus::Reverted) => Some("reverted"),
+            None => None,
+        };
+        let output = serde_json::json!({
+            "from": from_addr,
+            "to": to,
+            "nonce": nonce,
+            "raw_tx": args.output_format.render(&raw_tx),
+            "tx_hash": tx_hash,
+            "receipt": receipt,
+            "status": status,
+        });
+        println!("{output}");
+        return Ok(());
+    }
+    let Some(receipt) = receipt else {
+        return Ok(());
+    };
+    print_receipt(&receipt);
+    // older nodes leave the price out of the receipt, so work it out
+    let price = match (receipt.effective_gas_price, &signed) {
+        (Some(price), _) => Some(price),
+        (None, SignedTx::Legacy(tx)) => Some(tx.gas_price),
+        (None, SignedTx::Eip1559(tx)) => {
+            mined_gas_price(
+                &provider,
+                receipt.block_number,
+                tx.max_fee_per_gas,
+                tx.max_priority_fee_per_gas,
+            )
+            .await?
+        }
+        (None, SignedTx::Eip7702(tx)) => {
+            mined_gas_price(
+                &provider,
+                receipt.block_number,
+                tx.max_fee_per_gas,
+                tx.max_priority_fee_per_gas,
+            )
+            .await?
+        }
+    };
+    if let Some(price) = price {
+        let fee = price.saturating_mul(U256::from(receipt.gas_used));
+        println!(
+            "Fee paid: {} ETH at {} gwei",
+            format_ether(fee),
+            format_gwei(price)
+        );
     }
 
     Ok(())
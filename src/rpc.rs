@@ -0,0 +1,1654 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+
+use alloy::primitives::{keccak256, Address, Bytes, B256, U256};
+use reqwest::{header::RETRY_AFTER, Client, Response, StatusCode};
+use secp256k1::SecretKey;
+use serde::{
+    de::{self, DeserializeOwned},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use tokio::time::Instant;
+
+use crate::{
+    abi::revert_reason,
+    decode::SignedTx,
+    era::ERA_TX_TYPE,
+    error::TxError,
+    gas::apply_bps,
+    hexutil::parse_hex,
+    signing::{address_from_key, recover_address},
+    tx::{
+        delegation_designator, normalize_access_list, Authorization7702, CodeKind,
+        Eip7702Transaction,
+    },
+};
+
+#[derive(Serialize)]
+pub struct JsonRpcRequest<'a, T> {
+    pub jsonrpc: &'static str,
+    pub method: &'a str,
+    pub params: T,
+    pub id: u64,
+}
+
+// missing `result`/`error` fields deserialize as `None`
+#[derive(Deserialize)]
+pub struct JsonRpcResponse<T> {
+    pub id: Option<u64>,
+    pub result: Option<T>,
+    pub error: Option<JsonRpcError>,
+}
+
+/// The `error` member of a JSON-RPC response.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    /// Extra detail some nodes attach, e.g. the revert data of a failed call.
+    #[serde(default)]
+    pub data: Option<serde_json::Value>,
+}
+
+impl JsonRpcError {
+    /// The `Error(string)` message in `data`, for nodes that answer a
+    /// reverted call with the raw revert data.
+    pub fn revert_reason(&self) -> Option<String> {
+        let data = parse_hex(self.data.as_ref()?.as_str()?).ok()?;
+        revert_reason(&data)
+    }
+}
+
+impl fmt::Display for JsonRpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)?;
+        // geth already puts the reason in the message
+        match self.revert_reason() {
+            Some(reason) if !self.message.contains(&reason) => write!(f, ": {reason}")?,
+            _ => {}
+        }
+        write!(f, " (code {})", self.code)?;
+        if let Some(data) = &self.data {
+            write!(f, ", data: {data}")?;
+        }
+        Ok(())
+    }
+}
+
+/// How many times a rate-limited (429) request is retried before giving up.
+const MAX_RATE_LIMIT_RETRIES: u32 = 3;
+/// Used when a 429 doesn't come with a usable `Retry-After` header.
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(1);
+/// Don't let a misbehaving endpoint park us for hours.
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+/// Far more than any answer we ask for; stops a hostile endpoint from
+/// exhausting memory.
+const MAX_RESPONSE_SIZE: usize = 8 * 1024 * 1024;
+
+/// A JSON-RPC endpoint plus the state shared by every request sent to it.
+///
+/// Request ids come from a per-provider counter, so calls multiplexed onto a
+/// shared connection or proxy never reuse an id.
+pub struct Provider {
+    url: String,
+    client: Client,
+    next_id: AtomicU64,
+    null_nonce_as_zero: bool,
+    poll_interval: Duration,
+    wait_timeout: Duration,
+    max_response_size: usize,
+}
+
+impl Provider {
+    pub fn new(url: impl Into<String>) -> Self {
+        Self {
+            url: url.into(),
+            client: Client::new(),
+            next_id: AtomicU64::new(1),
+            null_nonce_as_zero: false,
+            poll_interval: RECEIPT_POLL_INTERVAL,
+            wait_timeout: RECEIPT_WAIT_TIMEOUT,
+            max_response_size: MAX_RESPONSE_SIZE,
+        }
+    }
+
+    /// Some proxies answer `eth_getTransactionCount` with a `null` result for
+    /// accounts they have never seen. By default that's an error; with this
+    /// set it is read as nonce 0 instead.
+    pub fn with_null_nonce_as_zero(mut self, null_nonce_as_zero: bool) -> Self {
+        self.null_nonce_as_zero = null_nonce_as_zero;
+        self
+    }
+
+    /// How long `wait_for_receipt` sleeps between polls.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// How long `wait_for_receipt` goes on without progress before giving up.
+    pub fn with_wait_timeout(mut self, wait_timeout: Duration) -> Self {
+        self.wait_timeout = wait_timeout;
+        self
+    }
+
+    /// The largest response body read, in bytes (8 MiB by default);
+    /// anything bigger is an error before it is parsed.
+    pub fn with_max_response_size(mut self, max_response_size: usize) -> Self {
+        self.max_response_size = max_response_size;
+        self
+    }
+
+    fn next_id(&self) -> u64 {
+        self.next_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Sends a single JSON-RPC request.
+    pub async fn rpc_call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        params: P,
+    ) -> Result<JsonRpcResponse<R>, TxError> {
+        let req = JsonRpcRequest {
+            jsonrpc: "2.0",
+            method,
+            params,
+            id: self.next_id(),
+        };
+        trace_request(&req);
+        let resp: JsonRpcResponse<R> = self.post(method, &req).await?;
+        trace_response(method, &resp);
+        Ok(resp)
+    }
+
+    /// Sends several requests as one JSON-RPC batch.
+    ///
+    /// Batch responses may come back in any order, so they are matched to
+    /// their requests by id; the result is in the order of `calls`.
+    pub async fn batch_call<P: Serialize, R: DeserializeOwned>(
+        &self,
+        calls: Vec<(&str, P)>,
+    ) -> Result<Vec<JsonRpcResponse<R>>, TxError> {
+        let reqs: Vec<_> = calls
+            .into_iter()
+            .map(|(method, params)| JsonRpcRequest {
+                jsonrpc: "2.0",
+                method,
+                params,
+                id: self.next_id(),
+            })
+            .collect();
+        reqs.iter().for_each(trace_request);
+
+        let resps: Vec<JsonRpcResponse<R>> = self.post("batch", &reqs).await?;
+        let mut by_id: HashMap<u64, JsonRpcResponse<R>> = resps
+            .into_iter()
+            .filter_map(|resp| Some((resp.id?, resp)))
+            .collect();
+
+        reqs.iter()
+            .map(|req| {
+                let resp = by_id.remove(&req.id).ok_or_else(|| {
+                    TxError::Rpc(format!("no response for {} (id {})", req.method, req.id))
+                })?;
+                trace_response(req.method, &resp);
+                Ok(resp)
+            })
+            .collect()
+    }
+
+    /// POSTs `body`, checking the HTTP status before the body is touched.
+    ///
+    /// 429 waits for `Retry-After` and tries again, any other non-2xx (e.g.
+    /// an HTML 502 page) becomes `TxError::Rpc`, and only 2xx bodies are
+    /// parsed as JSON, once they are known to fit the size limit.
+    async fn post<B: Serialize, R: DeserializeOwned>(
+        &self,
+        method: &str,
+        body: &B,
+    ) -> Result<R, TxError> {
+        let mut attempt = 0;
+        loop {
+            let resp = self.client.post(&self.url).json(body).send().await?;
+            let status = resp.status();
+
+            if status == StatusCode::TOO_MANY_REQUESTS && attempt < MAX_RATE_LIMIT_RETRIES {
+                let wait = retry_after(&resp).unwrap_or(DEFAULT_RETRY_AFTER);
+                tracing::warn!(
+                    method,
+                    retry_after_secs = wait.as_secs(),
+                    "rate limited, retrying"
+                );
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+                continue;
+            }
+            if !status.is_success() {
+                return Err(TxError::Rpc(format!("{method} failed with HTTP {status}")));
+            }
+
+            let body = self.read_body(method, resp).await?;
+            return serde_json::from_slice(&body)
+                .map_err(|err| TxError::Rpc(format!("invalid JSON in {method} response: {err}")));
+        }
+    }
+
+    /// Reads the body a chunk at a time, stopping as soon as it outgrows
+    /// `max_response_size`, whatever `Content-Length` claimed.
+    async fn read_body(&self, method: &str, mut resp: Response) -> Result<Vec<u8>, TxError> {
+        let too_large = || {
+            TxError::Rpc(format!(
+                "{method} response too large (over {} bytes)",
+                self.max_response_size
+            ))
+        };
+        let announced = resp.content_length().unwrap_or(0);
+        if announced > self.max_response_size as u64 {
+            return Err(too_large());
+        }
+        let mut body = Vec::with_capacity(announced as usize);
+        while let Some(chunk) = resp.chunk().await? {
+            if body.len() + chunk.len() > self.max_response_size {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body)
+    }
+}
+
+/// How much of the params `trace_request` shows.
+const PARAMS_PREVIEW_LEN: usize = 120;
+
+/// Debug event per request, so ids can be matched to `trace_response`
+/// when a proxy answers out of order or with the wrong id.
+fn trace_request<P: Serialize>(req: &JsonRpcRequest<'_, P>) {
+    if !tracing::enabled!(tracing::Level::DEBUG) {
+        return;
+    }
+    let mut params = serde_json::to_string(&req.params).unwrap_or_default();
+    if params.len() > PARAMS_PREVIEW_LEN {
+        let end = params.floor_char_boundary(PARAMS_PREVIEW_LEN);
+        params.truncate(end);
+        params.push_str("...");
+    }
+    tracing::debug!(id = req.id, method = req.method, params, "rpc request");
+}
+
+fn trace_response<R>(method: &str, resp: &JsonRpcResponse<R>) {
+    tracing::debug!(
+        id = ?resp.id,
+        method,
+        error = resp.error.as_ref().map(tracing::field::display),
+        "rpc response"
+    );
+}
+
+/// Parses `Retry-After` in its delta-seconds form (the HTTP-date form is ignored).
+fn retry_after(resp: &Response) -> Option<Duration> {
+    let secs = resp
+        .headers()
+        .get(RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim()
+        .parse::<u64>()
+        .ok()?;
+    Some(Duration::from_secs(secs).min(MAX_RETRY_AFTER))
+}
+
+pub async fn get_nonce(provider: &Provider, addr: Address) -> Result<U256, TxError> {
+    get_nonce_at(provider, addr, BlockTag::Latest).await
+}
+
+/// The nonce of `addr` as of `tag`; at `pending` that counts the txs the
+/// node has queued for it too.
+pub async fn get_nonce_at(
+    provider: &Provider,
+    addr: Address,
+    tag: BlockTag,
+) -> Result<U256, TxError> {
+    let params = (format!("0x{:x}", addr), tag);
+    let resp: JsonRpcResponse<String> = provider
+        .rpc_call("eth_getTransactionCount", &params)
+        .await?;
+
+    let nonce_value = parse_nonce(provider, addr, resp)?;
+    tracing::debug!(%addr, nonce = %nonce_value, "nonce");
+    Ok(nonce_value)
+}
+
+/// Fetches the nonces of several accounts in a single batch request.
+pub async fn get_nonces(provider: &Provider, addrs: &[Address]) -> Result<Vec<U256>, TxError> {
+    get_nonces_at(provider, addrs, BlockTag::Latest).await
+}
+
+/// [`get_nonces`] as of `tag`.
+pub async fn get_nonces_at(
+    provider: &Provider,
+    addrs: &[Address],
+    tag: BlockTag,
+) -> Result<Vec<U256>, TxError> {
+    let calls = addrs
+        .iter()
+        .map(|addr| ("eth_getTransactionCount", (format!("0x{:x}", addr), tag)))
+        .collect();
+    let resps: Vec<JsonRpcResponse<String>> = provider.batch_call(calls).await?;
+
+    let nonces = addrs
+        .iter()
+        .zip(resps)
+        .map(|(addr, resp)| parse_nonce(provider, *addr, resp))
+        .collect::<Result<Vec<_>, _>>()?;
+    for (addr, nonce) in addrs.iter().zip(&nonces) {
+        tracing::debug!(%addr, %nonce, "nonce");
+    }
+    Ok(nonces)
+}
+
+/// Signs one authorization per `(authority key, delegate)` pair, fetching
+/// all the authority nonces in a single batch request.
+///
+/// An authority that also sends the tx must sign for its nonce + 1; that
+/// is up to the caller.
+pub async fn build_authorizations(
+    provider: &Provider,
+    chain_id: u64,
+    pairs: &[(SecretKey, Address)],
+) -> Result<Vec<Authorization7702>, TxError> {
+    if pairs.is_empty() {
+        return Ok(vec![]);
+    }
+    let authorities: Vec<Address> = pairs.iter().map(|(key, _)| address_from_key(key)).collect();
+    let nonces = get_nonces(provider, &authorities).await?;
+    pairs
+        .iter()
+        .zip(nonces)
+        .map(|((key, delegate), nonce)| Authorization7702::sign(chain_id, *delegate, nonce, key))
+        .collect()
+}
+
+fn parse_nonce(
+    provider: &Provider,
+    addr: Address,
+    resp: JsonRpcResponse<String>,
+) -> Result<U256, TxError> {
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let Some(nonce_hex) = resp.result else {
+        if provider.null_nonce_as_zero {
+            return Ok(U256::ZERO);
+        }
+        return Err(TxError::Rpc(format!(
+            "eth_getTransactionCount returned no result for 0x{:x}",
+            addr
+        )));
+    };
+    U256::from_str_radix(nonce_hex.trim_start_matches("0x"), 16)
+        .map_err(|err| TxError::Rpc(format!("invalid nonce {nonce_hex}: {err}")))
+}
+
+pub async fn fetch_chain_id(provider: &Provider) -> Result<u64, TxError> {
+    let resp: JsonRpcResponse<String> = provider.rpc_call("eth_chainId", ()).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let chain_id_hex = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("eth_chainId returned no result".to_string()))?;
+    u64::from_str_radix(chain_id_hex.trim_start_matches("0x"), 16)
+        .map_err(|err| TxError::Rpc(format!("invalid chain id {chain_id_hex}: {err}")))
+}
+
+/// Which block `get_block` asks for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockTag {
+    Latest,
+    Pending,
+    Safe,
+    Finalized,
+    Number(u64),
+}
+
+impl Serialize for BlockTag {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            BlockTag::Latest => serializer.serialize_str("latest"),
+            BlockTag::Pending => serializer.serialize_str("pending"),
+            BlockTag::Safe => serializer.serialize_str("safe"),
+            BlockTag::Finalized => serializer.serialize_str("finalized"),
+            BlockTag::Number(number) => serializer.serialize_str(&format!("0x{number:x}")),
+        }
+    }
+}
+
+/// The header fields this tool looks at.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Block {
+    #[serde(with = "alloy::serde::quantity")]
+    pub number: u64,
+    /// `None` before London.
+    pub base_fee_per_gas: Option<U256>,
+    /// `None` before Prague, which added it (EIP-7685) along with 7702.
+    pub requests_hash: Option<B256>,
+    #[serde(with = "alloy::serde::quantity")]
+    pub timestamp: u64,
+    #[serde(with = "alloy::serde::quantity")]
+    pub gas_limit: u64,
+}
+
+/// The block at `tag`, without its transactions.
+pub async fn get_block(provider: &Provider, tag: BlockTag) -> Result<Block, TxError> {
+    let resp: JsonRpcResponse<Block> = provider
+        .rpc_call("eth_getBlockByNumber", (tag, false))
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    // `null` for a number past the head
+    resp.result
+        .ok_or_else(|| TxError::Rpc(format!("eth_getBlockByNumber found no {tag:?} block")))
+}
+
+pub async fn block_number(provider: &Provider) -> Result<u64, TxError> {
+    let resp: JsonRpcResponse<String> = provider.rpc_call("eth_blockNumber", ()).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let number_hex = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("eth_blockNumber returned no result".to_string()))?;
+    u64::from_str_radix(number_hex.trim_start_matches("0x"), 16)
+        .map_err(|err| TxError::Rpc(format!("invalid block number {number_hex}: {err}")))
+}
+
+/// How many recent blocks `oracle_fees` asks `eth_feeHistory` about, and
+/// which percentile of each block's priority fees it takes.
+const FEE_HISTORY_BLOCKS: u64 = 5;
+const FEE_HISTORY_PERCENTILE: f64 = 50.0;
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeHistory {
+    /// One entry per block, plus the base fee of the next one last.
+    pub base_fee_per_gas: Vec<U256>,
+    /// Per block, the tip at each percentile that was asked for.
+    #[serde(default)]
+    pub reward: Vec<Vec<U256>>,
+}
+
+/// A `--fee-strategy` preset: which fee history percentile the tip comes
+/// from and how much the base fee may grow before the tx is priced out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum FeeStrategy {
+    /// 10th percentile tip, 1.2x the base fee
+    Slow,
+    /// 50th percentile tip, 1.5x the base fee
+    Normal,
+    /// 75th percentile tip, 1.75x the base fee
+    Fast,
+    /// 90th percentile tip, 2x the base fee
+    Urgent,
+}
+
+/// The percentiles [`strategy_fees`] asks for, one per [`FeeStrategy`], so
+/// a single fee history prices every preset.
+const STRATEGY_PERCENTILES: [f64; 4] = [10.0, 50.0, 75.0, 90.0];
+
+impl FeeStrategy {
+    /// Index of this preset's percentile in [`STRATEGY_PERCENTILES`].
+    fn percentile_index(self) -> usize {
+        self as usize
+    }
+
+    /// How far above the base fee the max fee sits, in basis points for
+    /// [`apply_bps`].
+    fn base_fee_bump(self) -> u64 {
+        match self {
+            FeeStrategy::Slow => 2_000,
+            FeeStrategy::Normal => 5_000,
+            FeeStrategy::Fast => 7_500,
+            FeeStrategy::Urgent => 10_000,
+        }
+    }
+
+    /// `(max_fee_per_gas, max_priority_fee_per_gas)` from a fee history
+    /// fetched with [`STRATEGY_PERCENTILES`]: the highest tip any block paid
+    /// at this preset's percentile, on top of the scaled next base fee.
+    /// `None` if the history has no base fee.
+    pub fn fees(self, history: &FeeHistory) -> Option<(U256, U256)> {
+        let base_fee = *history.base_fee_per_gas.last()?;
+        // blocks without txs report no reward, which is a zero tip
+        let tip = history
+            .reward
+            .iter()
+            .filter_map(|rewards| rewards.get(self.percentile_index()).copied())
+            .max()
+            .unwrap_or_default();
+        let max_fee = apply_bps(base_fee, self.base_fee_bump()).saturating_add(tip);
+        Some((max_fee, tip))
+    }
+}
+
+/// `(max_fee_per_gas, max_priority_fee_per_gas)` for `strategy`, from the
+/// last [`FEE_HISTORY_BLOCKS`] blocks' `eth_feeHistory`.
+pub async fn strategy_fees(
+    provider: &Provider,
+    strategy: FeeStrategy,
+) -> Result<(U256, U256), TxError> {
+    let resp: JsonRpcResponse<FeeHistory> = provider
+        .rpc_call(
+            "eth_feeHistory",
+            (
+                format!("0x{FEE_HISTORY_BLOCKS:x}"),
+                "latest",
+                STRATEGY_PERCENTILES,
+            ),
+        )
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .as_ref()
+        .and_then(|history| strategy.fees(history))
+        .ok_or_else(|| TxError::Rpc("eth_feeHistory returned no base fee".to_string()))
+}
+
+/// The node's own tip suggestion, from `eth_maxPriorityFeePerGas`.
+pub async fn max_priority_fee(provider: &Provider) -> Result<U256, TxError> {
+    let resp: JsonRpcResponse<U256> = provider.rpc_call("eth_maxPriorityFeePerGas", ()).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc("eth_maxPriorityFeePerGas returned no result".to_string()))
+}
+
+/// Which suggestion `oracle_fees` takes the tip from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum TipSource {
+    /// `eth_maxPriorityFeePerGas`, falling back to the fee history
+    #[default]
+    Node,
+    /// The fee history percentile, falling back to `eth_maxPriorityFeePerGas`
+    History,
+    /// The highest of every source, `eth_gasPrice` included
+    Highest,
+}
+
+/// `(max_fee_per_gas, max_priority_fee_per_gas)` from the fee sources the
+/// node supports: `eth_feeHistory`, `eth_maxPriorityFeePerGas` and
+/// `eth_gasPrice`.
+///
+/// The tip comes from the source `tip_source` prefers, or the next one if
+/// the node doesn't answer it; the tip implied by `eth_gasPrice` is the last
+/// resort. [`TipSource::Highest`] lets every source have a say instead, so
+/// one low-ball estimate can't underprice the tx. The max fee leaves room
+/// for the base fee to double. A method the node refuses is skipped; only
+/// when none of them answer is this an error.
+pub async fn oracle_fees(
+    provider: &Provider,
+    tip_source: TipSource,
+) -> Result<(U256, U256), TxError> {
+    let history: Option<FeeHistory> = optional_call(
+        provider,
+        "eth_feeHistory",
+        (
+            format!("0x{FEE_HISTORY_BLOCKS:x}"),
+            "latest",
+            [FEE_HISTORY_PERCENTILE],
+        ),
+    )
+    .await?;
+    let node_tip = match max_priority_fee(provider).await {
+        Ok(tip) => Some(tip),
+        // most likely a node without the method
+        Err(TxError::Rpc(_)) => None,
+        Err(err) => return Err(err),
+    };
+    let gas_price: Option<U256> = optional_call(provider, "eth_gasPrice", ()).await?;
+
+    let base_fee = match history
+        .as_ref()
+        .and_then(|history| history.base_fee_per_gas.last())
+    {
+        Some(base_fee) => *base_fee,
+        None => get_block(provider, BlockTag::Latest)
+            .await?
+            .base_fee_per_gas
+            .unwrap_or_default(),
+    };
+    let history_tip = history.and_then(|history| {
+        history
+            .reward
+            .iter()
+            .filter_map(|rewards| rewards.first().copied())
+            .max()
+    });
+    // a legacy gas price is the base fee plus whatever tip the node suggests
+    let gas_price_tip = gas_price.map(|price| price.saturating_sub(base_fee));
+
+    let candidates = match tip_source {
+        TipSource::Node => [node_tip, history_tip, gas_price_tip],
+        TipSource::History | TipSource::Highest => [history_tip, node_tip, gas_price_tip],
+    };
+    let mut candidates = candidates.into_iter().flatten();
+    let priority_fee = match tip_source {
+        TipSource::Highest => candidates.max(),
+        TipSource::Node | TipSource::History => candidates.next(),
+    }
+    .ok_or_else(|| TxError::Rpc("the node supports none of the fee methods".to_string()))?;
+    let max_fee = apply_bps(base_fee, 10_000)
+        .saturating_add(priority_fee)
+        .max(gas_price.unwrap_or_default());
+    Ok((max_fee, priority_fee))
+}
+
+/// `None` if the node answers with an error, which for these methods almost
+/// always means it doesn't implement them.
+async fn optional_call<P: Serialize, R: DeserializeOwned>(
+    provider: &Provider,
+    method: &str,
+    params: P,
+) -> Result<Option<R>, TxError> {
+    let resp: JsonRpcResponse<R> = provider.rpc_call(method, params).await?;
+    Ok(match resp.error {
+        Some(_) => None,
+        None => resp.result,
+    })
+}
+
+/// Whether `addr` is an EOA, a contract, or an EOA delegating somewhere,
+/// going by its current code.
+pub async fn code_kind(provider: &Provider, addr: Address) -> Result<CodeKind, TxError> {
+    let resp: JsonRpcResponse<Bytes> = provider.rpc_call("eth_getCode", (addr, "latest")).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let code = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("eth_getCode returned no result".to_string()))?;
+    Ok(CodeKind::of(&code))
+}
+
+/// Where `addr` currently delegates to, or `None` if its code isn't a
+/// 7702 designator (no code, or a regular contract).
+pub async fn get_delegation(
+    provider: &Provider,
+    addr: Address,
+) -> Result<Option<Address>, TxError> {
+    Ok(match code_kind(provider, addr).await? {
+        CodeKind::Delegated(delegate) => Some(delegate),
+        CodeKind::Eoa | CodeKind::Contract => None,
+    })
+}
+
+/// Polls until `authority` delegates to `expected_delegate`, which is
+/// only once the 7702 tx that sets it is mined, for at most `timeout`.
+pub async fn wait_for_delegation(
+    provider: &Provider,
+    authority: Address,
+    expected_delegate: Address,
+    timeout: Duration,
+) -> Result<(), TxError> {
+    let started = Instant::now();
+    loop {
+        if get_delegation(provider, authority).await? == Some(expected_delegate) {
+            return Ok(());
+        }
+        if started.elapsed() >= timeout {
+            return Err(TxError::DelegationTimeout {
+                authority,
+                delegate: expected_delegate,
+            });
+        }
+        tokio::time::sleep(provider.poll_interval).await;
+    }
+}
+
+#[derive(Serialize)]
+struct CallRequest {
+    to: Address,
+    data: Bytes,
+}
+
+/// `eth_call` against the latest block.
+pub async fn eth_call(provider: &Provider, to: Address, data: &[u8]) -> Result<Vec<u8>, TxError> {
+    let call = CallRequest {
+        to,
+        data: data.to_vec().into(),
+    };
+    let resp: JsonRpcResponse<Bytes> = provider.rpc_call("eth_call", (call, "latest")).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .map(|data| data.to_vec())
+        .ok_or_else(|| TxError::Rpc("eth_call returned no result".to_string()))
+}
+
+/// Runs several `eth_call`s against the latest block in one batch request.
+///
+/// The outer error is for the batch as a whole; a call that reverts or
+/// otherwise fails only fails its own slot, in the order of `calls`.
+pub async fn eth_call_batch(
+    provider: &Provider,
+    calls: Vec<(Address, Vec<u8>)>,
+) -> Result<Vec<Result<Vec<u8>, TxError>>, TxError> {
+    let calls = calls
+        .into_iter()
+        .map(|(to, data)| {
+            let call = CallRequest {
+                to,
+                data: data.into(),
+            };
+            ("eth_call", (call, "latest"))
+        })
+        .collect();
+    let resps: Vec<JsonRpcResponse<Bytes>> = provider.batch_call(calls).await?;
+
+    Ok(resps
+        .into_iter()
+        .map(|resp| {
+            if let Some(err) = resp.error {
+                return Err(TxError::Rpc(err.to_string()));
+            }
+            resp.result
+                .map(|data| data.to_vec())
+                .ok_or_else(|| TxError::Rpc("eth_call returned no result".to_string()))
+        })
+        .collect())
+}
+
+#[derive(Serialize)]
+struct CodeOverride {
+    code: Bytes,
+}
+
+/// State override giving `authority` the designator code for `delegate`.
+fn delegation_override(authority: Address, delegate: Address) -> HashMap<Address, CodeOverride> {
+    HashMap::from([(
+        authority,
+        CodeOverride {
+            code: delegation_designator(delegate).to_vec().into(),
+        },
+    )])
+}
+
+/// `eth_call`s `authority` as if it already delegated to `delegate`.
+///
+/// Plain `eth_call` ignores authorization lists, so the delegation is faked
+/// with a state override that gives the authority the designator code.
+pub async fn call_as_delegated(
+    provider: &Provider,
+    authority: Address,
+    delegate: Address,
+    data: &[u8],
+) -> Result<Vec<u8>, TxError> {
+    let call = CallRequest {
+        to: authority,
+        data: data.to_vec().into(),
+    };
+    let resp: JsonRpcResponse<Bytes> = provider
+        .rpc_call(
+            "eth_call",
+            (call, "latest", delegation_override(authority, delegate)),
+        )
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .map(|data| data.to_vec())
+        .ok_or_else(|| TxError::Rpc("eth_call returned no result".to_string()))
+}
+
+#[derive(Serialize)]
+struct AccessListRequest {
+    from: Address,
+    to: Option<Address>,
+    value: U256,
+    data: Bytes,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListItem {
+    address: Address,
+    storage_keys: Vec<B256>,
+}
+
+impl AccessListItem {
+    fn from_entry((address, keys): &(Address, Vec<U256>)) -> Self {
+        Self {
+            address: *address,
+            storage_keys: keys.iter().map(|key| B256::from(*key)).collect(),
+        }
+    }
+
+    fn into_entry(self) -> (Address, Vec<U256>) {
+        let keys = self
+            .storage_keys
+            .into_iter()
+            .map(|key| U256::from_be_bytes(key.0))
+            .collect();
+        (self.address, keys)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListResult {
+    access_list: Vec<AccessListItem>,
+    /// Set when the simulated call failed; the list is then incomplete.
+    error: Option<String>,
+}
+
+/// `eth_createAccessList` for a tx from `from`, run as if `authority`
+/// already delegated to `delegate` (the same override as
+/// [`call_as_delegated`]), since the node can't apply the authorization
+/// itself. The list comes back normalized.
+pub async fn create_access_list(
+    provider: &Provider,
+    from: Address,
+    to: Option<Address>,
+    value: U256,
+    data: &[u8],
+    authority: Address,
+    delegate: Address,
+) -> Result<Vec<(Address, Vec<U256>)>, TxError> {
+    let call = AccessListRequest {
+        from,
+        to,
+        value,
+        data: data.to_vec().into(),
+    };
+    let resp: JsonRpcResponse<AccessListResult> = provider
+        .rpc_call(
+            "eth_createAccessList",
+            (call, "latest", delegation_override(authority, delegate)),
+        )
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let result = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("eth_createAccessList returned no result".to_string()))?;
+    if let Some(err) = result.error {
+        return Err(TxError::Rpc(format!("eth_createAccessList: {err}")));
+    }
+    Ok(normalize_access_list(
+        result
+            .access_list
+            .into_iter()
+            .map(AccessListItem::into_entry)
+            .collect(),
+    ))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EstimateGasRequest<'a> {
+    from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Address>,
+    value: U256,
+    data: Bytes,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    authorization_list: &'a [Authorization7702],
+}
+
+/// `eth_estimateGas` for a 7702 tx from `from`, authorizations and all,
+/// so their cost is part of the estimate.
+pub async fn estimate_gas(
+    provider: &Provider,
+    from: Address,
+    tx: &Eip7702Transaction,
+) -> Result<u64, TxError> {
+    let call = EstimateGasRequest {
+        from,
+        to: tx.to,
+        value: tx.value,
+        data: tx.data.clone().into(),
+        authorization_list: &tx.authorization_list,
+    };
+    request_gas_estimate(provider, &call).await
+}
+
+/// `eth_estimateGas` for a plain call from `from`, for the tx types that
+/// carry no authorizations.
+pub async fn estimate_call_gas(
+    provider: &Provider,
+    from: Address,
+    to: Option<Address>,
+    value: U256,
+    data: &[u8],
+) -> Result<u64, TxError> {
+    let call = EstimateGasRequest {
+        from,
+        to,
+        value,
+        data: data.to_vec().into(),
+        authorization_list: &[],
+    };
+    request_gas_estimate(provider, &call).await
+}
+
+/// `eth_estimateGas` for deploying `initcode` from `from`, constructor
+/// included.
+pub async fn estimate_deploy_gas(
+    provider: &Provider,
+    from: Address,
+    initcode: &[u8],
+) -> Result<u64, TxError> {
+    let call = EstimateGasRequest {
+        from,
+        to: None,
+        value: U256::ZERO,
+        data: initcode.to_vec().into(),
+        authorization_list: &[],
+    };
+    request_gas_estimate(provider, &call).await
+}
+
+async fn request_gas_estimate(
+    provider: &Provider,
+    call: &EstimateGasRequest<'_>,
+) -> Result<u64, TxError> {
+    let resp: JsonRpcResponse<U256> = provider
+        .rpc_call("eth_estimateGas", (call, "latest"))
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let gas = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("eth_estimateGas returned no result".to_string()))?;
+    u64::try_from(gas).map_err(|_| TxError::Rpc(format!("gas estimate {gas} is out of range")))
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct BundleCall<'a> {
+    from: Address,
+    to: Option<Address>,
+    gas: U256,
+    value: U256,
+    data: Bytes,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    authorization_list: &'a [Authorization7702],
+}
+
+#[derive(Deserialize)]
+struct BundleTrace {
+    #[serde(default)]
+    output: Bytes,
+    /// Every call frame; the first is the tx itself.
+    #[serde(default)]
+    trace: Vec<TraceFrame>,
+}
+
+#[derive(Deserialize)]
+struct TraceFrame {
+    error: Option<String>,
+    result: Option<TraceFrameResult>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TraceFrameResult {
+    gas_used: U256,
+}
+
+/// How one tx of a [`simulate_bundle`] went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimResult {
+    pub success: bool,
+    /// Gas the execution used, without the intrinsic cost.
+    pub gas_used: u64,
+    /// Return data, or the revert data of a failed tx.
+    pub output: Vec<u8>,
+    /// Why a failed tx failed, as the node words it (e.g. `Reverted`).
+    pub error: Option<String>,
+}
+
+/// Runs `txs` one after another on top of the latest block with
+/// `trace_callMany`, each seeing the state the previous ones left.
+///
+/// Unlike `eth_call` this carries the authorization list, so a 7702 tx is
+/// simulated with its delegations in place. Only nodes with the `trace`
+/// namespace (Reth, Erigon, ...) answer it.
+pub async fn simulate_bundle(
+    provider: &Provider,
+    txs: &[SignedTx],
+) -> Result<Vec<SimResult>, TxError> {
+    let calls = txs
+        .iter()
+        .map(|tx| {
+            let call = match tx {
+                SignedTx::Legacy(tx) => BundleCall {
+                    from: tx.recover_sender()?,
+                    to: tx.to,
+                    gas: tx.gas_limit,
+                    value: tx.value,
+                    data: tx.data.clone().into(),
+                    authorization_list: &[],
+                },
+                SignedTx::Eip1559(tx) => BundleCall {
+                    from: recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity)?,
+                    to: tx.to,
+                    gas: tx.gas_limit,
+                    value: tx.value,
+                    data: tx.data.clone().into(),
+                    authorization_list: &[],
+                },
+                SignedTx::Eip7702(tx) => BundleCall {
+                    from: recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity)?,
+                    to: tx.to,
+                    gas: tx.gas_limit,
+                    value: tx.value,
+                    data: tx.data.clone().into(),
+                    authorization_list: &tx.authorization_list,
+                },
+            };
+            Ok((call, ["trace"]))
+        })
+        .collect::<Result<Vec<_>, TxError>>()?;
+
+    let resp: JsonRpcResponse<Vec<BundleTrace>> = provider
+        .rpc_call("trace_callMany", (calls, "latest"))
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    let traces = resp
+        .result
+        .ok_or_else(|| TxError::Rpc("trace_callMany returned no result".to_string()))?;
+    if traces.len() != txs.len() {
+        return Err(TxError::Rpc(format!(
+            "trace_callMany returned {} results for {} txs",
+            traces.len(),
+            txs.len()
+        )));
+    }
+    Ok(traces
+        .into_iter()
+        .map(|trace| {
+            let top = trace.trace.into_iter().next();
+            let (error, gas_used) = match top {
+                Some(frame) => (
+                    frame.error,
+                    frame
+                        .result
+                        .map_or(0, |result| result.gas_used.saturating_to()),
+                ),
+                None => (None, 0),
+            };
+            SimResult {
+                success: error.is_none(),
+                gas_used,
+                output: trace.output.to_vec(),
+                error,
+            }
+        })
+        .collect())
+}
+
+/// The lists of an `eth_getTransactionByHash` result, enough to check a
+/// fetched tx again (recover its authorities, re-encode it).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RpcTransaction {
+    /// Empty on legacy txs, which don't have one.
+    #[serde(default, deserialize_with = "deserialize_access_list")]
+    pub access_list: Vec<(Address, Vec<U256>)>,
+    /// Empty on anything but 7702 txs.
+    #[serde(default)]
+    pub authorization_list: Vec<Authorization7702>,
+}
+
+fn deserialize_access_list<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<(Address, Vec<U256>)>, D::Error> {
+    let items = Vec::<AccessListItem>::deserialize(deserializer)?;
+    Ok(items.into_iter().map(AccessListItem::into_entry).collect())
+}
+
+/// `eth_getTransactionByHash`; an error if the node doesn't know the tx.
+pub async fn get_transaction(
+    provider: &Provider,
+    tx_hash: B256,
+) -> Result<RpcTransaction, TxError> {
+    let resp: JsonRpcResponse<RpcTransaction> = provider
+        .rpc_call("eth_getTransactionByHash", [tx_hash])
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc(format!("the node doesn't know tx {tx_hash}")))
+}
+
+/// The access list of a tx the node knows, in the order it was signed with.
+pub async fn get_transaction_access_list(
+    provider: &Provider,
+    tx_hash: B256,
+) -> Result<Vec<(Address, Vec<U256>)>, TxError> {
+    Ok(get_transaction(provider, tx_hash).await?.access_list)
+}
+
+/// The node errors worth telling apart from the rest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcErrorKind {
+    /// The tx is already in the node's pool.
+    AlreadyKnown,
+    /// The sender can't cover `gas_limit * fee + value`.
+    InsufficientFunds,
+    /// The tx's nonce is already used, e.g. another tx from the sender got
+    /// in first.
+    NonceTooLow,
+    /// The node doesn't know the tx type, e.g. a type-4 tx sent to a node
+    /// from before Prague.
+    TxTypeNotSupported,
+    Other,
+}
+
+impl RpcErrorKind {
+    /// Classifies an error by its message: clients agree on the wording far
+    /// more than on the error codes.
+    pub fn classify(message: &str) -> Self {
+        let message = message.to_lowercase();
+        if message.contains("already known") {
+            RpcErrorKind::AlreadyKnown
+        } else if message.contains("insufficient funds") {
+            RpcErrorKind::InsufficientFunds
+        } else if message.contains("nonce too low") {
+            RpcErrorKind::NonceTooLow
+        } else if message.contains("type not supported")
+            || message.contains("unsupported transaction type")
+            || message.contains("unsupported tx type")
+        {
+            RpcErrorKind::TxTypeNotSupported
+        } else {
+            RpcErrorKind::Other
+        }
+    }
+
+    /// What to add to the node's own message, when it doesn't say enough.
+    pub fn hint(self) -> Option<&'static str> {
+        match self {
+            RpcErrorKind::TxTypeNotSupported => Some(
+                "the node doesn't accept this tx type; 7702 txs need a node that has \
+                 activated Prague, or use --tx-type 1559",
+            ),
+            _ => None,
+        }
+    }
+}
+
+pub async fn get_balance(provider: &Provider, addr: Address) -> Result<U256, TxError> {
+    let resp: JsonRpcResponse<U256> = provider
+        .rpc_call("eth_getBalance", (addr, "latest"))
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc("eth_getBalance returned no result".to_string()))
+}
+
+/// The raw 32-byte word in storage `slot` of `addr` at `block`, e.g. slot 0
+/// for the example counter's `number`.
+pub async fn get_storage_at(
+    provider: &Provider,
+    addr: Address,
+    slot: U256,
+    block: BlockTag,
+) -> Result<U256, TxError> {
+    let resp: JsonRpcResponse<B256> = provider
+        .rpc_call("eth_getStorageAt", (addr, slot, block))
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .map(|word| U256::from_be_bytes(word.0))
+        .ok_or_else(|| TxError::Rpc("eth_getStorageAt returned no result".to_string()))
+}
+
+/// Broadcasts a signed tx, returning its hash.
+///
+/// A node that already has the tx in its pool answers with an "already
+/// known" error; that is still a successful send, so the hash is computed
+/// locally instead. Not for Era txs, whose hash isn't the keccak of the
+/// envelope: for those the error is returned.
+pub async fn send_raw(provider: &Provider, raw_tx: &[u8]) -> Result<B256, TxError> {
+    let resp: JsonRpcResponse<B256> = provider
+        .rpc_call(
+            "eth_sendRawTransaction",
+            [format!("0x{}", hex::encode(raw_tx))],
+        )
+        .await?;
+    if let Some(err) = resp.error {
+        let kind = RpcErrorKind::classify(&err.message);
+        if kind == RpcErrorKind::AlreadyKnown && raw_tx.first() != Some(&ERA_TX_TYPE) {
+            return Ok(keccak256(raw_tx));
+        }
+        return Err(TxError::Rpc(match kind.hint() {
+            Some(hint) => format!("{err} ({hint})"),
+            None => err.to_string(),
+        }));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc("eth_sendRawTransaction returned no result".to_string()))
+}
+
+/// An unsigned tx in the shape `eth_sendTransaction` takes, for a node that
+/// signs with one of its own unlocked accounts.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionRequest<'a> {
+    #[serde(rename = "type", with = "alloy::serde::quantity")]
+    tx_type: u8,
+    from: Address,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    to: Option<Address>,
+    gas: U256,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    gas_price: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_fee_per_gas: Option<U256>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_priority_fee_per_gas: Option<U256>,
+    value: U256,
+    data: Bytes,
+    nonce: U256,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        with = "alloy::serde::quantity::opt"
+    )]
+    chain_id: Option<u64>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    access_list: Vec<AccessListItem>,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    authorization_list: &'a [Authorization7702],
+}
+
+impl<'a> TransactionRequest<'a> {
+    /// The request for `tx` from `from`. Any signature on `tx` is left out;
+    /// a 7702 tx's authorizations are kept, as the node can't make those.
+    pub fn new(from: Address, tx: &'a SignedTx) -> Self {
+        match tx {
+            SignedTx::Legacy(tx) => Self {
+                tx_type: 0,
+                from,
+                to: tx.to,
+                gas: tx.gas_limit,
+                gas_price: Some(tx.gas_price),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                value: tx.value,
+                data: tx.data.clone().into(),
+                nonce: tx.nonce,
+                // unsigned, v holds the chain id; 0 leaves it to the node
+                chain_id: Some(tx.v).filter(|&chain_id| chain_id != 0),
+                access_list: vec![],
+                authorization_list: &[],
+            },
+            SignedTx::Eip1559(tx) => Self {
+                tx_type: 2,
+                from,
+                to: tx.to,
+                gas: tx.gas_limit,
+                gas_price: None,
+                max_fee_per_gas: Some(tx.max_fee_per_gas),
+                max_priority_fee_per_gas: Some(tx.max_priority_fee_per_gas),
+                value: tx.value,
+                data: tx.data.clone().into(),
+                nonce: tx.nonce,
+                chain_id: Some(tx.chain_id),
+                access_list: tx
+                    .access_list
+                    .iter()
+                    .map(AccessListItem::from_entry)
+                    .collect(),
+                authorization_list: &[],
+            },
+            SignedTx::Eip7702(tx) => Self {
+                tx_type: 4,
+                from,
+                to: tx.to,
+                gas: tx.gas_limit,
+                gas_price: None,
+                max_fee_per_gas: Some(tx.max_fee_per_gas),
+                max_priority_fee_per_gas: Some(tx.max_priority_fee_per_gas),
+                value: tx.value,
+                data: tx.data.clone().into(),
+                nonce: tx.nonce,
+                chain_id: Some(tx.chain_id),
+                access_list: tx
+                    .access_list
+                    .iter()
+                    .map(AccessListItem::from_entry)
+                    .collect(),
+                authorization_list: &tx.authorization_list,
+            },
+        }
+    }
+}
+
+/// `eth_sendTransaction`: the node signs `request` with its unlocked `from`
+/// account and broadcasts it.
+pub async fn send_transaction(
+    provider: &Provider,
+    request: &TransactionRequest<'_>,
+) -> Result<B256, TxError> {
+    let resp: JsonRpcResponse<B256> = provider.rpc_call("eth_sendTransaction", [request]).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(
+            match RpcErrorKind::classify(&err.message).hint() {
+                Some(hint) => format!("{err} ({hint})"),
+                None => err.to_string(),
+            },
+        ));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc("eth_sendTransaction returned no result".to_string()))
+}
+
+/// How often `wait_for_receipt` polls, and how long it keeps going while
+/// nothing moves (no receipt, no new block) before giving up.
+const RECEIPT_POLL_INTERVAL: Duration = Duration::from_secs(1);
+const RECEIPT_WAIT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Polls that see no progress double the wait before the next one, up to
+/// this many times the poll interval.
+const RECEIPT_MAX_BACKOFF: u32 = 8;
+
+/// Outcome of a mined transaction, parsed from the receipt's hex `status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    Success,
+    Reverted,
+}
+
+impl<'de> Deserialize<'de> for TxStatus {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let raw = String::deserialize(deserializer)?;
+        match u64::from_str_radix(raw.trim_start_matches("0x"), 16) {
+            Ok(1) => Ok(TxStatus::Success),
+            Ok(0) => Ok(TxStatus::Reverted),
+            _ => Err(de::Error::custom(format!("invalid receipt status {raw}"))),
+        }
+    }
+}
+
+impl Serialize for TxStatus {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            TxStatus::Success => "0x1",
+            TxStatus::Reverted => "0x0",
+        })
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Receipt {
+    #[serde(with = "alloy::serde::quantity")]
+    pub block_number: u64,
+    #[serde(with = "alloy::serde::quantity")]
+    pub gas_used: u64,
+    /// What the sender paid per gas; older nodes leave it out.
+    pub effective_gas_price: Option<U256>,
+    /// Absent on pre-Byzantium receipts, which carry the state `root` instead.
+    pub status: Option<TxStatus>,
+    pub root: Option<B256>,
+}
+
+pub async fn get_receipt(provider: &Provider, tx_hash: B256) -> Result<Option<Receipt>, TxError> {
+    let resp: JsonRpcResponse<Receipt> = provider
+        .rpc_call("eth_getTransactionReceipt", [tx_hash])
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    // `null` result just means the tx isn't mined yet
+    Ok(resp.result)
+}
+
+/// An `eth_getLogs` filter. `None` topics match anything in their
+/// position; trailing ones can be left out.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    pub from_block: BlockTag,
+    pub to_block: BlockTag,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<Address>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub topics: Vec<Option<B256>>,
+}
+
+/// One event from `eth_getLogs`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Log {
+    /// The account whose code emitted it: for a delegated call, the
+    /// authority rather than the delegate.
+    pub address: Address,
+    pub topics: Vec<B256>,
+    pub data: Bytes,
+    /// `None` while the log's tx is pending.
+    #[serde(default, with = "alloy::serde::quantity::opt")]
+    pub block_number: Option<u64>,
+    pub transaction_hash: Option<B256>,
+    #[serde(default, with = "alloy::serde::quantity::opt")]
+    pub log_index: Option<u64>,
+    /// Set when a reorg dropped the log's block.
+    #[serde(default)]
+    pub removed: bool,
+}
+
+/// The logs matching `filter`, in the order the node returns them.
+pub async fn get_logs(provider: &Provider, filter: &LogFilter) -> Result<Vec<Log>, TxError> {
+    let resp: JsonRpcResponse<Vec<Log>> = provider.rpc_call("eth_getLogs", [filter]).await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    resp.result
+        .ok_or_else(|| TxError::Rpc("eth_getLogs returned no result".to_string()))
+}
+
+/// Whether the node knows the tx at all, pending or mined.
+async fn is_known(provider: &Provider, tx_hash: B256) -> Result<bool, TxError> {
+    let resp: JsonRpcResponse<serde_json::Value> = provider
+        .rpc_call("eth_getTransactionByHash", [tx_hash])
+        .await?;
+    if let Some(err) = resp.error {
+        return Err(TxError::Rpc(err.to_string()));
+    }
+    Ok(resp.result.is_some())
+}
+
+/// Where the sender's nonce comes from (`--nonce-strategy`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NonceStrategy {
+    /// The mined txs only, so a tx still pending gets replaced.
+    Latest,
+    /// The node's count including its pending txs: the next free nonce as
+    /// far as this node knows.
+    Pending,
+    /// One past the highest nonce in the node's txpool for the sender, for
+    /// nodes whose `pending` count lags their pool.
+    Txpool,
+    /// This nonce, without asking the node.
+    Fixed(U256),
+}
+
+impl NonceStrategy {
+    /// The tag to read the count at, if the strategy is a plain count.
+    pub fn block_tag(self) -> Option<BlockTag> {
+        match self {
+            NonceStrategy::Latest => Some(BlockTag::Latest),
+            NonceStrategy::Pending => Some(BlockTag::Pending),
+            NonceStrategy::Txpool | NonceStrategy::Fixed(_) => None,
+        }
+    }
+}
+
+impl FromStr for NonceStrategy {
+    type Err = String;
+
+    /// Parses `latest`, `pending`, `txpool` or `fixed:N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "latest" => Ok(NonceStrategy::Latest),
+            "pending" => Ok(NonceStrategy::Pending),
+            "txpool" => Ok(NonceStrategy::Txpool),
+            _ => s
+                .strip_prefix("fixed:")
+                // an empty string would parse as 0
+                .filter(|n| !n.is_empty())
+                .and_then(|n| U256::from_str(n).ok())
+                .map(NonceStrategy::Fixed)
+                .ok_or_else(|| {
+                    format!(
+                        "invalid nonce strategy {s}, expected latest, pending, txpool or fixed:N"
+                    )
+                }),
+        }
+    }
+}
+
+/// The sender's part of `txpool_contentFrom`: its txs by nonce.
+#[derive(Deserialize)]
+struct TxpoolContent {
+    #[serde(default)]
+    pending: BTreeMap<String, serde_json::Value>,
+}
+
+/// The nonce `strategy` picks for `addr`.
+pub async fn resolve_nonce(
+    provider: &Provider,
+    addr: Address,
+    strategy: NonceStrategy,
+) -> Result<U256, TxError> {
+    match strategy {
+        NonceStrategy::Fixed(nonce) => Ok(nonce),
+        NonceStrategy::Latest | NonceStrategy::Pending => {
+            get_nonce_at(provider, addr, strategy.block_tag().unwrap()).await
+        }
+        NonceStrategy::Txpool => {
+            let mined = get_nonce(provider, addr).await?;
+            let resp: JsonRpcResponse<TxpoolContent> =
+                provider.rpc_call("txpool_contentFrom", [addr]).await?;
+            if let Some(err) = resp.error {
+                return Err(TxError::Rpc(err.to_string()));
+            }
+            // geth keys the txs by decimal nonce
+            let highest = resp
+                .result
+                .into_iter()
+                .flat_map(|content| content.pending.into_keys())
+                .filter_map(|nonce| U256::from_str(&nonce).ok())
+                .max();
+            Ok(highest.map_or(mined, |highest| mined.max(highest + U256::from(1))))
+        }
+    }
+}
+
+/// How far to take a signed tx (`--strategy`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendStrategy {
+    /// Print it, send nothing.
+    Print,
+    /// Send it and stop once the node has it.
+    Send,
+    /// Send it and wait for the receipt.
+    Wait,
+    /// Send it and wait until it is this many blocks deep.
+    Confirm(u64),
+}
+
+impl SendStrategy {
+    /// The confirmations to wait for, or `None` if the receipt isn't
+    /// waited for at all.
+    pub fn confirmations(self) -> Option<u64> {
+        match self {
+            SendStrategy::Print | SendStrategy::Send => None,
+            SendStrategy::Wait => Some(0),
+            SendStrategy::Confirm(confirmations) => Some(confirmations),
+        }
+    }
+}
+
+impl FromStr for SendStrategy {
+    type Err = String;
+
+    /// Parses `print`, `send`, `wait` or `confirm:N`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "print" => Ok(SendStrategy::Print),
+            "send" => Ok(SendStrategy::Send),
+            "wait" => Ok(SendStrategy::Wait),
+            _ => s
+                .strip_prefix("confirm:")
+                .and_then(|n| n.parse().ok())
+                .map(SendStrategy::Confirm)
+                .ok_or_else(|| {
+                    format!("invalid strategy {s}, expected print, send, wait or confirm:N")
+                }),
+        }
+    }
+}
+
+/// Polls until the tx is mined and `confirmations` blocks have been built on
+/// top of it (0 returns as soon as there is a receipt).
+///
+/// The receipt is fetched again on every poll, so if a reorg drops the tx or
+/// moves it to another block, the depth is counted from where it is now.
+/// While there is no receipt the tx is looked up by hash too: once the node
+/// has forgotten it, it was dropped from the mempool and waiting is
+/// pointless. Polls back off exponentially while nothing moves.
+pub async fn wait_for_receipt(
+    provider: &Provider,
+    tx_hash: B256,
+    confirmations: u64,
+) -> Result<Receipt, TxError> {
+    let mut last_progress = Instant::now();
+    let mut last_head = None;
+    let mut backoff = 1;
+    while last_progress.elapsed() < provider.wait_timeout {
+        let receipt = get_receipt(provider, tx_hash).await?;
+        if receipt.is_none() && !is_known(provider, tx_hash).await? {
+            return Err(TxError::Dropped { tx_hash });
+        }
+        if let Some(receipt) = receipt {
+            if confirmations == 0 {
+                return Ok(receipt);
+            }
+            let head = block_number(provider).await?;
+            let confirmed = head.saturating_sub(receipt.block_number);
+            if confirmed >= confirmations {
+                return Ok(receipt);
+            }
+            if last_head != Some(head) {
+                last_head = Some(head);
+                last_progress = Instant::now();
+                backoff = 1;
+            }
+        }
+        tokio::time::sleep(provider.poll_interval * backoff).await;
+        backoff = (backoff * 2).min(RECEIPT_MAX_BACKOFF);
+    }
+    Err(TxError::Timeout { tx_hash })
+}
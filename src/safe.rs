@@ -0,0 +1,35 @@
+//! Messages signed for a Safe (formerly Gnosis Safe) smart account.
+//!
+//! A Safe checks an owner's signature over its own EIP-712 hash of the
+//! message, in a domain of just the chain and the Safe's address (Safe
+//! 1.3.0 and later).
+
+use alloy::primitives::{b256, keccak256, Address, B256, U256};
+
+use crate::signing::eip712_hash;
+
+/// `keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")`.
+pub const DOMAIN_SEPARATOR_TYPEHASH: B256 =
+    b256!("47e79534a245952e8b16893a336b85a3d9ea9fa8c573f3d803afb92a79469218");
+/// `keccak256("SafeMessage(bytes message)")`.
+pub const SAFE_MSG_TYPEHASH: B256 =
+    b256!("60b3cbf8b4a223d68d641b3b6ddf9a298e7f33710cf3d3a9d1146b5a6150fbca");
+
+/// What the Safe's `domainSeparator()` returns.
+pub fn safe_domain_separator(safe: Address, chain_id: u64) -> B256 {
+    keccak256(
+        [
+            DOMAIN_SEPARATOR_TYPEHASH,
+            U256::from(chain_id).into(),
+            safe.into_word(),
+        ]
+        .concat(),
+    )
+}
+
+/// The hash the Safe's owners sign for `message`, as its
+/// `getMessageHashForSafe` computes it.
+pub fn safe_message_hash(safe: Address, chain_id: u64, message: &[u8]) -> B256 {
+    let struct_hash = keccak256([SAFE_MSG_TYPEHASH, keccak256(message)].concat());
+    eip712_hash(safe_domain_separator(safe, chain_id), struct_hash)
+}
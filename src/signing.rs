@@ -0,0 +1,254 @@
+use std::future::Future;
+
+use alloy::primitives::{keccak256, uint, Address, B256, U256};
+use secp256k1::{
+    ecdsa::{RecoverableSignature, RecoveryId},
+    PublicKey, Secp256k1, SecretKey,
+};
+
+use crate::{decode::SignedTx, error::TxError, keys::parse_private_key, tx::SignableTransaction};
+
+/// Produces the sender's signature over a tx hash.
+///
+/// The key doesn't have to live in this process: implementations may hand
+/// the hash to a remote service or a device, which is why signing is async
+/// and fallible.
+pub trait Signer: Sync {
+    /// The account whose key signs.
+    fn address(&self) -> Address;
+
+    /// Signs a 32-byte digest, returning `(r, s, y_parity)`.
+    fn sign_hash(
+        &self,
+        hash: &B256,
+    ) -> impl Future<Output = Result<(U256, U256, u8), TxError>> + Send;
+
+    /// Signs an unsigned tx encoding (`rlp_encode_unsigned`). Devices that
+    /// only sign whole transactions override this; everything else signs
+    /// its hash.
+    fn sign_transaction(
+        &self,
+        unsigned_rlp: &[u8],
+    ) -> impl Future<Output = Result<(U256, U256, u8), TxError>> + Send {
+        async move { self.sign_hash(&keccak256(unsigned_rlp)).await }
+    }
+
+    /// Signs `tx` and stores the signature in it.
+    fn finalize(
+        &self,
+        tx: &mut (impl SignableTransaction + Send),
+    ) -> impl Future<Output = Result<(), TxError>> + Send {
+        async move {
+            tx.validate()?;
+            let (r, s, y_parity) = self.sign_transaction(&tx.unsigned_payload()).await?;
+            tx.set_signature(r, s, y_parity)
+        }
+    }
+}
+
+/// Signs with a private key held in memory.
+pub struct LocalSigner {
+    secret_key: SecretKey,
+    address: Address,
+}
+
+impl LocalSigner {
+    pub fn new(secret_key: SecretKey) -> Self {
+        Self {
+            address: address_from_key(&secret_key),
+            secret_key,
+        }
+    }
+}
+
+impl Signer for LocalSigner {
+    fn address(&self) -> Address {
+        self.address
+    }
+
+    async fn sign_hash(&self, hash: &B256) -> Result<(U256, U256, u8), TxError> {
+        sign_hash(hash, &self.secret_key)
+    }
+}
+
+/// Signs `tx` with a local key and stores the signature in it. The
+/// signature is low-s, as libsecp256k1 always produces it.
+pub fn finalize_signature(
+    tx: &mut impl SignableTransaction,
+    key: &SecretKey,
+) -> Result<(), TxError> {
+    let hash = tx.signature_hash();
+    finalize_signature_with_hash(tx, &hash, key)
+}
+
+/// [`finalize_signature`] over a digest the caller already has, e.g. one
+/// computed elsewhere or a fixed one in a test. Nothing checks that `hash`
+/// is the tx's own signing hash.
+pub fn finalize_signature_with_hash(
+    tx: &mut impl SignableTransaction,
+    hash: &B256,
+    key: &SecretKey,
+) -> Result<(), TxError> {
+    tx.validate()?;
+    let (r, s, y_parity) = sign_hash(hash, key)?;
+    tx.set_signature(r, s, y_parity)
+}
+
+/// Drops `tx`'s signature and signs it again with `new_key`, e.g. to send a
+/// decoded tx from another sender. Everything but the signature is kept, so
+/// the nonce may not suit the new sender.
+pub fn resign(tx: &mut impl SignableTransaction, new_key: &SecretKey) -> Result<(), TxError> {
+    tx.clear_signature()?;
+    finalize_signature(tx, new_key)
+}
+
+/// Moves `tx` to `nonce` and has `signer` sign it again, e.g. after the
+/// node said its nonce was already taken. A pre-EIP-155 legacy tx can't be
+/// re-signed this way, and neither can a 7702 tx carrying the sender's own
+/// authorization, which is signed over the nonce after the tx's.
+pub async fn resign_with_nonce(
+    signer: &impl Signer,
+    tx: &mut SignedTx,
+    nonce: U256,
+) -> Result<(), TxError> {
+    match tx {
+        SignedTx::Legacy(tx) => {
+            tx.clear_signature()?;
+            tx.nonce = nonce;
+            signer.finalize(tx).await
+        }
+        SignedTx::Eip1559(tx) => {
+            tx.clear_signature()?;
+            tx.nonce = nonce;
+            signer.finalize(tx).await
+        }
+        SignedTx::Eip7702(tx) => {
+            for auth in &tx.authorization_list {
+                if auth.recover_authority()? == signer.address() {
+                    return Err(TxError::Signer(
+                        "the sender's own authorization is signed for the old nonce".to_string(),
+                    ));
+                }
+            }
+            tx.clear_signature()?;
+            tx.nonce = nonce;
+            signer.finalize(tx).await
+        }
+    }
+}
+
+/// Signs a 32-byte digest, returning `(r, s, y_parity)`.
+pub fn sign_hash(hash: &B256, secret_key: &SecretKey) -> Result<(U256, U256, u8), TxError> {
+    let msg = secp256k1::Message::from_digest(hash.0);
+    let signature = Secp256k1::new().sign_ecdsa_recoverable(&msg, secret_key);
+
+    let (recovery_id, rsig) = signature.serialize_compact();
+    let r = bytes32_to_u256(&rsig[..32]);
+    let s = bytes32_to_u256(&rsig[32..64]);
+    let y_parity = recovery_to_parity(recovery_id.to_i32())?;
+    Ok((r, s, y_parity))
+}
+
+/// The `y_parity` of a secp256k1 recovery id. Ids 2 and 3 mean `r`
+/// overflowed the curve order, which no tx or authorization can express,
+/// so they (and anything else outside 0 and 1) are an error rather than a
+/// bogus parity.
+pub fn recovery_to_parity(rid: i32) -> Result<u8, TxError> {
+    match rid {
+        0 | 1 => Ok(rid as u8),
+        rid => Err(TxError::Signer(format!(
+            "recovery id {rid} has no y_parity, expected 0 or 1"
+        ))),
+    }
+}
+
+/// The EIP-712 digest of a struct: `keccak256(0x19 0x01 || domain || struct)`.
+pub fn eip712_hash(domain_separator: B256, struct_hash: B256) -> B256 {
+    keccak256(
+        [
+            &[0x19, 0x01],
+            domain_separator.as_slice(),
+            struct_hash.as_slice(),
+        ]
+        .concat(),
+    )
+}
+
+/// Packs a signature as `r || s || v` with `v = 27 + y_parity`, the 65-byte
+/// form wallets and `ecrecover`-style tooling expect. A `y_parity` other
+/// than 0 or 1 has no `v`, so it is an error.
+pub fn signature_bytes(r: U256, s: U256, y_parity: u8) -> Result<[u8; 65], TxError> {
+    if y_parity > 1 {
+        return Err(TxError::Signer(format!(
+            "y_parity {y_parity} has no v, expected 0 or 1"
+        )));
+    }
+    let mut bytes = [0u8; 65];
+    bytes[..32].copy_from_slice(&r.to_be_bytes::<32>());
+    bytes[32..64].copy_from_slice(&s.to_be_bytes::<32>());
+    bytes[64] = 27 + y_parity;
+    Ok(bytes)
+}
+
+/// The inverse of [`signature_bytes`]. A `v` of 0 or 1 is taken as the
+/// parity itself, since some tools pack it that way; any `v` but those and
+/// 27 or 28 is an error.
+pub fn signature_from_bytes(bytes: &[u8; 65]) -> Result<(U256, U256, u8), TxError> {
+    let r = bytes32_to_u256(&bytes[..32]);
+    let s = bytes32_to_u256(&bytes[32..64]);
+    let y_parity = match bytes[64] {
+        v @ (0 | 1) => v,
+        v @ (27 | 28) => v - 27,
+        v => {
+            return Err(TxError::Decode(format!(
+                "signature v {v} is not 0, 1, 27 or 28"
+            )))
+        }
+    };
+    Ok((r, s, y_parity))
+}
+
+pub fn bytes32_to_u256(bytes: &[u8]) -> U256 {
+    U256::from_be_bytes::<32>(bytes.try_into().expect("slice must be 32 bytes"))
+}
+
+pub fn address_from_pkey(private_key_hex: &str) -> Result<Address, TxError> {
+    Ok(address_from_key(&parse_private_key(private_key_hex)?))
+}
+
+pub fn address_from_key(secret_key: &SecretKey) -> Address {
+    address_from_pubkey(&secret_key.public_key(&Secp256k1::new()))
+}
+
+pub fn address_from_pubkey(pubkey: &PublicKey) -> Address {
+    let pubkey_uncompressed = pubkey.serialize_uncompressed(); // 65 bytes, [0x04, x, y]
+
+    let hash = keccak256(&pubkey_uncompressed[1..]); // skip the 0x04
+    Address::from_slice(&hash[12..]) // last 20 bytes
+}
+
+/// Half the secp256k1 group order. EIP-2 made signatures whose `s` is above
+/// this invalid, since `(r, n - s)` is just as valid a signature as `(r, s)`.
+pub const SECP256K1N_HALF: U256 =
+    uint!(0x7fffffffffffffffffffffffffffffff5d576e7357a4501ddfe92f46681b20a0_U256);
+
+/// Recovers the address that produced `(r, s, y_parity)` over `hash`.
+pub fn recover_address(hash: &B256, r: U256, s: U256, y_parity: u8) -> Result<Address, TxError> {
+    let mut compact = [0u8; 64];
+    compact[..32].copy_from_slice(&r.to_be_bytes::<32>());
+    compact[32..].copy_from_slice(&s.to_be_bytes::<32>());
+
+    let recovery_id = RecoveryId::from_i32(y_parity as i32)?;
+    let signature = RecoverableSignature::from_compact(&compact, recovery_id)?;
+    let msg = secp256k1::Message::from_digest_slice(hash.as_slice())?;
+    let pubkey = Secp256k1::new().recover_ecdsa(&msg, &signature)?;
+    Ok(address_from_pubkey(&pubkey))
+}
+
+/// The parity that makes `(r, s)` recover to `expected`, for backends such
+/// as KMS and HSMs that return a signature without one.
+pub fn find_recovery_id(hash: &B256, r: U256, s: U256, expected: Address) -> Option<u8> {
+    [0, 1]
+        .into_iter()
+        .find(|&y_parity| recover_address(hash, r, s, y_parity).ok() == Some(expected))
+}
@@ -0,0 +1,211 @@
+use std::fmt::Write;
+
+use alloy::primitives::{Address, B256, U256};
+
+use crate::{
+    decode::SignedTx,
+    tx::{Authorization7702, Eip1559Transaction},
+    units::{format_ether, format_gwei},
+};
+
+/// One-screen overview of a tx, printed before it is broadcast.
+pub fn summary(from: Address, tx: &SignedTx) -> String {
+    let (kind, nonce, gas_limit, to, value, data) = match tx {
+        SignedTx::Legacy(tx) => ("legacy", tx.nonce, tx.gas_limit, tx.to, tx.value, &tx.data),
+        SignedTx::Eip1559(tx) => (
+            "EIP-1559",
+            tx.nonce,
+            tx.gas_limit,
+            tx.to,
+            tx.value,
+            &tx.data,
+        ),
+        SignedTx::Eip7702(tx) => (
+            "EIP-7702",
+            tx.nonce,
+            tx.gas_limit,
+            tx.to,
+            tx.value,
+            &tx.data,
+        ),
+    };
+    let fees = match tx {
+        SignedTx::Legacy(tx) => format!(
+            "gas price {} wei ({} gwei)",
+            tx.gas_price,
+            format_gwei(tx.gas_price)
+        ),
+        SignedTx::Eip1559(tx) => fee_caps(tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+        SignedTx::Eip7702(tx) => fee_caps(tx.max_fee_per_gas, tx.max_priority_fee_per_gas),
+    };
+
+    let mut out = String::new();
+    // writing to a String can't fail
+    let _ = writeln!(out, "Type:      {kind}");
+    let _ = writeln!(out, "From:      {}", from.to_checksum(None));
+    let _ = match to {
+        Some(to) => writeln!(out, "To:        {}", to.to_checksum(None)),
+        None => writeln!(out, "To:        (contract creation)"),
+    };
+    let _ = writeln!(out, "Value:     {value} wei ({} ETH)", format_ether(value));
+    let _ = writeln!(out, "Nonce:     {nonce}");
+    let _ = writeln!(out, "Gas limit: {gas_limit}");
+    let _ = writeln!(out, "Fees:      {fees}");
+    let _ = match tx.max_cost() {
+        Some(cost) => writeln!(out, "Max cost:  {cost} wei ({} ETH)", format_ether(cost)),
+        None => writeln!(out, "Max cost:  overflows 256 bits"),
+    };
+    let _ = writeln!(out, "Data:      {} bytes", data.len());
+    let access_list = tx.access_list();
+    if !access_list.is_empty() {
+        let keys: usize = access_list.iter().map(|(_, keys)| keys.len()).sum();
+        let _ = writeln!(
+            out,
+            "Access list: {} addresses, {keys} storage keys",
+            access_list.len()
+        );
+    }
+    if let SignedTx::Eip7702(tx) = tx {
+        let _ = writeln!(out, "Authorizations: {}", tx.authorization_list.len());
+        for auth in &tx.authorization_list {
+            let _ = writeln!(
+                out,
+                "  -> {} (chain {}, nonce {})",
+                auth.address.to_checksum(None),
+                auth.chain_id,
+                auth.nonce
+            );
+        }
+    }
+    out
+}
+
+/// `--debug-auth`: what the authority signed and who it recovers to, for
+/// working out why a node rejects the authorization.
+pub fn describe_authorization(index: usize, auth: &Authorization7702) -> String {
+    let signer = match auth.recover_authority() {
+        Ok(signer) => signer.to_checksum(None),
+        Err(err) => format!("unrecoverable ({err})"),
+    };
+    format!(
+        "Authorization {index}:\n  Magic RLP:    0x{}\n  Signing hash: {}\n  Signer:       {signer}",
+        hex::encode(auth.magic_payload()),
+        auth.signing_hash()
+    )
+}
+
+/// Every field that differs between two 1559 txs, as `(field, old, new)`
+/// in encoding order, e.g. to check that a replacement only bumps the fees.
+/// The signature is left out, since it changes with any edit.
+pub fn diff_tx(a: &Eip1559Transaction, b: &Eip1559Transaction) -> Vec<(String, String, String)> {
+    let to = |to: Option<Address>| match to {
+        Some(to) => to.to_checksum(None),
+        None => "(contract creation)".to_string(),
+    };
+    let fields = [
+        ("chain_id", a.chain_id.to_string(), b.chain_id.to_string()),
+        ("nonce", a.nonce.to_string(), b.nonce.to_string()),
+        (
+            "max_priority_fee_per_gas",
+            a.max_priority_fee_per_gas.to_string(),
+            b.max_priority_fee_per_gas.to_string(),
+        ),
+        (
+            "max_fee_per_gas",
+            a.max_fee_per_gas.to_string(),
+            b.max_fee_per_gas.to_string(),
+        ),
+        (
+            "gas_limit",
+            a.gas_limit.to_string(),
+            b.gas_limit.to_string(),
+        ),
+        ("to", to(a.to), to(b.to)),
+        ("value", a.value.to_string(), b.value.to_string()),
+        (
+            "data",
+            format!("0x{}", hex::encode(&a.data)),
+            format!("0x{}", hex::encode(&b.data)),
+        ),
+        (
+            "access_list",
+            format!("{:?}", a.access_list),
+            format!("{:?}", b.access_list),
+        ),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, old, new)| old != new)
+        .map(|(field, old, new)| (field.to_string(), old, new))
+        .collect()
+}
+
+/// `--verbose` and `--explain`: the signature of the tx and of each of its
+/// authorizations, `r` and `s` as full 32-byte words.
+pub fn describe_signatures(tx: &SignedTx) -> String {
+    let mut out = String::new();
+    let _ = match tx {
+        SignedTx::Legacy(tx) => writeln!(
+            out,
+            "Signature:\n{}",
+            signature_components(tx.r, tx.s, "v", tx.v)
+        ),
+        SignedTx::Eip1559(tx) => writeln!(
+            out,
+            "Signature:\n{}",
+            signature_components(tx.r, tx.s, "y_parity", tx.y_parity.into())
+        ),
+        SignedTx::Eip7702(tx) => writeln!(
+            out,
+            "Signature:\n{}",
+            signature_components(tx.r, tx.s, "y_parity", tx.y_parity.into())
+        ),
+    };
+    if let SignedTx::Eip7702(tx) = tx {
+        for (i, auth) in tx.authorization_list.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "Authorization {i} signature:\n{}",
+                signature_components(auth.r, auth.s, "y_parity", auth.y_parity.into())
+            );
+        }
+    }
+    out
+}
+
+/// The access list of a decoded tx: a count, then each address with its
+/// storage keys as full 32-byte words.
+pub fn describe_access_list(access_list: &[(Address, Vec<U256>)]) -> String {
+    let keys: usize = access_list.iter().map(|(_, keys)| keys.len()).sum();
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "Access list: {} addresses, {keys} storage keys",
+        access_list.len()
+    );
+    for (address, keys) in access_list {
+        let _ = writeln!(out, "  {}", address.to_checksum(None));
+        for key in keys {
+            let _ = writeln!(out, "    {}", B256::from(*key));
+        }
+    }
+    out
+}
+
+/// `r`, `s` and `v` (or `y_parity`, as `v_name` says), one per line. `r`
+/// and `s` keep their leading zero bytes, so they are always 64 hex digits.
+pub fn signature_components(r: U256, s: U256, v_name: &str, v: u64) -> String {
+    format!(
+        "  r: {}\n  s: {}\n  {v_name}: {v}",
+        B256::from(r),
+        B256::from(s)
+    )
+}
+
+fn fee_caps(max_fee_per_gas: U256, max_priority_fee_per_gas: U256) -> String {
+    format!(
+        "max {max_fee_per_gas} wei ({} gwei), priority {max_priority_fee_per_gas} wei ({} gwei)",
+        format_gwei(max_fee_per_gas),
+        format_gwei(max_priority_fee_per_gas)
+    )
+}
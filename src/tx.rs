@@ -0,0 +1,709 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    str::FromStr,
+};
+
+use alloy::primitives::{keccak256, Address, B256, U256};
+use alloy_rlp::{Encodable, Header, EMPTY_STRING_CODE};
+use secp256k1::SecretKey;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decode::chain_id_from_v,
+    error::TxError,
+    keys::parse_private_key,
+    signing::{recover_address, sign_hash},
+};
+
+/// What signing needs from a tx: the hash to sign, and where the
+/// signature goes.
+pub trait SignableTransaction {
+    /// The unsigned encoding, `rlp_encode_unsigned`: what a device that
+    /// only signs whole txs is handed.
+    fn unsigned_payload(&self) -> Vec<u8>;
+
+    /// The hash the sender signs, `signing_hash`.
+    fn signature_hash(&self) -> B256 {
+        keccak256(self.unsigned_payload())
+    }
+
+    /// Stores a signature over [`Self::signature_hash`].
+    fn set_signature(&mut self, r: U256, s: U256, y_parity: u8) -> Result<(), TxError>;
+
+    /// Puts the tx back the way it was before [`Self::set_signature`], so
+    /// it can be signed again.
+    fn clear_signature(&mut self) -> Result<(), TxError>;
+
+    /// Rejects a tx no node would accept, so it isn't signed for nothing.
+    fn validate(&self) -> Result<(), TxError> {
+        Ok(())
+    }
+}
+
+/// A simple “legacy” transaction container
+#[derive(Debug)]
+pub struct LegacyTransaction {
+    pub nonce: U256,
+    pub gas_price: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    // data must be string (if vec<u8> then it gets encoded as list..)
+    pub data: Vec<u8>,
+    pub v: u64,
+    pub r: U256,
+    pub s: U256,
+}
+
+/// While unsigned, `v` holds the chain id, the same as in the EIP-155
+/// signing payload `[..., chain_id, 0, 0]`; the signature replaces it with
+/// `chain_id * 2 + 35 + y_parity`.
+impl SignableTransaction for LegacyTransaction {
+    fn unsigned_payload(&self) -> Vec<u8> {
+        self.rlp_encode_unsigned(self.v)
+    }
+
+    fn set_signature(&mut self, r: U256, s: U256, y_parity: u8) -> Result<(), TxError> {
+        self.v = self
+            .v
+            .checked_mul(2)
+            .and_then(|v| v.checked_add(35 + y_parity as u64))
+            .ok_or_else(|| {
+                TxError::Signer(format!("chain id {} is too large for EIP-155", self.v))
+            })?;
+        self.r = r;
+        self.s = s;
+        Ok(())
+    }
+
+    /// Turns an EIP-155 `v` back into the chain id. A 27/28 `v` has none to
+    /// go back to; such a tx is re-signed through [`PreEip155`].
+    fn clear_signature(&mut self) -> Result<(), TxError> {
+        if self.r.is_zero() && self.s.is_zero() {
+            // never signed, so v already holds the chain id
+            return Ok(());
+        }
+        self.v = chain_id_from_v(self.v).ok_or_else(|| {
+            TxError::Signer(format!(
+                "v = {} is not EIP-155, so there is no chain id to sign for",
+                self.v
+            ))
+        })?;
+        (self.r, self.s) = (U256::ZERO, U256::ZERO);
+        Ok(())
+    }
+}
+
+impl SignableTransaction for Eip1559Transaction {
+    fn unsigned_payload(&self) -> Vec<u8> {
+        self.rlp_encode_unsigned()
+    }
+
+    fn set_signature(&mut self, r: U256, s: U256, y_parity: u8) -> Result<(), TxError> {
+        (self.r, self.s, self.y_parity) = (r, s, y_parity);
+        Ok(())
+    }
+
+    fn clear_signature(&mut self) -> Result<(), TxError> {
+        (self.r, self.s, self.y_parity) = (U256::ZERO, U256::ZERO, 0);
+        Ok(())
+    }
+}
+
+impl SignableTransaction for Eip7702Transaction {
+    fn unsigned_payload(&self) -> Vec<u8> {
+        self.rlp_encode_unsigned()
+    }
+
+    fn set_signature(&mut self, r: U256, s: U256, y_parity: u8) -> Result<(), TxError> {
+        (self.r, self.s, self.y_parity) = (r, s, y_parity);
+        Ok(())
+    }
+
+    fn clear_signature(&mut self) -> Result<(), TxError> {
+        (self.r, self.s, self.y_parity) = (U256::ZERO, U256::ZERO, 0);
+        Ok(())
+    }
+
+    /// EIP-7702 makes a type-4 tx without authorizations invalid.
+    fn validate(&self) -> Result<(), TxError> {
+        if self.authorization_list.is_empty() {
+            return Err(TxError::EmptyAuthorizationList);
+        }
+        Ok(())
+    }
+}
+
+/// A legacy tx signed the pre-EIP-155 way: over the six fields alone, with
+/// a `v` of 27 or 28, so the signature is valid on every chain.
+#[derive(Debug)]
+pub struct PreEip155(pub LegacyTransaction);
+
+impl SignableTransaction for PreEip155 {
+    fn unsigned_payload(&self) -> Vec<u8> {
+        self.0.rlp_encode_pre_eip155()
+    }
+
+    fn set_signature(&mut self, r: U256, s: U256, y_parity: u8) -> Result<(), TxError> {
+        (self.0.v, self.0.r, self.0.s) = (27 + y_parity as u64, r, s);
+        Ok(())
+    }
+
+    fn clear_signature(&mut self) -> Result<(), TxError> {
+        (self.0.v, self.0.r, self.0.s) = (0, U256::ZERO, U256::ZERO);
+        Ok(())
+    }
+}
+
+/// RLP for the “unsigned” portion and for the “signed” portion
+impl LegacyTransaction {
+    fn internal_length(&self) -> usize {
+        self.nonce.length()
+            + self.gas_price.length()
+            + self.gas_limit.length()
+            + to_length(self.to)
+            + self.value.length()
+            + self.data.as_slice().length()
+    }
+
+    fn encode_internal(&self, out: &mut Vec<u8>) {
+        self.nonce.encode(out);
+        self.gas_price.encode(out);
+        self.gas_limit.encode(out);
+        encode_to(self.to, out);
+        self.value.encode(out);
+        self.data.as_slice().encode(out);
+    }
+
+    pub fn rlp_encode_unsigned(&self, chain_id: u64) -> Vec<u8> {
+        // EIP-155 includes chain_id, 0, 0 at the end
+        let payload_length = self.internal_length() + chain_id.length() + 2 * 0u8.length();
+
+        let mut buffer = list_buffer(None, payload_length);
+        self.encode_internal(&mut buffer);
+        chain_id.encode(&mut buffer);
+        0u8.encode(&mut buffer);
+        0u8.encode(&mut buffer);
+        buffer
+    }
+
+    /// The hash the sender signs for `chain_id`.
+    pub fn signing_hash(&self, chain_id: u64) -> B256 {
+        keccak256(self.rlp_encode_unsigned(chain_id))
+    }
+
+    /// Recovers the sender of a signed tx. Takes an EIP-155 `v` for any
+    /// chain id that fits, or the chain-less 27/28.
+    pub fn recover_sender(&self) -> Result<Address, TxError> {
+        let (hash, y_parity) = match self.v {
+            27 | 28 => (keccak256(self.rlp_encode_pre_eip155()), self.v - 27),
+            v if v >= 35 => (self.signing_hash((v - 35) / 2), (v - 35) % 2),
+            v => return Err(TxError::Decode(format!("invalid legacy v {v}"))),
+        };
+        recover_address(&hash, self.r, self.s, y_parity as u8)
+    }
+
+    /// What chain-less (pre-EIP-155) signatures are over: just the six fields.
+    fn rlp_encode_pre_eip155(&self) -> Vec<u8> {
+        let mut buffer = list_buffer(None, self.internal_length());
+        self.encode_internal(&mut buffer);
+        buffer
+    }
+
+    pub fn rlp_encode_signed(&self) -> Vec<u8> {
+        let payload_length =
+            self.internal_length() + self.v.length() + self.r.length() + self.s.length();
+
+        let mut buffer = list_buffer(None, payload_length);
+        self.encode_internal(&mut buffer);
+        self.v.encode(&mut buffer);
+        self.r.encode(&mut buffer);
+        self.s.encode(&mut buffer);
+        debug_assert_canonical(&buffer);
+        buffer
+    }
+}
+
+/// Minimal EIP-1559 transaction (type=2), ignoring error checks/optional fields
+#[derive(Debug)]
+pub struct Eip1559Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    /// `(address, storage keys)` pairs the tx pre-declares.
+    pub access_list: Vec<(Address, Vec<U256>)>,
+
+    // Signature
+    pub y_parity: u8, // 0 or 1
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Eip1559Transaction {
+    fn internal_length(&self) -> usize {
+        self.chain_id.length()
+            + self.nonce.length()
+            + self.max_priority_fee_per_gas.length()
+            + self.max_fee_per_gas.length()
+            + self.gas_limit.length()
+            + to_length(self.to)
+            + self.value.length()
+            + self.data.as_slice().length()
+            + access_list_length(&self.access_list)
+    }
+
+    fn encode_internal(&self, out: &mut Vec<u8>) {
+        self.chain_id.encode(out);
+        self.nonce.encode(out);
+        self.max_priority_fee_per_gas.encode(out);
+        self.max_fee_per_gas.encode(out);
+        self.gas_limit.encode(out);
+        encode_to(self.to, out);
+        self.value.encode(out);
+        self.data.as_slice().encode(out);
+        encode_access_list(&self.access_list, out);
+    }
+
+    /// RLP for the *unsigned* portion, which you then keccak256 and sign
+    pub fn rlp_encode_unsigned(&self) -> Vec<u8> {
+        // this is crucial here.
+        let mut buffer = list_buffer(Some(0x02), self.internal_length());
+        self.encode_internal(&mut buffer);
+        buffer
+    }
+
+    /// The hash the sender signs.
+    pub fn signing_hash(&self) -> B256 {
+        keccak256(self.rlp_encode_unsigned())
+    }
+
+    pub fn rlp_encode_signed(&self) -> Vec<u8> {
+        let payload_length =
+            self.internal_length() + self.y_parity.length() + self.r.length() + self.s.length();
+
+        let mut buffer = list_buffer(Some(0x02), payload_length);
+        self.encode_internal(&mut buffer);
+        self.y_parity.encode(&mut buffer);
+        self.r.encode(&mut buffer);
+        self.s.encode(&mut buffer);
+        debug_assert_canonical(&buffer);
+        buffer
+    }
+}
+
+#[derive(Debug)]
+pub struct Eip7702Transaction {
+    pub chain_id: u64,
+    pub nonce: U256,
+    pub max_priority_fee_per_gas: U256,
+    pub max_fee_per_gas: U256,
+    pub gas_limit: U256,
+    pub to: Option<Address>,
+    pub value: U256,
+    pub data: Vec<u8>,
+    /// `(address, storage keys)` pairs the tx pre-declares.
+    pub access_list: Vec<(Address, Vec<U256>)>,
+    pub authorization_list: Vec<Authorization7702>,
+
+    // Signature
+    pub y_parity: u8, // 0 or 1
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Eip7702Transaction {
+    fn internal_length(&self) -> usize {
+        self.chain_id.length()
+            + self.nonce.length()
+            + self.max_priority_fee_per_gas.length()
+            + self.max_fee_per_gas.length()
+            + self.gas_limit.length()
+            + to_length(self.to)
+            + self.value.length()
+            + self.data.as_slice().length()
+            + access_list_length(&self.access_list)
+            + self.authorization_list.length()
+    }
+
+    fn encode_internal(&self, out: &mut Vec<u8>) {
+        self.chain_id.encode(out);
+        self.nonce.encode(out);
+        self.max_priority_fee_per_gas.encode(out);
+        self.max_fee_per_gas.encode(out);
+        self.gas_limit.encode(out);
+        encode_to(self.to, out);
+        self.value.encode(out);
+        self.data.as_slice().encode(out);
+        encode_access_list(&self.access_list, out);
+
+        self.authorization_list.encode(out);
+    }
+
+    /// RLP for the *unsigned* portion, which you then keccak256 and sign
+    pub fn rlp_encode_unsigned(&self) -> Vec<u8> {
+        // this is crucial here.
+        let mut buffer = list_buffer(Some(0x04), self.internal_length());
+        self.encode_internal(&mut buffer);
+        buffer
+    }
+
+    /// The hash the sender signs.
+    pub fn signing_hash(&self) -> B256 {
+        keccak256(self.rlp_encode_unsigned())
+    }
+
+    pub fn rlp_encode_signed(&self) -> Vec<u8> {
+        let payload_length =
+            self.internal_length() + self.y_parity.length() + self.r.length() + self.s.length();
+
+        let mut buffer = list_buffer(Some(0x04), payload_length);
+        self.encode_internal(&mut buffer);
+        self.y_parity.encode(&mut buffer);
+        self.r.encode(&mut buffer);
+        self.s.encode(&mut buffer);
+        debug_assert_canonical(&buffer);
+        buffer
+    }
+}
+
+/// A 7702 tx that only installs `authorizations`: no value, no calldata,
+/// sent by `sender` to itself. Fails if there are no authorizations.
+///
+/// Compare the example in `main`, which also moves 1 ETH to an unrelated
+/// address - the delegation doesn't need any of that.
+pub fn build_delegation_only_tx(
+    sender: Address,
+    authorizations: Vec<Authorization7702>,
+    chain_id: u64,
+    nonce: U256,
+    max_fee_per_gas: U256,
+    max_priority_fee_per_gas: U256,
+    gas_limit: U256,
+) -> Result<Eip7702Transaction, TxError> {
+    let tx = Eip7702Transaction {
+        chain_id,
+        nonce,
+        max_priority_fee_per_gas,
+        max_fee_per_gas,
+        gas_limit,
+        to: Some(sender),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list: authorizations,
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    tx.validate()?;
+    Ok(tx)
+}
+
+/// (De)serializes with the field names and hex quantities nodes use in a
+/// tx's `authorizationList`.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Authorization7702 {
+    #[serde(with = "alloy::serde::quantity")]
+    pub chain_id: u64,
+    pub address: Address,
+    pub nonce: U256,
+    #[serde(with = "alloy::serde::quantity")]
+    pub y_parity: u8, // 0 or 1
+    pub r: U256,
+    pub s: U256,
+}
+
+impl Authorization7702 {
+    pub fn new(
+        chain_id: u64,
+        address: Address,
+        nonce: U256,
+        private_key_hex: String,
+    ) -> Result<Self, TxError> {
+        let secret_key = parse_private_key(&private_key_hex)?;
+        Self::sign(chain_id, address, nonce, &secret_key)
+    }
+
+    /// Signs the delegation to `address` with the authority's key.
+    pub fn sign(
+        chain_id: u64,
+        address: Address,
+        nonce: U256,
+        secret_key: &SecretKey,
+    ) -> Result<Self, TxError> {
+        let message_hash = keccak256(Self::signing_payload(chain_id, address, nonce));
+        let (r, s, y_parity) = sign_hash(&message_hash, secret_key)?;
+        Ok(Self {
+            chain_id,
+            address,
+            nonce,
+            y_parity,
+            r,
+            s,
+        })
+    }
+
+    /// The hash the authority signs: keccak256 of [`Self::magic_payload`].
+    pub fn signing_hash(&self) -> B256 {
+        keccak256(self.magic_payload())
+    }
+
+    /// `0x05 || rlp([chain_id, address, nonce])`, the bytes behind
+    /// [`Self::signing_hash`].
+    pub fn magic_payload(&self) -> Vec<u8> {
+        Self::signing_payload(self.chain_id, self.address, self.nonce)
+    }
+
+    /// `0x05 || rlp([chain_id, address, nonce])` - the bytes whose hash the authority signs.
+    fn signing_payload(chain_id: u64, address: Address, nonce: U256) -> Vec<u8> {
+        let payload_length = chain_id.length() + address.length() + nonce.length();
+        // this is crucial here - this is 'MAGIC' part.
+        let mut buffer = list_buffer(Some(0x05), payload_length);
+        chain_id.encode(&mut buffer);
+        address.encode(&mut buffer);
+        nonce.encode(&mut buffer);
+        buffer
+    }
+
+    fn payload_length(&self) -> usize {
+        self.chain_id.length()
+            + self.address.length()
+            + self.nonce.length()
+            + self.y_parity.length()
+            + self.r.length()
+            + self.s.length()
+    }
+
+    /// Recovers the account that signed (and is delegated by) this authorization.
+    pub fn recover_authority(&self) -> Result<Address, TxError> {
+        recover_address(&self.signing_hash(), self.r, self.s, self.y_parity)
+    }
+}
+
+/// Code prefix marking an account as delegated (EIP-7702).
+pub const DELEGATION_PREFIX: [u8; 3] = [0xef, 0x01, 0x00];
+
+/// The code an authority ends up with once its delegation to `delegate` is applied.
+pub fn delegation_designator(delegate: Address) -> [u8; 23] {
+    let mut code = [0u8; 23];
+    code[..3].copy_from_slice(&DELEGATION_PREFIX);
+    code[3..].copy_from_slice(delegate.as_slice());
+    code
+}
+
+/// The delegate named by an account's code, if that code is a delegation designator.
+pub fn parse_delegation(code: &[u8]) -> Option<Address> {
+    match code.strip_prefix(&DELEGATION_PREFIX[..]) {
+        Some(delegate) if delegate.len() == 20 => Some(Address::from_slice(delegate)),
+        _ => None,
+    }
+}
+
+/// What an account's code makes of it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CodeKind {
+    /// No code at all.
+    Eoa,
+    /// Code that isn't a delegation designator.
+    Contract,
+    /// An EOA delegating to this address (EIP-7702).
+    Delegated(Address),
+}
+
+impl CodeKind {
+    pub fn of(code: &[u8]) -> Self {
+        match parse_delegation(code) {
+            Some(delegate) => CodeKind::Delegated(delegate),
+            None if code.is_empty() => CodeKind::Eoa,
+            None => CodeKind::Contract,
+        }
+    }
+}
+
+/// Recovers the signer of every authorization in the tx, in list order.
+pub fn authorities(tx: &Eip7702Transaction) -> Vec<Result<Address, TxError>> {
+    tx.authorization_list
+        .iter()
+        .map(Authorization7702::recover_authority)
+        .collect()
+}
+
+impl Encodable for Authorization7702 {
+    fn encode(&self, out: &mut dyn alloy_rlp::BufMut) {
+        Header {
+            list: true,
+            payload_length: self.payload_length(),
+        }
+        .encode(out);
+        self.chain_id.encode(out);
+        self.address.encode(out);
+        self.nonce.encode(out);
+        self.y_parity.encode(out);
+        self.r.encode(out);
+        self.s.encode(out);
+    }
+
+    // without this the default impl encodes into a scratch Vec just to measure it
+    fn length(&self) -> usize {
+        let payload_length = self.payload_length();
+        alloy_rlp::length_of_length(payload_length) + payload_length
+    }
+}
+
+/// Debug builds decode every signed encoding again: the decoder rejects
+/// integers with leading zero bytes, so a non-minimal field trips here.
+fn debug_assert_canonical(encoded: &[u8]) {
+    debug_assert!(
+        crate::decode::decode_signed_tx_lenient(encoded).is_ok(),
+        "non-canonical RLP: 0x{}",
+        hex::encode(encoded)
+    );
+}
+
+/// Sorts an access list by address and its storage keys by value, merging
+/// repeated addresses and dropping repeated keys, so the same set of slots
+/// always encodes to the same bytes.
+pub fn normalize_access_list(list: Vec<(Address, Vec<U256>)>) -> Vec<(Address, Vec<U256>)> {
+    let mut merged: BTreeMap<Address, BTreeSet<U256>> = BTreeMap::new();
+    for (address, keys) in list {
+        merged.entry(address).or_default().extend(keys);
+    }
+    merged
+        .into_iter()
+        .map(|(address, keys)| (address, keys.into_iter().collect()))
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccessListEntry {
+    address: String,
+    #[serde(default)]
+    storage_keys: Vec<String>,
+}
+
+/// Parses the JSON-RPC shape, `[{"address": "0x..", "storageKeys": ["0x.."]}]`.
+///
+/// Addresses must be 20 bytes and storage keys exactly 32 - a short key is
+/// more likely a typo than a slot meant to be zero-padded. The list is kept
+/// as given, not normalized.
+pub fn parse_access_list(json: &str) -> Result<Vec<(Address, Vec<U256>)>, TxError> {
+    let entries: Vec<AccessListEntry> = serde_json::from_str(json)
+        .map_err(|err| TxError::Config(format!("invalid access list: {err}")))?;
+    entries
+        .into_iter()
+        .map(|entry| {
+            let address = Address::from_str(&entry.address).map_err(|_| {
+                TxError::Config(format!("invalid access list address {:?}", entry.address))
+            })?;
+            let keys = entry
+                .storage_keys
+                .iter()
+                .map(|key| {
+                    B256::from_str(key)
+                        .map(|key| U256::from_be_bytes(key.0))
+                        .map_err(|_| {
+                            TxError::Config(format!(
+                                "invalid storage key {key:?}, expected 32 bytes of hex"
+                            ))
+                        })
+                })
+                .collect::<Result<_, _>>()?;
+            Ok((address, keys))
+        })
+        .collect()
+}
+
+/// An authorization list as JSON, in the shape nodes return it.
+pub fn authorizations_to_json(authorizations: &[Authorization7702]) -> String {
+    serde_json::to_string_pretty(authorizations).expect("authorizations always serialize")
+}
+
+/// The inverse of [`authorizations_to_json`]; also takes the list from an
+/// `eth_getTransactionByHash` result or a block explorer's JSON view.
+pub fn authorizations_from_json(json: &str) -> Result<Vec<Authorization7702>, TxError> {
+    serde_json::from_str(json)
+        .map_err(|err| TxError::Config(format!("invalid authorization list: {err}")))
+}
+
+/// Storage keys are fixed 32-byte strings, not minimal integers.
+const STORAGE_KEY_LENGTH: usize = 33;
+
+fn access_list_item_payload_length(keys: &[U256]) -> usize {
+    let keys_length = STORAGE_KEY_LENGTH * keys.len();
+    Address::ZERO.length() + alloy_rlp::length_of_length(keys_length) + keys_length
+}
+
+fn access_list_payload_length(list: &[(Address, Vec<U256>)]) -> usize {
+    list.iter()
+        .map(|(_, keys)| {
+            let item = access_list_item_payload_length(keys);
+            alloy_rlp::length_of_length(item) + item
+        })
+        .sum()
+}
+
+fn access_list_length(list: &[(Address, Vec<U256>)]) -> usize {
+    let payload_length = access_list_payload_length(list);
+    alloy_rlp::length_of_length(payload_length) + payload_length
+}
+
+/// `[[address, [key, ...]], ...]`
+fn encode_access_list(list: &[(Address, Vec<U256>)], out: &mut Vec<u8>) {
+    Header {
+        list: true,
+        payload_length: access_list_payload_length(list),
+    }
+    .encode(out);
+    for (address, keys) in list {
+        Header {
+            list: true,
+            payload_length: access_list_item_payload_length(keys),
+        }
+        .encode(out);
+        address.encode(out);
+        Header {
+            list: true,
+            payload_length: STORAGE_KEY_LENGTH * keys.len(),
+        }
+        .encode(out);
+        for key in keys {
+            B256::from(*key).encode(out);
+        }
+    }
+}
+
+/// If `to` is `None`, encode as empty bytes
+pub(crate) fn to_length(to: Option<Address>) -> usize {
+    match to {
+        Some(to_addr) => to_addr.length(),
+        None => 1,
+    }
+}
+
+pub(crate) fn encode_to(to: Option<Address>, out: &mut Vec<u8>) {
+    match to {
+        Some(to_addr) => to_addr.encode(out),
+        None => out.push(EMPTY_STRING_CODE),
+    }
+}
+
+/// Starts an `[type ||] list header` buffer with room for the whole encoding,
+/// so the payload can be encoded straight in after it - one allocation, no copy.
+pub(crate) fn list_buffer(tx_type: Option<u8>, payload_length: usize) -> Vec<u8> {
+    let header = Header {
+        list: true,
+        payload_length,
+    };
+    let mut buffer =
+        Vec::with_capacity(tx_type.is_some() as usize + header.length() + payload_length);
+    buffer.extend(tx_type);
+    header.encode(&mut buffer);
+    buffer
+}
@@ -0,0 +1,27 @@
+//! Wei amounts as decimal ether and gwei strings for output.
+
+use alloy::primitives::U256;
+
+/// `wei` in ether, e.g. `"1.5"`; see [`format_units`].
+pub fn format_ether(wei: U256) -> String {
+    format_units(wei, 18)
+}
+
+/// `wei` in gwei, e.g. `"30"` or `"0.000000001"`; see [`format_units`].
+pub fn format_gwei(wei: U256) -> String {
+    format_units(wei, 9)
+}
+
+/// `wei` divided by `10^decimals`, written out in full: no exponent, no
+/// trailing zeros, and no decimal point for whole amounts.
+fn format_units(wei: U256, decimals: usize) -> String {
+    let unit = U256::from(10).pow(U256::from(decimals));
+    let (whole, fraction) = wei.div_rem(unit);
+    let fraction = format!("{fraction:0>decimals$}");
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{whole}.{fraction}")
+    }
+}
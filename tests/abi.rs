@@ -0,0 +1,65 @@
+use alloy::{
+    hex,
+    primitives::{address, U256},
+};
+use example_7702::{
+    abi::{decode_address, decode_string, decode_uint256, revert_reason},
+    error::TxError,
+};
+
+/// `abi.encode("Hello")`.
+const HELLO: &str = "0000000000000000000000000000000000000000000000000000000000000020\
+                     0000000000000000000000000000000000000000000000000000000000000005\
+                     48656c6c6f000000000000000000000000000000000000000000000000000000";
+
+#[test]
+fn decodes_uint256() {
+    let data =
+        hex::decode("000000000000000000000000000000000000000000000000000000000000002a").unwrap();
+    assert_eq!(decode_uint256(&data).unwrap(), U256::from(42));
+    assert_eq!(decode_uint256(&[0xff; 32]).unwrap(), U256::MAX);
+}
+
+#[test]
+fn decodes_address() {
+    let data =
+        hex::decode("0000000000000000000000004f747741ef10551969f9688a8264fc6bb337fa5f").unwrap();
+    assert_eq!(
+        decode_address(&data).unwrap(),
+        address!("4F747741EF10551969F9688a8264FC6bb337fA5f")
+    );
+
+    // anything in the padding means it isn't an address
+    let mut dirty = data.clone();
+    dirty[0] = 1;
+    assert!(matches!(decode_address(&dirty), Err(TxError::Decode(_))));
+}
+
+#[test]
+fn decodes_string() {
+    assert_eq!(
+        decode_string(&hex::decode(HELLO).unwrap()).unwrap(),
+        "Hello"
+    );
+}
+
+#[test]
+fn truncated_data_is_rejected() {
+    assert!(matches!(decode_uint256(&[0; 31]), Err(TxError::Decode(_))));
+    // the length word promises more bytes than follow it
+    let data = hex::decode(HELLO).unwrap();
+    assert!(matches!(
+        decode_string(&data[..68]),
+        Err(TxError::Decode(_))
+    ));
+}
+
+#[test]
+fn decodes_revert_reason() {
+    let data = hex::decode(format!("08c379a0{HELLO}")).unwrap();
+    assert_eq!(revert_reason(&data).as_deref(), Some("Hello"));
+
+    // a custom error, or no data at all
+    assert_eq!(revert_reason(&hex::decode("deadbeef").unwrap()), None);
+    assert_eq!(revert_reason(&[]), None);
+}
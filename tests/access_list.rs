@@ -0,0 +1,37 @@
+use alloy::primitives::{address, U256};
+use example_7702::tx::parse_access_list;
+
+#[test]
+fn two_entries_parse_into_address_key_pairs() {
+    let json = r#"[
+        {
+            "address": "0x1111111111111111111111111111111111111111",
+            "storageKeys": [
+                "0x0000000000000000000000000000000000000000000000000000000000000000",
+                "0x00000000000000000000000000000000000000000000000000000000000000ff"
+            ]
+        },
+        { "address": "0x2222222222222222222222222222222222222222", "storageKeys": [] }
+    ]"#;
+    assert_eq!(
+        parse_access_list(json).unwrap(),
+        [
+            (
+                address!("1111111111111111111111111111111111111111"),
+                vec![U256::ZERO, U256::from(0xff)]
+            ),
+            (address!("2222222222222222222222222222222222222222"), vec![]),
+        ]
+    );
+}
+
+#[test]
+fn short_keys_and_bad_addresses_are_rejected() {
+    for json in [
+        r#"[{"address": "0x1111111111111111111111111111111111111111", "storageKeys": ["0x01"]}]"#,
+        r#"[{"address": "0x1234", "storageKeys": []}]"#,
+        r#"{"address": "0x1111111111111111111111111111111111111111"}"#,
+    ] {
+        assert!(parse_access_list(json).is_err(), "{json}");
+    }
+}
@@ -0,0 +1,179 @@
+use alloy::{
+    consensus::{SignableTransaction as _, TxEip1559, TxEip7702, TxEnvelope, TxLegacy},
+    eips::{
+        eip2718::Encodable2718,
+        eip2930::{AccessList, AccessListItem},
+        eip7702::Authorization,
+    },
+    primitives::{keccak256, Address, Bytes, TxKind, B256, U256},
+    signers::{local::PrivateKeySigner, SignerSync},
+};
+use example_7702::{
+    signing::finalize_signature,
+    tx::{Authorization7702, Eip1559Transaction, Eip7702Transaction, LegacyTransaction},
+};
+use secp256k1::SecretKey;
+
+const KEY: [u8; 32] = [0x11; 32];
+const AUTHORITY_KEY: [u8; 32] = [0x22; 32];
+const TO: Address = Address::repeat_byte(0xaa);
+const DELEGATE: Address = Address::repeat_byte(0xdd);
+
+fn key() -> SecretKey {
+    SecretKey::from_slice(&KEY).unwrap()
+}
+
+fn alloy_signer(key: [u8; 32]) -> PrivateKeySigner {
+    PrivateKeySigner::from_bytes(&B256::from(key)).unwrap()
+}
+
+fn data() -> Vec<u8> {
+    vec![0xd0, 0x9d, 0xe0, 0x8a]
+}
+
+fn access_list() -> Vec<(Address, Vec<U256>)> {
+    vec![(TO, vec![U256::from(1), U256::from(2)])]
+}
+
+fn alloy_access_list() -> AccessList {
+    AccessList(vec![AccessListItem {
+        address: TO,
+        storage_keys: vec![B256::with_last_byte(1), B256::with_last_byte(2)],
+    }])
+}
+
+/// Checks the raw bytes and, through them, the tx hash.
+fn assert_same(ours: &[u8], theirs: TxEnvelope) {
+    assert_eq!(hex::encode(ours), hex::encode(theirs.encoded_2718()));
+    assert_eq!(keccak256(ours), *theirs.tx_hash());
+}
+
+#[test]
+fn legacy_matches_alloy() {
+    let mut ours = LegacyTransaction {
+        nonce: U256::from(7),
+        gas_price: U256::from(20_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(TO),
+        value: U256::from(1_000),
+        data: data(),
+        v: 1337,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut ours, &key()).unwrap();
+
+    let theirs = TxLegacy {
+        chain_id: Some(1337),
+        nonce: 7,
+        gas_price: 20_000_000_000,
+        gas_limit: 21_000,
+        to: TxKind::Call(TO),
+        value: U256::from(1_000),
+        input: Bytes::from(data()),
+    };
+    let signature = alloy_signer(KEY)
+        .sign_hash_sync(&theirs.signature_hash())
+        .unwrap();
+    assert_same(
+        &ours.rlp_encode_signed(),
+        theirs.into_signed(signature).into(),
+    );
+}
+
+#[test]
+fn eip1559_matches_alloy() {
+    for to in [Some(TO), None] {
+        let mut ours = Eip1559Transaction {
+            chain_id: 1337,
+            nonce: U256::from(7),
+            max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+            max_fee_per_gas: U256::from(3_000_000_000u64),
+            gas_limit: U256::from(60_000),
+            to,
+            value: U256::from(1_000),
+            data: data(),
+            access_list: access_list(),
+            y_parity: 0,
+            r: U256::ZERO,
+            s: U256::ZERO,
+        };
+        finalize_signature(&mut ours, &key()).unwrap();
+
+        let theirs = TxEip1559 {
+            chain_id: 1337,
+            nonce: 7,
+            gas_limit: 60_000,
+            max_fee_per_gas: 3_000_000_000,
+            max_priority_fee_per_gas: 1_000_000_000,
+            to: to.map_or(TxKind::Create, TxKind::Call),
+            value: U256::from(1_000),
+            access_list: alloy_access_list(),
+            input: Bytes::from(data()),
+        };
+        let signature = alloy_signer(KEY)
+            .sign_hash_sync(&theirs.signature_hash())
+            .unwrap();
+        assert_same(
+            &ours.rlp_encode_signed(),
+            theirs.into_signed(signature).into(),
+        );
+    }
+}
+
+#[test]
+fn eip7702_matches_alloy() {
+    let authority_key = SecretKey::from_slice(&AUTHORITY_KEY).unwrap();
+    let mut ours = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(3_000_000_000u64),
+        gas_limit: U256::from(100_000),
+        to: Some(TO),
+        value: U256::ZERO,
+        data: data(),
+        access_list: access_list(),
+        authorization_list: vec![
+            Authorization7702::sign(0, DELEGATE, U256::from(3), &authority_key).unwrap(),
+            Authorization7702::sign(1337, DELEGATE, U256::from(4), &authority_key).unwrap(),
+        ],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut ours, &key()).unwrap();
+
+    let authority = alloy_signer(AUTHORITY_KEY);
+    let authorization_list = [(0, 3), (1337, 4)]
+        .into_iter()
+        .map(|(chain_id, nonce)| {
+            let auth = Authorization {
+                chain_id,
+                address: DELEGATE,
+                nonce,
+            };
+            let signature = authority.sign_hash_sync(&auth.signature_hash()).unwrap();
+            auth.into_signed(signature)
+        })
+        .collect();
+    let theirs = TxEip7702 {
+        chain_id: 1337,
+        nonce: 7,
+        gas_limit: 100_000,
+        max_fee_per_gas: 3_000_000_000,
+        max_priority_fee_per_gas: 1_000_000_000,
+        to: TO,
+        value: U256::ZERO,
+        access_list: alloy_access_list(),
+        authorization_list,
+        input: Bytes::from(data()),
+    };
+    let signature = alloy_signer(KEY)
+        .sign_hash_sync(&theirs.signature_hash())
+        .unwrap();
+    assert_same(
+        &ours.rlp_encode_signed(),
+        theirs.into_signed(signature).into(),
+    );
+}
@@ -0,0 +1,227 @@
+mod common;
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+
+use alloy::primitives::{address, B256, U256};
+use common::{HttpReply, MockNode};
+use example_7702::{
+    batch::{parse_recipients, send_batch, NonceManager, RowOutcome, TransferTemplate},
+    error::TxError,
+    rpc::Provider,
+    signing::LocalSigner,
+};
+use secp256k1::SecretKey;
+use serde_json::json;
+
+#[test]
+fn three_rows_become_three_transfers_with_sequential_nonces() {
+    let csv = "\
+# airdrop
+0x1111111111111111111111111111111111111111,100
+0x2222222222222222222222222222222222222222, 200
+
+0x3333333333333333333333333333333333333333,0x12c
+";
+    let rows = parse_recipients(csv)
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap();
+    assert_eq!(rows.len(), 3);
+    assert_eq!(rows[2].line, 5);
+
+    let template = TransferTemplate {
+        chain_id: 1337,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21000),
+    };
+    let mut nonces = NonceManager::new(U256::from(9));
+    let txs: Vec<_> = rows
+        .iter()
+        .map(|row| template.build(row, nonces.next_nonce()))
+        .collect();
+
+    assert_eq!(
+        txs.iter().map(|tx| tx.nonce).collect::<Vec<_>>(),
+        [U256::from(9), U256::from(10), U256::from(11)]
+    );
+    assert_eq!(
+        txs[1].to,
+        Some(address!("2222222222222222222222222222222222222222"))
+    );
+    assert_eq!(
+        txs.iter().map(|tx| tx.value).collect::<Vec<_>>(),
+        [U256::from(100), U256::from(200), U256::from(300)]
+    );
+}
+
+#[test]
+fn bad_rows_are_reported_by_line() {
+    let csv = "\
+0x1111111111111111111111111111111111111111,100
+0x12345,100
+0x1111111111111111111111111111111111111111,lots
+0xAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAa,1
+";
+    let rows = parse_recipients(csv);
+    assert!(rows[0].is_ok());
+    for (row, line) in rows[1..].iter().zip(2..) {
+        let err = row.as_ref().unwrap_err().to_string();
+        assert!(err.contains(&format!("line {line}:")), "{err}");
+    }
+}
+
+#[test]
+fn released_nonce_is_handed_out_again() {
+    let mut nonces = NonceManager::new(U256::from(3));
+    let nonce = nonces.next_nonce();
+    nonces.release(nonce);
+    assert_eq!(nonces.next_nonce(), nonce);
+    assert_eq!(nonces.next_nonce(), U256::from(4));
+}
+
+#[tokio::test]
+async fn interrupted_batch_reports_what_was_sent() {
+    // Ctrl-C arrives while the first tx is in flight
+    let interrupted = Arc::new(AtomicBool::new(false));
+    let flag = interrupted.clone();
+    let node = MockNode::with_methods(move |method, _| match method {
+        "eth_sendRawTransaction" => {
+            flag.store(true, Ordering::SeqCst);
+            json!({ "result": B256::repeat_byte(0x11) })
+        }
+        _ => json!({ "result": null }),
+    });
+    let provider = Provider::new(&node.url);
+    let signer = LocalSigner::new(SecretKey::from_slice(&[0x11; 32]).unwrap());
+    let template = TransferTemplate {
+        chain_id: 1337,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21_000),
+    };
+    let rows = parse_recipients(
+        "0x1111111111111111111111111111111111111111,100\n\
+         0x2222222222222222222222222222222222222222,200\n\
+         0x3333333333333333333333333333333333333333,300\n",
+    );
+    let mut nonces = NonceManager::new(U256::from(7));
+
+    let outcomes = send_batch(
+        &provider,
+        &signer,
+        rows,
+        &template,
+        &mut nonces,
+        &interrupted,
+    )
+    .await;
+
+    assert!(matches!(
+        &outcomes[0],
+        RowOutcome::Sent { nonce, tx_hash, .. }
+            if *nonce == U256::from(7) && *tx_hash == B256::repeat_byte(0x11)
+    ));
+    assert!(matches!(&outcomes[1], RowOutcome::NotSent(r) if r.line == 2));
+    assert!(matches!(&outcomes[2], RowOutcome::NotSent(r) if r.line == 3));
+    // only the first nonce was used
+    assert_eq!(nonces.next_nonce(), U256::from(8));
+    assert_eq!(node.methods(), ["eth_sendRawTransaction"]);
+}
+
+#[tokio::test]
+async fn nonce_of_an_unanswered_send_is_not_reused() {
+    // the first send drops the connection, the node might have the tx
+    let sends = AtomicUsize::new(0);
+    let node = MockNode::start_http(move |req| {
+        (sends.fetch_add(1, Ordering::SeqCst) > 0).then(|| {
+            HttpReply::ok(
+                json!({ "jsonrpc": "2.0", "id": req["id"], "result": B256::repeat_byte(0x22) })
+                    .to_string(),
+            )
+        })
+    });
+    let provider = Provider::new(&node.url);
+    let signer = LocalSigner::new(SecretKey::from_slice(&[0x11; 32]).unwrap());
+    let template = TransferTemplate {
+        chain_id: 1337,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21_000),
+    };
+    let rows = parse_recipients(
+        "0x1111111111111111111111111111111111111111,100\n\
+         0x2222222222222222222222222222222222222222,200\n",
+    );
+    let mut nonces = NonceManager::new(U256::from(7));
+
+    let outcomes = send_batch(
+        &provider,
+        &signer,
+        rows,
+        &template,
+        &mut nonces,
+        &AtomicBool::new(false),
+    )
+    .await;
+
+    assert!(matches!(
+        &outcomes[0],
+        RowOutcome::Unknown { nonce, error: TxError::Http(_), .. } if *nonce == U256::from(7)
+    ));
+    assert!(matches!(
+        &outcomes[1],
+        RowOutcome::Sent { nonce, .. } if *nonce == U256::from(8)
+    ));
+    assert_eq!(nonces.next_nonce(), U256::from(9));
+}
+
+#[tokio::test]
+async fn nonce_of_a_rejected_send_goes_to_the_next_row() {
+    let sends = AtomicUsize::new(0);
+    let node = MockNode::with_methods(move |_, _| {
+        if sends.fetch_add(1, Ordering::SeqCst) == 0 {
+            json!({ "error": { "code": -32000, "message": "intrinsic gas too low" } })
+        } else {
+            json!({ "result": B256::repeat_byte(0x22) })
+        }
+    });
+    let provider = Provider::new(&node.url);
+    let signer = LocalSigner::new(SecretKey::from_slice(&[0x11; 32]).unwrap());
+    let template = TransferTemplate {
+        chain_id: 1337,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21_000),
+    };
+    let rows = parse_recipients(
+        "0x1111111111111111111111111111111111111111,100\n\
+         0x2222222222222222222222222222222222222222,200\n",
+    );
+    let mut nonces = NonceManager::new(U256::from(7));
+
+    let outcomes = send_batch(
+        &provider,
+        &signer,
+        rows,
+        &template,
+        &mut nonces,
+        &AtomicBool::new(false),
+    )
+    .await;
+
+    assert!(matches!(
+        &outcomes[0],
+        RowOutcome::Failed {
+            error: TxError::Rpc(_),
+            ..
+        }
+    ));
+    assert!(matches!(
+        &outcomes[1],
+        RowOutcome::Sent { nonce, .. } if *nonce == U256::from(7)
+    ));
+}
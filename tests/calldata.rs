@@ -0,0 +1,56 @@
+use std::io::Write;
+
+use alloy::primitives::hex;
+use example_7702::{
+    calldata::DataInput,
+    counter::{counter_calldata, CounterMethod},
+    error::TxError,
+};
+
+#[test]
+fn every_source_resolves_to_the_same_bytes() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(file, "0xd09de08a").unwrap();
+    let path = file.path().to_str().unwrap();
+
+    for (data, data_file, call) in [
+        (Some("0xd09de08a"), None, None),
+        (None, Some(path), None),
+        (None, None, Some("increment()")),
+    ] {
+        let input = DataInput::from_flags(data, data_file, call)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            input.resolve().unwrap(),
+            counter_calldata(CounterMethod::Increment),
+            "{input:?}"
+        );
+    }
+    assert_eq!(DataInput::from_flags(None, None, None).unwrap(), None);
+}
+
+#[test]
+fn two_sources_are_an_error() {
+    for (data, data_file, call) in [
+        (Some("0x01"), Some("data.hex"), None),
+        (Some("0x01"), None, Some("increment()")),
+        (None, Some("data.hex"), Some("increment()")),
+    ] {
+        assert!(matches!(
+            DataInput::from_flags(data, data_file, call),
+            Err(TxError::Config(_))
+        ));
+    }
+}
+
+#[test]
+fn call_takes_only_functions_without_arguments() {
+    assert_eq!(
+        DataInput::Call("number()".into()).resolve().unwrap(),
+        hex!("8381f58a")
+    );
+    for bad in ["setNumber(uint256)", "increment", "()", "1up()"] {
+        assert!(DataInput::Call(bad.into()).resolve().is_err(), "{bad}");
+    }
+}
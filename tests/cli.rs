@@ -0,0 +1,1293 @@
+mod common;
+
+use alloy::primitives::{Address, U256};
+use common::{devnet, run_cli, MockNode};
+use example_7702::{
+    config::KeyConfig,
+    decode::{decode_authorization, decode_signed_tx, SignedTx},
+    safe::safe_message_hash,
+    signing::{address_from_pkey, recover_address, signature_from_bytes},
+};
+use serde_json::{json, Value};
+use std::sync::atomic::Ordering::SeqCst;
+
+#[test]
+fn explicit_nonce_skips_the_nonce_lookup() {
+    for tx_type in ["legacy", "1559"] {
+        let node = MockNode::with_methods(devnet);
+        let out = run_cli(&node, &["--tx-type", tx_type, "--nonce", "7"]);
+
+        assert!(out.status.success(), "{out:?}");
+        assert!(String::from_utf8_lossy(&out.stdout).contains("Nonce:     7"));
+        assert!(!node
+            .methods()
+            .contains(&"eth_getTransactionCount".to_string()));
+    }
+}
+
+#[test]
+fn nonce_must_be_a_non_negative_integer() {
+    let node = MockNode::with_methods(devnet);
+    for bad in ["-1", "abc", &format!("1{}", "0".repeat(80))] {
+        let out = run_cli(&node, &["--tx-type", "legacy", "--nonce", bad]);
+        assert!(!out.status.success(), "{bad}");
+    }
+    assert!(node.methods().is_empty());
+}
+
+#[test]
+fn mistyped_checksum_is_refused_before_any_request() {
+    let node = MockNode::with_methods(devnet);
+    let typo = "0x4F747741EF10551969F9688a8264FC6bb337Fa5f";
+    for args in [
+        ["--tx-type", "7702", "--delegate-to", typo],
+        ["--tx-type", "1559", "--to", typo],
+    ] {
+        let out = run_cli(&node, &args);
+        assert!(!out.status.success(), "{args:?}");
+        let stderr = String::from_utf8_lossy(&out.stderr);
+        assert!(stderr.contains("bad EIP-55 checksum"), "{stderr}");
+    }
+    assert!(node.methods().is_empty());
+}
+
+#[test]
+fn node_sign_hands_the_unsigned_tx_to_eth_send_transaction() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_sendTransaction" => json!({ "result": format!("0x{}", "22".repeat(32)) }),
+        _ => devnet(method, params),
+    });
+    let from = "0x2222222222222222222222222222222222222222";
+    let out = run_cli(&node, &["--tx-type", "7702", "--node-sign", "--from", from]);
+    assert!(out.status.success(), "{out:?}");
+
+    let methods = node.methods();
+    assert!(!methods.contains(&"eth_sendRawTransaction".to_string()));
+    let requests = node.requests.lock().unwrap();
+    let request = &requests
+        .iter()
+        .find(|req| req["method"] == "eth_sendTransaction")
+        .expect("nothing went to eth_sendTransaction")["params"][0];
+    assert_eq!(request["type"], "0x4");
+    assert_eq!(request["from"], from);
+    assert_eq!(request["authorizationList"].as_array().unwrap().len(), 1);
+    assert!(request.get("r").is_none());
+}
+
+/// The raw tx the CLI handed to `eth_sendRawTransaction`.
+fn sent_tx(node: &MockNode) -> SignedTx {
+    let requests = node.requests.lock().unwrap();
+    let send = requests
+        .iter()
+        .find(|req| req["method"] == "eth_sendRawTransaction")
+        .expect("no tx was sent");
+    let raw = send["params"][0].as_str().unwrap();
+    decode_signed_tx(&hex::decode(raw.trim_start_matches("0x")).unwrap()).unwrap()
+}
+
+#[test]
+fn explicit_auth_nonce_lands_in_the_authorization() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &["--tx-type", "7702", "--nonce", "7", "--auth-nonce", "9"],
+    );
+    assert!(out.status.success(), "{out:?}");
+    assert!(!node
+        .methods()
+        .contains(&"eth_getTransactionCount".to_string()));
+
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    assert_eq!(tx.nonce, U256::from(7));
+    assert_eq!(tx.authorization_list[0].nonce, U256::from(9));
+}
+
+#[test]
+fn auth_nonce_alone_still_fetches_the_sender_nonce() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702", "--auth-nonce", "9"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let lookups = node
+        .methods()
+        .iter()
+        .filter(|method| *method == "eth_getTransactionCount")
+        .count();
+    assert_eq!(lookups, 1);
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    // from the mock
+    assert_eq!(tx.nonce, U256::from(3));
+    assert_eq!(tx.authorization_list[0].nonce, U256::from(9));
+}
+
+/// The README authority, already delegating to 0x2222...
+fn delegated(method: &str, params: &Value) -> Value {
+    match method {
+        "eth_getCode" => json!({ "result": format!("0xef0100{}", "22".repeat(20)) }),
+        _ => devnet(method, params),
+    }
+}
+
+#[test]
+fn existing_delegation_is_reported_and_warned_about() {
+    let node = MockNode::with_methods(delegated);
+    let out = run_cli(&node, &["--tx-type", "7702"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains(": 0x2222222222222222222222222222222222222222"));
+    assert!(stdout.contains("Warning: this authorization replaces the delegation"));
+}
+
+#[test]
+fn ensure_delegation_sends_nothing_when_already_delegated() {
+    let node = MockNode::with_methods(delegated);
+    let delegate = format!("0x{}", "22".repeat(20));
+    let out = run_cli(&node, &["ensure-delegation", "--delegate", &delegate]);
+
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("already delegated"));
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn ensure_delegation_replaces_a_different_delegation() {
+    let node = MockNode::with_methods(delegated);
+    let delegate = format!("0x{}", "33".repeat(20));
+    let out = run_cli(&node, &["ensure-delegation", "--delegate", &delegate]);
+
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    assert_eq!(
+        tx.authorization_list[0].address,
+        delegate.parse::<Address>().unwrap()
+    );
+}
+
+#[test]
+fn fail_if_delegated_aborts_before_sending() {
+    let node = MockNode::with_methods(delegated);
+    let out = run_cli(&node, &["--tx-type", "7702", "--fail-if-delegated"]);
+
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("already delegates"));
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn undelegated_authority_reports_none() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702", "--fail-if-delegated"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains(": none"));
+}
+
+#[test]
+fn recipients_send_one_transfer_per_good_row() {
+    let dir = tempfile::tempdir().unwrap();
+    let csv = dir.path().join("recipients.csv");
+    std::fs::write(
+        &csv,
+        "0x1111111111111111111111111111111111111111,100\n\
+         not-an-address,1\n\
+         0x3333333333333333333333333333333333333333,300\n",
+    )
+    .unwrap();
+    let csv = csv.to_str().unwrap();
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--recipients", csv]);
+    // aborts before sending anything
+    assert!(!out.status.success());
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+
+    let out = run_cli(&node, &["--recipients", csv, "--skip-bad-rows"]);
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("2 sent, 1 failed"), "{stdout}");
+    assert!(stdout.contains("line 2: invalid address"), "{stdout}");
+
+    let requests = node.requests.lock().unwrap();
+    let nonces: Vec<_> = requests
+        .iter()
+        .filter(|req| req["method"] == "eth_sendRawTransaction")
+        .map(|req| {
+            let raw = req["params"][0].as_str().unwrap();
+            match decode_signed_tx(&hex::decode(raw.trim_start_matches("0x")).unwrap()).unwrap() {
+                SignedTx::Eip1559(tx) => tx.nonce,
+                _ => panic!("not a 1559 tx"),
+            }
+        })
+        .collect();
+    assert_eq!(nonces, [U256::from(3), U256::from(4)]);
+}
+
+#[test]
+fn auto_access_list_is_normalized_into_the_7702_tx() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_createAccessList" => json!({ "result": {
+            "accessList": [
+                {
+                    "address": "0x2222222222222222222222222222222222222222",
+                    "storageKeys": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000002",
+                        "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    ]
+                },
+                {
+                    "address": "0x1111111111111111111111111111111111111111",
+                    "storageKeys": []
+                },
+                {
+                    "address": "0x2222222222222222222222222222222222222222",
+                    "storageKeys": [
+                        "0x0000000000000000000000000000000000000000000000000000000000000001"
+                    ]
+                }
+            ],
+            "gasUsed": "0x7530"
+        } }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["--tx-type", "7702", "--auto-access-list"]);
+    assert!(out.status.success(), "{out:?}");
+
+    // the call ran with the authority already delegated
+    let requests = node.requests.lock().unwrap().clone();
+    let create = requests
+        .iter()
+        .find(|req| req["method"] == "eth_createAccessList")
+        .unwrap();
+    let overrides = create["params"][2].as_object().unwrap();
+    let code = overrides.values().next().unwrap()["code"].as_str().unwrap();
+    assert!(code.starts_with("0xef0100"), "{code}");
+
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    let (a, b) = (
+        "0x1111111111111111111111111111111111111111"
+            .parse()
+            .unwrap(),
+        "0x2222222222222222222222222222222222222222"
+            .parse()
+            .unwrap(),
+    );
+    assert_eq!(
+        tx.access_list,
+        [(a, vec![]), (b, vec![U256::from(1), U256::from(2)])]
+    );
+    // 46000 plus 2 addresses and 2 keys
+    assert_eq!(tx.gas_limit, U256::from(46000 + 2 * 2400 + 2 * 1900));
+}
+
+#[test]
+fn json_output_is_a_single_object() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--json"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let output: Value = serde_json::from_slice(&out.stdout).expect("stdout is one JSON value");
+    let requests = node.requests.lock().unwrap().clone();
+    let send = requests
+        .iter()
+        .find(|req| req["method"] == "eth_sendRawTransaction")
+        .unwrap();
+    assert_eq!(output["raw_tx"], send["params"][0]);
+    assert_eq!(output["tx_hash"], format!("0x{}", "11".repeat(32)));
+    assert_eq!(output["status"], "success");
+    assert_eq!(output["receipt"]["blockNumber"], "0x10");
+    assert_eq!(output["nonce"], "0x3");
+}
+
+#[test]
+fn json_errors_are_json_too() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--chain-id", "1", "--json"]);
+    assert_eq!(out.status.code(), Some(1));
+
+    let output: Value = serde_json::from_slice(&out.stdout).expect("stdout is one JSON value");
+    assert!(output["error"].as_str().unwrap().contains("chain id 1337"));
+}
+
+#[test]
+fn access_list_file_lands_in_the_1559_tx() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("access-list.json");
+    std::fs::write(
+        &path,
+        r#"[{"address": "0x1111111111111111111111111111111111111111",
+             "storageKeys": ["0x0000000000000000000000000000000000000000000000000000000000000007"]}]"#,
+    )
+    .unwrap();
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &["--tx-type", "1559", "--access-list", path.to_str().unwrap()],
+    );
+    assert!(out.status.success(), "{out:?}");
+
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.access_list.len(), 1);
+    assert_eq!(tx.access_list[0].1, [U256::from(7)]);
+    assert_eq!(tx.gas_limit, U256::from(21000 + 2400 + 1900));
+}
+
+#[test]
+fn print_hash_stops_before_signing() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--print-hash", "--json"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let output: Value = serde_json::from_slice(&out.stdout).unwrap();
+    let hash = output["signing_hash"].as_str().unwrap();
+    assert_eq!(hash.len(), 2 + 64);
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn insufficient_funds_reports_the_shortfall() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_sendRawTransaction" => json!({ "error": {
+            "code": -32000,
+            "message": "insufficient funds for gas * price + value: balance 0, tx cost 1000046000000000000"
+        } }),
+        // half an ETH
+        "eth_getBalance" => json!({ "result": "0x6f05b59d3b20000" }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["--tx-type", "1559", "--gas", "21000"]);
+    assert!(!out.status.success());
+
+    // 1 ETH + 21000 gas at the default 1 gwei max fee, minus the 0.5 ETH balance
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("500021000000000000 wei (0.500021 ETH) short"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn deploy_sends_a_creation_tx_and_predicts_its_address() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &[
+            "deploy",
+            "--bytecode",
+            "0x6080",
+            "--args",
+            "uint256:7",
+            "bool:true",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.to, None);
+    assert_eq!(tx.data.len(), 2 + 2 * 32);
+    assert_eq!(tx.data[2 + 31], 7);
+    assert_eq!(tx.data[2 + 63], 1);
+
+    // the mock's sender nonce is 3
+    let from = example_7702::signing::recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity)
+        .unwrap();
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains(&format!("Contract address: {}", from.create(3))),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn auto_gas_deploy_asks_the_node_for_the_creation_gas() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => {
+            // a creation: no `to`, the initcode as data
+            assert!(params[0].get("to").is_none(), "{params}");
+            assert_eq!(params[0]["data"], "0x6080");
+            json!({ "result": "0x1d4c0" })
+        }
+        _ => devnet(method, params),
+    });
+    let out = run_cli(
+        &node,
+        &[
+            "--auto-gas",
+            "--gas-multiplier",
+            "1.5",
+            "deploy",
+            "--bytecode",
+            "0x6080",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    // 120000 from the node, times 1.5
+    assert_eq!(tx.gas_limit, U256::from(180_000));
+
+    // an estimate below the creation's intrinsic gas is raised to it
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => json!({ "result": "0x5208" }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["--auto-gas", "deploy", "--bytecode", "0x6080"]);
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    // 21000 + 2 * 16 calldata + 32000 CREATE + 2 for one initcode word
+    assert_eq!(tx.gas_limit, U256::from(53_034));
+}
+
+#[test]
+fn to_address_replaces_the_default_recipient() {
+    let node = MockNode::with_methods(devnet);
+    let to = "0x2222222222222222222222222222222222222222";
+    let out = run_cli(&node, &["--tx-type", "1559", "--to", to]);
+    assert!(out.status.success(), "{out:?}");
+
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.to, Some(to.parse().unwrap()));
+    // an address needs no lookup
+    assert!(!node.methods().contains(&"eth_call".to_string()));
+}
+
+#[test]
+fn safe_message_signature_recovers_to_the_owner() {
+    let node = MockNode::with_methods(devnet);
+    let safe = "0xa063cb7cfd8e57c30c788a0572cbbf2129ae56b6";
+    let out = run_cli(
+        &node,
+        &[
+            "sign-safe-message",
+            "--safe",
+            safe,
+            "--message",
+            "0x68656c6c6f",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    assert!(node.methods().is_empty());
+
+    let sig = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let sig: [u8; 65] = hex::decode(sig.trim_start_matches("0x"))
+        .unwrap()
+        .try_into()
+        .unwrap();
+    // v is 27 or 28, which a Safe takes as a plain ECDSA owner signature
+    assert!(matches!(sig[64], 27 | 28));
+    let (r, s, y_parity) = signature_from_bytes(&sig).unwrap();
+    let hash = safe_message_hash(safe.parse().unwrap(), 1337, b"hello");
+    let owner = recover_address(&hash, r, s, y_parity).unwrap();
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(stderr.contains(&format!("Owner: {owner}")), "{stderr}");
+    assert!(
+        stderr.contains(&format!("Safe message hash: {hash}")),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn signed_authorization_round_trips_into_a_7702_tx() {
+    let node = MockNode::with_methods(devnet);
+    let delegate = "0x4F747741EF10551969F9688a8264FC6bb337fA5f";
+    let out = run_cli(
+        &node,
+        &[
+            "sign-authorization",
+            "--chain-id",
+            "1337",
+            "--delegate",
+            delegate,
+            "--nonce",
+            "5",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    // offline: nothing asked of the node
+    assert!(node.methods().is_empty());
+
+    let raw = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    let auth = decode_authorization(&hex::decode(raw.trim_start_matches("0x")).unwrap()).unwrap();
+    assert_eq!(auth.chain_id, 1337);
+    assert_eq!(auth.address, delegate.parse::<Address>().unwrap());
+    assert_eq!(auth.nonce, U256::from(5));
+    let authority = auth.recover_authority().unwrap();
+    assert!(String::from_utf8_lossy(&out.stderr).contains(&format!("Authority: {authority}")));
+
+    let out = run_cli(&node, &["--tx-type", "7702", "--authorization-hex", &raw]);
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    assert_eq!(tx.authorization_list.len(), 2);
+    assert_eq!(
+        tx.authorization_list[1].recover_authority().unwrap(),
+        authority
+    );
+    assert_eq!(tx.gas_limit, U256::from(21000 + 2 * 25000));
+}
+
+#[test]
+fn print_strategy_sends_nothing() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--strategy", "print"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Not sent (--strategy print)"), "{stdout}");
+    let methods = node.methods();
+    assert!(!methods.contains(&"eth_sendRawTransaction".to_string()));
+    assert!(!methods.contains(&"eth_getTransactionReceipt".to_string()));
+}
+
+#[test]
+fn send_strategy_does_not_wait_but_wait_does() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--strategy", "send"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+    assert!(!node
+        .methods()
+        .contains(&"eth_getTransactionReceipt".to_string()));
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--strategy", "wait"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(node
+        .methods()
+        .contains(&"eth_getTransactionReceipt".to_string()));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("SUCCESS"));
+}
+
+#[test]
+fn explain_shows_the_type_byte_and_named_fields() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702", "--explain"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let explained: Vec<&str> = stdout
+        .lines()
+        .skip_while(|line| !line.starts_with("type: "))
+        .collect();
+    assert_eq!(
+        explained.first(),
+        Some(&"type: 0x04 (EIP-7702)"),
+        "{stdout}"
+    );
+    assert_eq!(explained[1], "rlp: [");
+    assert!(explained.contains(&"  authorization_list: ["), "{stdout}");
+    assert!(
+        explained.iter().any(|line| line.contains("0x05")),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn debug_auth_prints_the_hash_of_the_magic_rlp() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702", "--debug-auth"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let field = |name: &str| {
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix(name))
+            .unwrap_or_else(|| panic!("no {name} in {stdout}"))
+            .trim()
+            .to_string()
+    };
+    let magic = hex::decode(field("Magic RLP:").trim_start_matches("0x")).unwrap();
+    assert_eq!(magic[0], 0x05);
+    assert_eq!(
+        field("Signing hash:"),
+        alloy::primitives::keccak256(&magic).to_string()
+    );
+
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    let authority = tx.authorization_list[0].recover_authority().unwrap();
+    assert_eq!(field("Signer:"), authority.to_checksum(None));
+}
+
+#[test]
+fn access_list_from_tx_looks_up_a_hash() {
+    let tx_hash = format!("0x{}", "ab".repeat(32));
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_getTransactionByHash" => {
+            assert_eq!(params[0], format!("0x{}", "ab".repeat(32)));
+            json!({ "result": { "accessList": [{
+                "address": "0x1111111111111111111111111111111111111111",
+                "storageKeys": [format!("0x{:064x}", 5)],
+            }] } })
+        }
+        _ => devnet(method, params),
+    });
+    let out = run_cli(
+        &node,
+        &["--tx-type", "1559", "--access-list-from-tx", &tx_hash],
+    );
+    assert!(out.status.success(), "{out:?}");
+
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(
+        tx.access_list,
+        [(
+            alloy::primitives::Address::repeat_byte(0x11),
+            vec![U256::from(5)]
+        )]
+    );
+}
+
+#[test]
+fn no_eip155_signs_a_chainless_legacy_tx() {
+    let sender = |extra: &[&str]| {
+        let node = MockNode::with_methods(devnet);
+        let out = run_cli(&node, &[&["--tx-type", "legacy"], extra].concat());
+        assert!(out.status.success(), "{out:?}");
+        let SignedTx::Legacy(tx) = sent_tx(&node) else {
+            panic!("not a legacy tx");
+        };
+        (tx.v, tx.recover_sender().unwrap())
+    };
+
+    let (v, pre_155) = sender(&["--no-eip155"]);
+    assert!(v == 27 || v == 28, "v = {v}");
+    // the same key signs either way; only the hash and v differ
+    let (v, eip_155) = sender(&[]);
+    assert!(v >= 35 + 2 * 1337);
+    assert_eq!(pre_155, eip_155);
+}
+
+#[test]
+fn no_eip155_is_only_for_legacy_txs() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--no-eip155"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("--no-eip155 needs --tx-type legacy"));
+}
+
+#[test]
+fn call_sends_the_selector_as_data() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--call", "increment()"]);
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.data, [0xd0, 0x9d, 0xe0, 0x8a]);
+
+    let out = run_cli(
+        &node,
+        &[
+            "--tx-type",
+            "1559",
+            "--call",
+            "increment()",
+            "--data",
+            "0x01",
+        ],
+    );
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("only one of --data"));
+}
+
+#[test]
+fn from_that_disagrees_with_the_key_warns_or_fails() {
+    let other = format!("0x{}", "ab".repeat(20));
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--from", &other]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("Warning: the key signs for"));
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "1559", "--from", &other, "--strict"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("(--strict)"));
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn a_pre_prague_node_is_warned_about_before_sending_7702() {
+    let node = MockNode::with_methods(|method, params| match method {
+        // a Cancun block: no requestsHash
+        "eth_getBlockByNumber" => json!({ "result": {
+            "number": "0x10",
+            "baseFeePerGas": "0x7",
+            "timestamp": "0x6553f100",
+            "gasLimit": "0x1c9c380",
+        } }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["--tx-type", "7702"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("may predate Prague"));
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702"]);
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("may predate Prague"));
+}
+
+fn node_with_base_fee(base_fee: &'static str) -> MockNode {
+    MockNode::with_methods(move |method, params| match method {
+        "eth_getBlockByNumber" => {
+            let mut block = devnet(method, params);
+            block["result"]["baseFeePerGas"] = json!(base_fee);
+            block
+        }
+        _ => devnet(method, params),
+    })
+}
+
+#[test]
+fn max_fee_close_to_the_base_fee_is_warned_about() {
+    // base fee 800, so the next block's may be 900
+    let node = node_with_base_fee("0x320");
+    let out = run_cli(
+        &node,
+        &[
+            "--tx-type",
+            "1559",
+            "--max-fee-per-gas",
+            "850",
+            "--max-priority-fee-per-gas",
+            "1",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("Warning: max fee per gas 850 is below 900"),
+        "{stdout}"
+    );
+}
+
+#[test]
+fn max_fee_below_the_base_fee_aborts_before_signing() {
+    let node = node_with_base_fee("0x3e8");
+    let out = run_cli(
+        &node,
+        &[
+            "--tx-type",
+            "1559",
+            "--max-fee-per-gas",
+            "850",
+            "--max-priority-fee-per-gas",
+            "1",
+        ],
+    );
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("max fee per gas 850 is below the base fee 1000"),
+        "{stderr}"
+    );
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn cost_estimate_prices_the_estimated_gas() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => {
+            // the authorization goes along, so the node can price it
+            assert_eq!(params[0]["authorizationList"].as_array().unwrap().len(), 1);
+            json!({ "result": "0xb3b0" })
+        }
+        _ => devnet(method, params),
+    });
+    let out = run_cli(
+        &node,
+        &[
+            "--max-fee-per-gas",
+            "100",
+            "--max-priority-fee-per-gas",
+            "3",
+            "cost-estimate",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Estimated gas: 46000"), "{stdout}");
+    assert!(
+        stdout.contains("authorizations: 25000 (1 x 25000)"),
+        "{stdout}"
+    );
+    // 46000 * 100 wei on top of the 1 ETH value
+    assert!(
+        stdout.contains("Worst case: 1000000000004600000 wei"),
+        "{stdout}"
+    );
+    // base fee 7 + tip 3
+    assert!(
+        stdout.contains(
+            "Expected:   1000000000000460000 wei (1.00000000000046 ETH) at 10 wei per gas"
+        ),
+        "{stdout}"
+    );
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn era_txs_carry_the_gas_per_pubdata_flag() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &[
+            "--tx-type",
+            "era",
+            "--gas-per-pubdata",
+            "800",
+            "--strategy",
+            "send",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("gas per pubdata 800"));
+    let requests = node.requests.lock().unwrap();
+    let send = requests
+        .iter()
+        .find(|req| req["method"] == "eth_sendRawTransaction")
+        .expect("no tx was sent");
+    assert!(send["params"][0].as_str().unwrap().starts_with("0x71"));
+    drop(requests);
+
+    for args in [
+        &["--tx-type", "era", "--gas-per-pubdata", "0"][..],
+        &["--tx-type", "1559", "--gas-per-pubdata", "800"],
+    ] {
+        let out = run_cli(&node, args);
+        assert!(!out.status.success(), "{args:?}");
+    }
+}
+
+#[test]
+fn predict_address_starts_at_the_accounts_nonce() {
+    let deployer = Address::repeat_byte(0x11);
+    let node = MockNode::builder()
+        .on("eth_getTransactionCount", "0x5")
+        .start();
+    let out = run_cli(
+        &node,
+        &[
+            "predict-address",
+            "--address",
+            &deployer.to_string(),
+            "--count",
+            "2",
+        ],
+    );
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let lines: Vec<_> = stdout.lines().collect();
+    assert_eq!(
+        lines,
+        [
+            format!("Nonce 5: {}", deployer.create(5)),
+            format!("Nonce 6: {}", deployer.create(6)),
+        ]
+    );
+    assert_eq!(node.methods(), ["eth_getTransactionCount"]);
+}
+
+/// A node where another tx took nonce 3 just before ours: the first send
+/// is refused, and by then the pending nonce is 4.
+fn node_with_a_stale_nonce() -> MockNode {
+    let sends = std::sync::atomic::AtomicUsize::new(0);
+    MockNode::with_methods(move |method, params| match method {
+        "eth_getTransactionCount" if params[1] == "pending" && sends.load(SeqCst) > 0 => {
+            json!({ "result": "0x4" })
+        }
+        "eth_sendRawTransaction" if sends.fetch_add(1, SeqCst) == 0 => json!({
+            "error": { "code": -32000, "message": "nonce too low: next nonce 4, tx nonce 3" }
+        }),
+        _ => devnet(method, params),
+    })
+}
+
+fn sent_nonces(node: &MockNode) -> Vec<U256> {
+    node.requests
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|req| req["method"] == "eth_sendRawTransaction")
+        .map(|req| {
+            let raw = hex::decode(&req["params"][0].as_str().unwrap()[2..]).unwrap();
+            decode_signed_tx(&raw).unwrap().nonce()
+        })
+        .collect()
+}
+
+#[test]
+fn auto_nonce_retry_re_signs_with_the_pending_nonce() {
+    let node = node_with_a_stale_nonce();
+    let out = run_cli(&node, &["--tx-type", "1559", "--auto-nonce-retry"]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(
+        stdout.contains("Nonce 3 is too low; re-signing with the pending nonce 4"),
+        "{stdout}"
+    );
+    assert_eq!(sent_nonces(&node), [U256::from(3), U256::from(4)]);
+
+    // without the flag the first answer stands
+    let node = node_with_a_stale_nonce();
+    let out = run_cli(&node, &["--tx-type", "1559"]);
+    assert!(!out.status.success());
+    assert!(String::from_utf8_lossy(&out.stderr).contains("nonce too low"));
+    assert_eq!(sent_nonces(&node), [U256::from(3)]);
+}
+
+#[test]
+fn auto_nonce_retry_only_retries_once() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_sendRawTransaction" => json!({
+            "error": { "code": -32000, "message": "nonce too low" }
+        }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["--tx-type", "1559", "--auto-nonce-retry"]);
+    assert!(!out.status.success());
+    assert_eq!(sent_nonces(&node).len(), 2);
+}
+
+#[test]
+fn invalid_authority_key_in_the_config_is_reported() {
+    let mut config = tempfile::NamedTempFile::new().unwrap();
+    std::io::Write::write_all(&mut config, b"[keys]\nauthority = \"0x1234\"\n").unwrap();
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &[
+            "--config",
+            config.path().to_str().unwrap(),
+            "--tx-type",
+            "7702",
+        ],
+    );
+    // an error exit, not a panic
+    assert_eq!(out.status.code(), Some(1), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("not a valid secp256k1 private key"),
+        "{stderr}"
+    );
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn replay_resends_the_exact_bytes() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702", "--strategy", "print"]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let raw = stdout
+        .lines()
+        .find_map(|line| line.strip_prefix("Raw signed TX: "))
+        .unwrap()
+        .to_string();
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["replay", &raw]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("Warning"));
+    let requests = node.requests.lock().unwrap();
+    let send = requests
+        .iter()
+        .find(|req| req["method"] == "eth_sendRawTransaction")
+        .expect("no tx was sent");
+    assert_eq!(send["params"][0], raw);
+    drop(requests);
+
+    // on another chain it still goes out, with a warning
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_chainId" => json!({ "result": "0x1" }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(&node, &["replay", &raw]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout).contains("signed for chain 1337"));
+}
+
+fn mainnet_node() -> MockNode {
+    MockNode::with_methods(|method, params| match method {
+        "eth_chainId" => json!({ "result": "0x1" }),
+        _ => devnet(method, params),
+    })
+}
+
+#[test]
+fn chain_id_mismatch_aborts_before_signing() {
+    let node = mainnet_node();
+    let out = run_cli(&node, &["--tx-type", "1559"]);
+    assert!(!out.status.success());
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains(
+            "node reports chain id 1 but the tx would be signed for 1337 (use --force to sign anyway)"
+        ),
+        "{stderr}"
+    );
+    assert!(!String::from_utf8_lossy(&out.stdout).contains("Raw signed TX"));
+    assert!(!node
+        .methods()
+        .contains(&"eth_sendRawTransaction".to_string()));
+}
+
+#[test]
+fn force_signs_despite_a_chain_id_mismatch() {
+    let node = mainnet_node();
+    let out = run_cli(&node, &["--tx-type", "1559", "--force"]);
+    assert!(out.status.success(), "{out:?}");
+    assert!(String::from_utf8_lossy(&out.stdout)
+        .contains("Warning: node reports chain id 1 but the tx would be signed for 1337"));
+    // still signed for the configured chain
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.chain_id, 1337);
+}
+
+#[test]
+fn data_is_sent_and_priced_by_auto_gas() {
+    // an estimate below the intrinsic gas is raised to it
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => json!({ "result": "0x5208" }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(
+        &node,
+        &["--tx-type", "1559", "--data", "0xdeadbeef", "--auto-gas"],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip1559(tx) = sent_tx(&node) else {
+        panic!("not a 1559 tx");
+    };
+    assert_eq!(tx.data, [0xde, 0xad, 0xbe, 0xef]);
+    // four nonzero calldata bytes at 16 gas each
+    assert_eq!(tx.gas_limit, U256::from(21_000 + 4 * 16));
+
+    let requests = node.requests.lock().unwrap();
+    let estimate = requests
+        .iter()
+        .find(|req| req["method"] == "eth_estimateGas")
+        .expect("the node wasn't asked");
+    assert_eq!(estimate["params"][0]["data"], "0xdeadbeef");
+}
+
+#[test]
+fn auto_gas_prices_the_delegated_code_of_a_7702_call() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => json!({ "result": "0x15f90" }),
+        _ => devnet(method, params),
+    });
+    let out = run_cli(
+        &node,
+        &["--tx-type", "7702", "--call", "increment()", "--auto-gas"],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let SignedTx::Eip7702(tx) = sent_tx(&node) else {
+        panic!("not a 7702 tx");
+    };
+    // the node's 90000, well over the 46000-odd the calldata and authorization cost
+    assert_eq!(tx.gas_limit, U256::from(90_000));
+
+    let requests = node.requests.lock().unwrap();
+    let estimate = requests
+        .iter()
+        .find(|req| req["method"] == "eth_estimateGas")
+        .expect("the node wasn't asked");
+    assert_eq!(
+        estimate["params"][0]["authorizationList"]
+            .as_array()
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn cost_estimate_honours_json() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_estimateGas" => json!({ "result": "0xb3b0" }),
+        _ => devnet(method, params),
+    });
+    let fees = [
+        "--max-fee-per-gas",
+        "100",
+        "--max-priority-fee-per-gas",
+        "3",
+    ];
+    let out = run_cli(&node, &[&fees[..], &["--json", "cost-estimate"]].concat());
+    assert!(out.status.success(), "{out:?}");
+
+    let report: Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(report["gas"], 46000);
+    assert_eq!(report["authorization_count"], 1);
+    assert_eq!(report["authorization_gas"], 25000);
+    assert_eq!(report["per_authorization_gas"], 25000);
+    // 46000 * 100 wei on top of the 1 ETH value, then at base fee 7 + tip 3
+    assert_eq!(
+        report["worst_case"]
+            .as_str()
+            .map(|hex| U256::from_str_radix(&hex[2..], 16).unwrap()),
+        Some(U256::from(1_000_000_000_004_600_000u64))
+    );
+    assert_eq!(
+        report["expected"]
+            .as_str()
+            .map(|hex| U256::from_str_radix(&hex[2..], 16).unwrap()),
+        Some(U256::from(1_000_000_000_000_460_000u64))
+    );
+
+    let out = run_cli(&node, &[&fees[..], &["cost-estimate"]].concat());
+    assert!(String::from_utf8_lossy(&out.stdout).ends_with("per gas\n"));
+}
+
+#[test]
+fn nonce_lookups_only_log_under_verbose() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "7702"]);
+    assert!(out.status.success(), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(!stderr.contains("Nonce"), "{stderr}");
+
+    let out = run_cli(&node, &["--tx-type", "7702", "--verbose"]);
+    assert!(out.status.success(), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr
+            .lines()
+            .any(|line| line.starts_with("nonce addr=") && line.ends_with("nonce=3")),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn show_logs_lists_events_the_authority_emitted() {
+    // the README authority, which the delegated code runs as
+    let authority = address_from_pkey(&KeyConfig::default().authority).unwrap();
+    let node = MockNode::with_methods(move |method, params| match method {
+        "eth_getLogs" => json!({ "result": [
+            {
+                "address": authority,
+                "topics": [format!("0x{}", "ee".repeat(32))],
+                "data": "0x01",
+                "blockNumber": "0x10",
+                "transactionHash": format!("0x{}", "11".repeat(32)),
+                "logIndex": "0x0",
+            },
+            {
+                "address": authority,
+                "topics": [],
+                "data": "0x02",
+                "blockNumber": "0x10",
+                "transactionHash": format!("0x{}", "22".repeat(32)),
+                "logIndex": "0x1",
+            },
+        ] }),
+        _ => devnet(method, params),
+    });
+    let to = format!("0x{}", "cc".repeat(20));
+    let out = run_cli(&node, &["--tx-type", "7702", "--to", &to, "--show-logs"]);
+    assert!(out.status.success(), "{out:?}");
+
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Logs: 1\n"), "{stdout}");
+    assert!(
+        stdout.contains(&format!("  {authority} data 0x01")),
+        "{stdout}"
+    );
+
+    let requests = node.requests.lock().unwrap();
+    let get_logs = requests
+        .iter()
+        .find(|req| req["method"] == "eth_getLogs")
+        .unwrap();
+    assert!(get_logs["params"][0].get("address").is_none());
+}
+
+#[test]
+fn unknown_tx_type_is_rejected_before_touching_the_node() {
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--tx-type", "foo", "--json"]);
+    assert_eq!(out.status.code(), Some(2), "{out:?}");
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    assert!(
+        stderr.contains("invalid value 'foo' for '--tx-type"),
+        "{stderr}"
+    );
+    assert!(
+        stderr.contains("[possible values: legacy, 1559, 7702, era]"),
+        "{stderr}"
+    );
+    assert!(node.methods().is_empty());
+}
+
+#[test]
+fn every_send_path_reports_the_receipt_the_same_way() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_sendTransaction" => json!({ "result": format!("0x{}", "22".repeat(32)) }),
+        _ => devnet(method, params),
+    });
+    let from = "0x2222222222222222222222222222222222222222";
+    for args in [
+        &["--tx-type", "1559"][..],
+        &["--tx-type", "era"],
+        &["--tx-type", "1559", "--node-sign", "--from", from],
+    ] {
+        let out = run_cli(&node, args);
+        assert!(out.status.success(), "{out:?}");
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains("TX submitted! Hash: 0x"), "{stdout}");
+        assert!(
+            stdout.contains("SUCCESS\nBlock: 16, gas used: 21000\n"),
+            "{stdout}"
+        );
+
+        let out = run_cli(&node, &[args, &["--strategy", "print"]].concat());
+        assert!(out.status.success(), "{out:?}");
+        let stdout = String::from_utf8_lossy(&out.stdout);
+        assert!(stdout.contains("Not sent (--strategy print)"), "{stdout}");
+        assert!(!stdout.contains("SUCCESS"), "{stdout}");
+    }
+}
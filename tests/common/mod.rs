@@ -0,0 +1,208 @@
+//! A throwaway JSON-RPC endpoint for tests.
+
+// each test binary only uses some of this
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpListener,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use std::process::{Command, Output};
+
+use serde_json::{json, Value};
+
+/// Serves HTTP on a local port, answering every POST with `handler(body)`.
+/// Requests are kept so tests can check what was sent.
+pub struct MockNode {
+    pub url: String,
+    pub requests: Arc<Mutex<Vec<Value>>>,
+}
+
+/// An HTTP answer other than a plain 200, for tests of the transport.
+pub struct HttpReply {
+    pub status: u16,
+    pub headers: Vec<(&'static str, String)>,
+    pub body: String,
+}
+
+impl HttpReply {
+    pub fn ok(body: impl Into<String>) -> Self {
+        Self::status(200, body)
+    }
+
+    pub fn status(status: u16, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            headers: vec![("content-type", "application/json".to_string())],
+            body: body.into(),
+        }
+    }
+
+    pub fn header(mut self, name: &'static str, value: impl Into<String>) -> Self {
+        self.headers.push((name, value.into()));
+        self
+    }
+}
+
+impl MockNode {
+    pub fn start(handler: impl Fn(&Value) -> Value + Send + 'static) -> Self {
+        Self::start_http(move |req| Some(HttpReply::ok(handler(req).to_string())))
+    }
+
+    /// Like `start`, but `handler` picks the whole HTTP answer; `None`
+    /// closes the connection without one, like a node that fell over.
+    pub fn start_http(handler: impl Fn(&Value) -> Option<HttpReply> + Send + 'static) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let seen = requests.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let Ok(mut stream) = stream else { return };
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+                let mut content_length = 0;
+                loop {
+                    let mut line = String::new();
+                    if reader.read_line(&mut line).unwrap() == 0 || line == "\r\n" {
+                        break;
+                    }
+                    if let Some((name, value)) = line.split_once(':') {
+                        if name.eq_ignore_ascii_case("content-length") {
+                            content_length = value.trim().parse().unwrap();
+                        }
+                    }
+                }
+                let mut body = vec![0; content_length];
+                reader.read_exact(&mut body).unwrap();
+
+                let request: Value = serde_json::from_slice(&body).unwrap();
+                let reply = handler(&request);
+                seen.lock().unwrap().push(request);
+                let Some(reply) = reply else { continue };
+                let mut head = format!("HTTP/1.1 {} Mock\r\n", reply.status);
+                for (name, value) in &reply.headers {
+                    head.push_str(&format!("{name}: {value}\r\n"));
+                }
+                write!(
+                    stream,
+                    "{head}content-length: {}\r\nconnection: close\r\n\r\n{}",
+                    reply.body.len(),
+                    reply.body
+                )
+                .unwrap();
+            }
+        });
+
+        Self { url, requests }
+    }
+
+    /// Like `start`, but answers each request (batches included) by method.
+    /// `handler` returns the response minus `jsonrpc` and `id`, i.e.
+    /// `{"result": ...}` or `{"error": ...}`.
+    pub fn with_methods(handler: impl Fn(&str, &Value) -> Value + Send + 'static) -> Self {
+        let answer = move |req: &Value| {
+            let mut resp = handler(req["method"].as_str().unwrap(), &req["params"]);
+            resp["jsonrpc"] = json!("2.0");
+            resp["id"] = req["id"].clone();
+            resp
+        };
+        Self::start(move |body| match body {
+            Value::Array(batch) => batch.iter().map(&answer).collect(),
+            req => answer(req),
+        })
+    }
+
+    /// Canned answers by method name, for tests that don't need a whole
+    /// `with_methods` closure.
+    pub fn builder() -> MockNodeBuilder {
+        MockNodeBuilder {
+            methods: HashMap::new(),
+        }
+    }
+
+    /// Every method called so far, with batches flattened.
+    pub fn methods(&self) -> Vec<String> {
+        let requests = self.requests.lock().unwrap();
+        requests
+            .iter()
+            .flat_map(|body| match body {
+                Value::Array(batch) => batch.clone(),
+                req => vec![req.clone()],
+            })
+            .map(|req| req["method"].as_str().unwrap().to_string())
+            .collect()
+    }
+}
+
+type MethodHandler = Box<dyn Fn(&Value) -> Value + Send>;
+
+/// Built by [`MockNode::builder`]; methods it wasn't told about answer
+/// "method not found".
+pub struct MockNodeBuilder {
+    methods: HashMap<String, MethodHandler>,
+}
+
+impl MockNodeBuilder {
+    /// Answers `method` with `result`, whatever the params.
+    pub fn on(self, method: &str, result: impl Into<Value>) -> Self {
+        let result = result.into();
+        self.on_fn(method, move |_| json!({ "result": result }))
+    }
+
+    /// Answers `method` with `handler(params)`: `{"result": ...}` or
+    /// `{"error": ...}`, as for `with_methods`.
+    pub fn on_fn(
+        mut self,
+        method: &str,
+        handler: impl Fn(&Value) -> Value + Send + 'static,
+    ) -> Self {
+        self.methods.insert(method.to_string(), Box::new(handler));
+        self
+    }
+
+    pub fn start(self) -> MockNode {
+        MockNode::with_methods(move |method, params| match self.methods.get(method) {
+            Some(handler) => handler(params),
+            None => json!({ "error": { "code": -32601, "message": "method not found" } }),
+        })
+    }
+}
+
+/// Answers for a chain-1337 node on which every tx is mined straight away.
+pub fn devnet(method: &str, _params: &Value) -> Value {
+    match method {
+        "eth_chainId" => json!({ "result": "0x539" }),
+        "eth_getBlockByNumber" => json!({ "result": {
+            "number": "0x10",
+            "baseFeePerGas": "0x7",
+            "timestamp": "0x6553f100",
+            "gasLimit": "0x1c9c380",
+            "requestsHash": format!("0x{}", "e3".repeat(32)),
+        } }),
+        "eth_getTransactionCount" => json!({ "result": "0x3" }),
+        "eth_sendRawTransaction" => json!({ "result": format!("0x{}", "11".repeat(32)) }),
+        "eth_getTransactionReceipt" => json!({
+            "result": { "blockNumber": "0x10", "gasUsed": "0x5208", "status": "0x1" }
+        }),
+        "eth_blockNumber" => json!({ "result": "0x10" }),
+        "eth_getCode" => json!({ "result": "0x" }),
+        _ => json!({ "error": { "code": -32601, "message": "method not found" } }),
+    }
+}
+
+/// Runs the CLI against `node`.
+pub fn run_cli(node: &MockNode, args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_example_7702"))
+        .args(["--rpc-url", &node.url])
+        .args(args)
+        .env_remove("RPC_URL")
+        .env_remove("PRIVATE_KEY")
+        .output()
+        .unwrap()
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use std::io::Write;
+
+use common::{devnet, run_cli, MockNode};
+use example_7702::{
+    config::{read_env_file, Config, ConfigOverrides},
+    error::TxError,
+};
+
+#[test]
+fn env_file_values_reach_the_config() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    writeln!(
+        file,
+        "# local node\n\
+         export RPC_URL=\"http://10.0.0.1:8545\"\n\
+         PRIVATE_KEY=0xabc # not a real key\n\
+         UNRELATED="
+    )
+    .unwrap();
+
+    let vars = read_env_file(file.path()).unwrap();
+    let config = Config::default().with_env(|key| vars.get(key).cloned());
+
+    assert_eq!(config.rpc_url, "http://10.0.0.1:8545");
+    assert_eq!(config.keys.sender, "0xabc");
+    assert_eq!(config.chain_id, Config::default().chain_id);
+}
+
+const SAMPLE: &str = r#"
+rpc_url = "http://10.0.0.2:8545"
+chain_id = 31337
+gas = 60000
+
+[fees]
+max_fee_per_gas = 3000000000
+# priority fee and gas price keep their defaults
+
+[keys]
+authority = "0x1111111111111111111111111111111111111111111111111111111111111111"
+"#;
+
+#[test]
+fn sample_toml_is_loaded_over_the_defaults() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(SAMPLE.as_bytes()).unwrap();
+    let config = Config::load(file.path()).unwrap();
+
+    assert_eq!(config.rpc_url, "http://10.0.0.2:8545");
+    assert_eq!(config.chain_id, 31337);
+    assert_eq!(config.gas, Some(60000));
+    assert_eq!(config.fees.max_fee_per_gas, 3_000_000_000);
+    let defaults = Config::default();
+    assert_eq!(
+        config.fees.max_priority_fee_per_gas,
+        defaults.fees.max_priority_fee_per_gas
+    );
+    assert_eq!(config.fees.gas_price, defaults.fees.gas_price);
+    assert_eq!(config.keys.authority, format!("0x{}", "11".repeat(32)));
+    assert_eq!(config.keys.sender, defaults.keys.sender);
+}
+
+#[test]
+fn unknown_keys_are_rejected_with_the_path() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"chain_idd = 1\n").unwrap();
+    let err = Config::load(file.path()).unwrap_err();
+    assert!(matches!(&err, TxError::Config(_)));
+    let msg = err.to_string();
+    assert!(msg.contains(&file.path().display().to_string()), "{msg}");
+    assert!(msg.contains("chain_idd"), "{msg}");
+}
+
+#[test]
+fn cli_flags_win_over_the_file() {
+    let config = Config::parse(SAMPLE)
+        .unwrap()
+        .with_overrides(ConfigOverrides {
+            chain_id: Some(1337),
+            max_fee_per_gas: Some(5),
+            ..Default::default()
+        });
+    assert_eq!(config.chain_id, 1337);
+    assert_eq!(config.fees.max_fee_per_gas, 5);
+    // what the flags leave alone stays as the file has it
+    assert_eq!(config.rpc_url, "http://10.0.0.2:8545");
+    assert_eq!(config.gas, Some(60000));
+}
+
+#[test]
+fn cli_run_signs_with_the_file_values_unless_overridden() {
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    file.write_all(b"gas = 60000\n[fees]\nmax_fee_per_gas = 3000000000\n")
+        .unwrap();
+    let path = file.path().to_str().unwrap();
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(&node, &["--config", path, "--tx-type", "1559"]);
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Gas limit: 60000"), "{stdout}");
+
+    let node = MockNode::with_methods(devnet);
+    let out = run_cli(
+        &node,
+        &["--config", path, "--tx-type", "1559", "--gas", "70000"],
+    );
+    assert!(out.status.success(), "{out:?}");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("Gas limit: 70000"), "{stdout}");
+}
@@ -0,0 +1,20 @@
+use alloy::primitives::{hex, keccak256, U256};
+use example_7702::counter::{counter_calldata, CounterMethod};
+
+#[test]
+fn selectors_match_the_hand_computed_ones() {
+    assert_eq!(counter_calldata(CounterMethod::Increment), hex!("d09de08a"));
+    assert_eq!(counter_calldata(CounterMethod::Number), hex!("8381f58a"));
+    assert_eq!(
+        counter_calldata(CounterMethod::SayHello),
+        keccak256("sayHello()")[..4]
+    );
+}
+
+#[test]
+fn arguments_follow_the_selector() {
+    let data = counter_calldata(CounterMethod::SetNumber(U256::from(42)));
+    assert_eq!(data[..4], keccak256("setNumber(uint256)")[..4]);
+    assert_eq!(data.len(), 4 + 32);
+    assert_eq!(U256::from_be_slice(&data[4..]), U256::from(42));
+}
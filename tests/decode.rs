@@ -0,0 +1,226 @@
+use alloy::primitives::{Address, U256};
+use example_7702::{
+    decode::{
+        chain_id_from_v, decode_signed_tx, decode_signed_tx_lenient, split_envelope, SignedTx,
+    },
+    signing::{address_from_key, recover_address, sign_hash, SECP256K1N_HALF},
+    tx::{
+        authorities, Authorization7702, Eip1559Transaction, Eip7702Transaction, LegacyTransaction,
+    },
+};
+use secp256k1::SecretKey;
+
+#[test]
+fn chain_id_from_eip155_v() {
+    assert_eq!(chain_id_from_v(37), Some(1));
+    assert_eq!(chain_id_from_v(38), Some(1));
+    // 2 * 1337 + 35 + 1
+    assert_eq!(chain_id_from_v(2710), Some(1337));
+}
+
+#[test]
+fn pre_eip155_v_has_no_chain_id() {
+    assert_eq!(chain_id_from_v(27), None);
+    assert_eq!(chain_id_from_v(28), None);
+    assert_eq!(chain_id_from_v(0), None);
+}
+
+fn signed_1559() -> Eip1559Transaction {
+    let mut tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    (tx.r, tx.s, tx.y_parity) = sign_hash(&tx.signing_hash(), &key).unwrap();
+    tx
+}
+
+#[test]
+fn high_s_is_rejected_unless_lenient() {
+    let mut tx = signed_1559();
+    let sender = recover_address(&tx.signing_hash(), tx.r, tx.s, tx.y_parity).unwrap();
+    assert!(decode_signed_tx(&tx.rlp_encode_signed()).is_ok());
+
+    // (r, n - s) with the other parity recovers the same sender
+    let n = SECP256K1N_HALF * U256::from(2) + U256::from(1);
+    tx.s = n - tx.s;
+    tx.y_parity ^= 1;
+    let raw = tx.rlp_encode_signed();
+
+    let err = decode_signed_tx(&raw).unwrap_err();
+    assert!(err.to_string().contains("high-s"), "{err}");
+
+    let SignedTx::Eip1559(decoded) = decode_signed_tx_lenient(&raw).unwrap() else {
+        panic!("decoded as another type");
+    };
+    assert_eq!(decoded.s, tx.s);
+    assert_eq!(
+        recover_address(
+            &decoded.signing_hash(),
+            decoded.r,
+            decoded.s,
+            decoded.y_parity
+        )
+        .unwrap(),
+        sender
+    );
+}
+
+#[test]
+fn split_envelope_strips_the_type_byte() {
+    let raw = signed_1559().rlp_encode_signed();
+    let (ty, rlp) = split_envelope(&raw).unwrap();
+    assert_eq!(ty, Some(0x02));
+    assert_eq!(rlp, &raw[1..]);
+
+    let tx = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(100_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let raw = tx.rlp_encode_signed();
+    let (ty, rlp) = split_envelope(&raw).unwrap();
+    assert_eq!(ty, Some(0x04));
+    assert_eq!(rlp, &raw[1..]);
+    assert!(rlp[0] >= 0xc0);
+}
+
+#[test]
+fn split_envelope_returns_legacy_whole() {
+    let tx = LegacyTransaction {
+        nonce: U256::from(3),
+        gas_price: U256::from(1_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![],
+        v: 2710,
+        r: U256::from(1),
+        s: U256::from(1),
+    };
+    let raw = tx.rlp_encode_signed();
+    assert_eq!(split_envelope(&raw).unwrap(), (None, &raw[..]));
+
+    assert!(split_envelope(&[]).is_err());
+    // a string header is neither a tx type nor a list
+    assert!(split_envelope(&[0x80]).is_err());
+}
+
+#[test]
+fn both_authorities_survive_a_round_trip() {
+    let keys = [
+        SecretKey::from_slice(&[0x21; 32]).unwrap(),
+        SecretKey::from_slice(&[0x22; 32]).unwrap(),
+    ];
+    let authorization_list = keys
+        .iter()
+        .zip([Address::repeat_byte(0x01), Address::repeat_byte(0x02)])
+        .map(|(key, delegate)| Authorization7702::sign(1337, delegate, U256::from(5), key).unwrap())
+        .collect();
+    let mut tx = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(100_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list,
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let sender = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    (tx.r, tx.s, tx.y_parity) = sign_hash(&tx.signing_hash(), &sender).unwrap();
+
+    let SignedTx::Eip7702(decoded) = decode_signed_tx(&tx.rlp_encode_signed()).unwrap() else {
+        panic!("not a 7702 tx");
+    };
+    let recovered: Vec<_> = authorities(&decoded)
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        recovered,
+        keys.iter().map(address_from_key).collect::<Vec<_>>()
+    );
+    assert_eq!(
+        decoded.authorization_list[1].address,
+        Address::repeat_byte(0x02)
+    );
+}
+
+/// `[nonce 3, gas price 1, gas 100000, to empty, value 0, data 0x6000]`,
+/// encoded by hand so the test doesn't lean on the encoder under test.
+const CREATION_FIELDS: [u8; 11] = [
+    0x03, 0x01, 0x83, 0x01, 0x86, 0xa0, 0x80, 0x80, 0x82, 0x60, 0x00,
+];
+
+/// Signs `unsigned` and returns `fields || v || r || s` as a raw legacy tx.
+fn raw_legacy_creation(unsigned: &[u8], v: impl Fn(u8) -> u64) -> (Vec<u8>, Address) {
+    use alloy_rlp::{Encodable, Header};
+
+    let key = SecretKey::from_slice(&[0x33; 32]).unwrap();
+    let hash = alloy::primitives::keccak256(unsigned);
+    let (r, s, y_parity) = sign_hash(&hash, &key).unwrap();
+    let mut payload = CREATION_FIELDS.to_vec();
+    v(y_parity).encode(&mut payload);
+    r.encode(&mut payload);
+    s.encode(&mut payload);
+    let mut raw = vec![];
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut raw);
+    raw.extend(payload);
+    (raw, address_from_key(&key))
+}
+
+#[test]
+fn legacy_creation_recovers_the_sender_and_re_encodes_unchanged() {
+    let pre_155 = [&[0xcb][..], &CREATION_FIELDS].concat();
+    // chain id 1337, 0, 0
+    let eip_155 = [
+        &[0xd0][..],
+        &CREATION_FIELDS,
+        &[0x82, 0x05, 0x39, 0x80, 0x80],
+    ]
+    .concat();
+    let signed = [
+        raw_legacy_creation(&pre_155, |y_parity| 27 + y_parity as u64),
+        raw_legacy_creation(&eip_155, |y_parity| 1337 * 2 + 35 + y_parity as u64),
+    ];
+
+    for (raw, sender) in signed {
+        let decoded = decode_signed_tx(&raw).unwrap();
+        let SignedTx::Legacy(tx) = &decoded else {
+            panic!("not a legacy tx");
+        };
+        assert_eq!(tx.to, None);
+        assert_eq!(decoded.sender().unwrap(), sender);
+        assert_eq!(decoded.rlp_encode_signed(), raw);
+    }
+}
@@ -0,0 +1,57 @@
+use alloy::{
+    dyn_abi::DynSolValue,
+    primitives::{address, keccak256, Address, U256},
+};
+use alloy_rlp::{Encodable, Header};
+use example_7702::deploy::{create_address, deploy_calldata, parse_constructor_arg};
+
+#[test]
+fn constructor_args_follow_the_bytecode() {
+    let bytecode = [0x60, 0x80, 0x60, 0x40, 0x52];
+    let args = [
+        parse_constructor_arg("uint256:42").unwrap(),
+        parse_constructor_arg("address:0x00000000000000000000000000000000000000aa").unwrap(),
+    ];
+    let calldata = deploy_calldata(&bytecode, &args);
+
+    assert_eq!(calldata.len(), bytecode.len() + 2 * 32);
+    assert_eq!(&calldata[..5], &bytecode);
+    assert_eq!(&calldata[5..37], &U256::from(42).to_be_bytes::<32>());
+    assert_eq!(calldata[68], 0xaa);
+    assert_eq!(deploy_calldata(&bytecode, &[]), bytecode);
+    assert_eq!(args[0], DynSolValue::Uint(U256::from(42), 256));
+}
+
+#[test]
+fn bad_constructor_args_are_rejected() {
+    for bad in ["42", "uint257:1", "uint8:256", "address:0x12"] {
+        assert!(parse_constructor_arg(bad).is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn create_address_is_derived_from_sender_and_nonce() {
+    let sender = address!("6ac7ea33f8831ea9dcc53393aaa88b25a785dbf0");
+    assert_eq!(
+        create_address(sender, 0),
+        address!("cd234a471b72ba2f1ccf0a70fcaba648a5eecd8d")
+    );
+
+    // keccak256(rlp([sender, nonce]))[12..]
+    for nonce in [1u64, 0x7f, 0x80, 1_000_000] {
+        let payload_length = sender.length() + nonce.length();
+        let mut rlp = Vec::new();
+        Header {
+            list: true,
+            payload_length,
+        }
+        .encode(&mut rlp);
+        sender.encode(&mut rlp);
+        nonce.encode(&mut rlp);
+        assert_eq!(
+            create_address(sender, nonce),
+            Address::from_slice(&keccak256(&rlp)[12..]),
+            "nonce {nonce}"
+        );
+    }
+}
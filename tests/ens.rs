@@ -0,0 +1,86 @@
+mod common;
+
+use alloy::primitives::{address, b256, keccak256, Address, B256};
+use common::MockNode;
+use example_7702::{
+    ens::{namehash, resolve_recipient, EnsResolver, NameResolver, ENS_REGISTRY},
+    error::TxError,
+    rpc::Provider,
+};
+use serde_json::json;
+
+const COUNTER: Address = address!("4F747741EF10551969F9688a8264FC6bb337fA5f");
+
+/// Knows one name.
+struct FixedResolver;
+
+impl NameResolver for FixedResolver {
+    async fn resolve(&self, name: &str) -> Result<Address, TxError> {
+        match name {
+            "counter.eth" => Ok(COUNTER),
+            _ => Err(TxError::Config(format!("unknown name {name}"))),
+        }
+    }
+}
+
+#[tokio::test]
+async fn names_go_through_the_resolver() {
+    assert_eq!(
+        resolve_recipient(&FixedResolver, "counter.eth")
+            .await
+            .unwrap(),
+        COUNTER
+    );
+    assert!(resolve_recipient(&FixedResolver, "nobody.eth")
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn addresses_skip_the_resolver() {
+    let addr = "0x2222222222222222222222222222222222222222";
+    assert_eq!(
+        resolve_recipient(&FixedResolver, addr).await.unwrap(),
+        Address::repeat_byte(0x22)
+    );
+}
+
+#[test]
+fn namehash_matches_eip137() {
+    assert_eq!(namehash(""), B256::ZERO);
+    assert_eq!(
+        namehash("eth"),
+        b256!("93cdeb708b7545dc668eb9280176169d1c33cfd8ed6f04690a0bcc88a93fc4ae")
+    );
+    assert_eq!(
+        namehash("foo.eth"),
+        b256!("de9b09fd7c5f901e23a3f19fecc54828e9c848539801e86591bd9801b019f84f")
+    );
+}
+
+#[tokio::test]
+async fn ens_asks_the_registry_then_the_resolver() {
+    let resolver = Address::repeat_byte(0x77);
+    let node_hash = namehash("counter.eth");
+    let node = MockNode::with_methods(move |method, params| {
+        assert_eq!(method, "eth_call");
+        let to: Address = params[0]["to"].as_str().unwrap().parse().unwrap();
+        let data =
+            hex::decode(params[0]["data"].as_str().unwrap().trim_start_matches("0x")).unwrap();
+        let (selector, node) = data.split_at(4);
+        let answer = match (to, node == node_hash.as_slice()) {
+            (_, false) => Address::ZERO,
+            (ENS_REGISTRY, true) if selector == &keccak256("resolver(bytes32)")[..4] => resolver,
+            (to, true) if to == resolver && selector == &keccak256("addr(bytes32)")[..4] => COUNTER,
+            _ => panic!("unexpected call to {to}"),
+        };
+        json!({ "result": answer.into_word() })
+    });
+    let provider = Provider::new(&node.url);
+
+    let ens = EnsResolver::new(&provider);
+    assert_eq!(ens.resolve("counter.eth").await.unwrap(), COUNTER);
+    // the registry knows no resolver for it
+    let err = ens.resolve("nobody.eth").await.unwrap_err();
+    assert!(err.to_string().contains("no ENS resolver"), "{err}");
+}
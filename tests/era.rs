@@ -0,0 +1,140 @@
+use alloy::{
+    primitives::{address, hex, Address, Bytes, FixedBytes, U256},
+    sol,
+    sol_types::{eip712_domain, SolStruct},
+};
+use example_7702::{
+    era::{
+        check_gas_per_pubdata, EraTransaction, PaymasterParams, DEFAULT_GAS_PER_PUBDATA,
+        MAX_GAS_PER_PUBDATA,
+    },
+    error::TxError,
+};
+
+sol! {
+    struct Transaction {
+        uint256 txType;
+        uint256 from;
+        uint256 to;
+        uint256 gasLimit;
+        uint256 gasPerPubdataByteLimit;
+        uint256 maxFeePerGas;
+        uint256 maxPriorityFeePerGas;
+        uint256 paymaster;
+        uint256 nonce;
+        uint256 value;
+        bytes data;
+        bytes32[] factoryDeps;
+        bytes paymasterInput;
+    }
+}
+
+const PAYMASTER: Address = address!("1111111111111111111111111111111111111111");
+
+fn sample_tx(paymaster_params: Option<PaymasterParams>) -> EraTransaction {
+    EraTransaction {
+        chain_id: 300,
+        nonce: U256::from(4),
+        max_priority_fee_per_gas: U256::ZERO,
+        max_fee_per_gas: U256::from(25_000_000),
+        gas_limit: U256::from(300_000),
+        to: Some(address!("aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")),
+        value: U256::from(1),
+        data: vec![0xde, 0xad],
+        from: address!("19E7E376E7C213B7E7e7e46cc70A5dD086DAff2A"),
+        gas_per_pubdata: U256::from(DEFAULT_GAS_PER_PUBDATA),
+        paymaster_params,
+        y_parity: 1,
+        r: U256::from(5),
+        s: U256::from(6),
+    }
+}
+
+#[test]
+fn general_paymaster_input_is_abi_encoded() {
+    let params = PaymasterParams::general(PAYMASTER, &[0xab, 0xcd]);
+    assert_eq!(
+        hex::encode(&params.input),
+        concat!(
+            "8c5a3445",
+            "0000000000000000000000000000000000000000000000000000000000000020",
+            "0000000000000000000000000000000000000000000000000000000000000002",
+            "abcd000000000000000000000000000000000000000000000000000000000000",
+        )
+    );
+}
+
+#[test]
+fn approval_based_paymaster_input_is_abi_encoded() {
+    let token = address!("2222222222222222222222222222222222222222");
+    let params = PaymasterParams::approval_based(PAYMASTER, token, U256::from(7), &[]);
+    assert_eq!(
+        hex::encode(&params.input),
+        concat!(
+            "949431dc",
+            "0000000000000000000000002222222222222222222222222222222222222222",
+            "0000000000000000000000000000000000000000000000000000000000000007",
+            "0000000000000000000000000000000000000000000000000000000000000060",
+            "0000000000000000000000000000000000000000000000000000000000000000",
+        )
+    );
+}
+
+#[test]
+fn paymaster_params_fill_the_last_slot() {
+    let params = PaymasterParams::general(PAYMASTER, &[]);
+    let with = sample_tx(Some(params.clone())).rlp_encode_signed().unwrap();
+    let without = sample_tx(None).rlp_encode_signed().unwrap();
+
+    assert_eq!(with[0], 0x71);
+    // [paymaster, input]: 0xf8 0x5b, 0x94 || 20 bytes, 0xb8 0x44 || 68 bytes
+    let mut tail = vec![0xf8, 0x5b, 0x94];
+    tail.extend_from_slice(PAYMASTER.as_slice());
+    tail.extend_from_slice(&[0xb8, 0x44]);
+    tail.extend_from_slice(&params.input);
+    assert!(with.ends_with(&tail));
+    assert_eq!(without.last(), Some(&0xc0));
+}
+
+#[test]
+fn signing_hash_matches_eip712() {
+    let params = PaymasterParams::general(PAYMASTER, &[0x01]);
+    let tx = sample_tx(Some(params.clone()));
+
+    let word = |address: Address| U256::from_be_bytes(address.into_word().0);
+    let expected = Transaction {
+        txType: U256::from(0x71),
+        from: word(tx.from),
+        to: word(tx.to.unwrap()),
+        gasLimit: tx.gas_limit,
+        gasPerPubdataByteLimit: tx.gas_per_pubdata,
+        maxFeePerGas: tx.max_fee_per_gas,
+        maxPriorityFeePerGas: tx.max_priority_fee_per_gas,
+        paymaster: word(PAYMASTER),
+        nonce: tx.nonce,
+        value: tx.value,
+        data: Bytes::from(tx.data.clone()),
+        factoryDeps: Vec::<FixedBytes<32>>::new(),
+        paymasterInput: Bytes::from(params.input),
+    }
+    .eip712_signing_hash(&eip712_domain! {
+        name: "zkSync",
+        version: "2",
+        chain_id: 300,
+    });
+
+    assert_eq!(tx.signing_hash(), expected);
+}
+
+#[test]
+fn gas_per_pubdata_is_bounded() {
+    for ok in [1, DEFAULT_GAS_PER_PUBDATA, MAX_GAS_PER_PUBDATA] {
+        check_gas_per_pubdata(ok).unwrap();
+    }
+    for bad in [0, MAX_GAS_PER_PUBDATA + 1] {
+        assert!(matches!(
+            check_gas_per_pubdata(bad),
+            Err(TxError::InvalidFees(_))
+        ));
+    }
+}
@@ -0,0 +1,154 @@
+use alloy::primitives::U256;
+use example_7702::{
+    error::TxError,
+    gas::{
+        apply_bps, base_fee_headroom_warning, check_fees, clamp_fee, creation_intrinsic_gas,
+        effective_gas_price, estimate_cost, next_base_fee_ceiling, total_max_cost, GasMultiplier,
+    },
+};
+
+#[test]
+fn priority_fee_above_max_fee_is_rejected() {
+    let err = check_fees(U256::from(1_000), U256::from(1_001), None).unwrap_err();
+    assert!(matches!(err, TxError::InvalidFees(_)));
+}
+
+#[test]
+fn priority_fee_equal_to_max_fee_is_fine() {
+    check_fees(U256::from(1_000), U256::from(1_000), None).unwrap();
+}
+
+#[test]
+fn max_fee_below_base_fee_is_rejected() {
+    let err = check_fees(U256::from(1_000), U256::from(1), Some(U256::from(1_001))).unwrap_err();
+    assert!(matches!(err, TxError::InvalidFees(_)));
+    check_fees(U256::from(1_000), U256::from(1), Some(U256::from(1_000))).unwrap();
+}
+
+#[test]
+fn next_base_fee_can_rise_by_an_eighth() {
+    assert_eq!(next_base_fee_ceiling(U256::from(800)), U256::from(900));
+    // rounded up
+    assert_eq!(next_base_fee_ceiling(U256::from(7)), U256::from(8));
+}
+
+#[test]
+fn max_fee_without_headroom_warns() {
+    let warning = base_fee_headroom_warning(U256::from(850), U256::from(800)).unwrap();
+    assert_eq!(
+        warning,
+        "max fee per gas 850 is below 900, the most the base fee 800 can rise to by the next block"
+    );
+    assert_eq!(
+        base_fee_headroom_warning(U256::from(900), U256::from(800)),
+        None
+    );
+}
+
+#[test]
+fn creation_pays_for_create_and_initcode_words() {
+    // 21000 + 16 + 16 + 4, 32000 for the CREATE, one word of initcode
+    assert_eq!(creation_intrinsic_gas(&[0x60, 0x80, 0x00]), 53_038);
+    // 33 bytes are two words
+    assert_eq!(
+        creation_intrinsic_gas(&[0; 33]) - creation_intrinsic_gas(&[0; 32]),
+        4 + 2
+    );
+}
+
+#[test]
+fn gas_multiplier_rounds_up() {
+    let multiplier: GasMultiplier = "1.2".parse().unwrap();
+    // 21001 * 1.2 = 25201.2
+    assert_eq!(multiplier.apply(U256::from(21_001)), U256::from(25_202));
+    assert_eq!(multiplier.apply(U256::from(21_000)), U256::from(25_200));
+    assert_eq!(
+        "1".parse::<GasMultiplier>().unwrap().apply(U256::from(7)),
+        U256::from(7)
+    );
+}
+
+#[test]
+fn gas_multiplier_rejects_bad_input() {
+    for bad in ["0.9", "1.2345", "abc", "1.-2", ""] {
+        assert!(bad.parse::<GasMultiplier>().is_err(), "{bad}");
+    }
+}
+
+#[test]
+fn fee_cap_clamps_large_suggestions() {
+    let cap = U256::from(50_000_000_000u64);
+    assert_eq!(clamp_fee(U256::from(900_000_000_000u64), Some(cap)), cap);
+    assert_eq!(clamp_fee(U256::from(1_000), Some(cap)), U256::from(1_000));
+    assert_eq!(clamp_fee(U256::from(1_000), None), U256::from(1_000));
+}
+
+#[test]
+fn total_max_cost_adds_value_to_the_gas_cost() {
+    let cost = total_max_cost(
+        U256::from(21_000),
+        U256::from(2_000_000_000u64),
+        U256::from(1),
+    );
+    assert_eq!(cost, Some(U256::from(42_000_000_000_001u64)));
+}
+
+#[test]
+fn total_max_cost_overflow_is_none() {
+    let half = U256::MAX / U256::from(2);
+    assert_eq!(total_max_cost(U256::from(3), half, U256::ZERO), None);
+    // the product fits, adding the value doesn't
+    assert_eq!(
+        total_max_cost(U256::from(1), U256::MAX, U256::from(1)),
+        None
+    );
+}
+
+#[test]
+fn apply_bps_rounds_a_bump_up() {
+    // 12.5% of 100 is 12.5 wei and of 9 it is 1.125 wei, both rounded up; of 8 it is exact
+    assert_eq!(apply_bps(U256::from(100), 1_250), U256::from(113));
+    assert_eq!(apply_bps(U256::from(9), 1_250), U256::from(11));
+    assert_eq!(apply_bps(U256::from(8), 1_250), U256::from(9));
+    assert_eq!(apply_bps(U256::from(8), 0), U256::from(8));
+    assert_eq!(apply_bps(U256::MAX, 1_250), U256::MAX);
+
+    // 2^255 - 1 plus an eighth of it still fits, so it isn't saturated
+    let half = U256::MAX >> 1;
+    assert_eq!(apply_bps(half, 1_250), half + (U256::from(1) << 252));
+    assert_eq!(apply_bps(half, 10_000), U256::MAX - U256::from(1));
+}
+
+#[test]
+fn effective_gas_price_is_base_fee_plus_tip() {
+    assert_eq!(
+        effective_gas_price(U256::from(100), U256::from(200), U256::from(5)),
+        U256::from(105)
+    );
+}
+
+#[test]
+fn effective_gas_price_is_capped_at_the_max_fee() {
+    // the base fee rose after signing, squeezing the tip
+    assert_eq!(
+        effective_gas_price(U256::from(198), U256::from(200), U256::from(5)),
+        U256::from(200)
+    );
+    assert_eq!(
+        effective_gas_price(U256::MAX, U256::from(200), U256::from(5)),
+        U256::from(200)
+    );
+}
+
+#[test]
+fn estimate_cost_breaks_out_the_authorizations() {
+    let cost = estimate_cost(100_000, 2, U256::from(5), U256::from(30), U256::from(12));
+    assert_eq!(cost.authorization_gas, 50_000);
+    assert_eq!(cost.worst_case, Some(U256::from(3_000_005)));
+    assert_eq!(cost.expected, Some(U256::from(1_200_005)));
+
+    // the worst case overflowing doesn't hide the expected cost
+    let cost = estimate_cost(2, 0, U256::ZERO, U256::MAX, U256::from(1));
+    assert_eq!(cost.worst_case, None);
+    assert_eq!(cost.expected, Some(U256::from(2)));
+}
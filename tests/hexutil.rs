@@ -0,0 +1,114 @@
+use alloy::primitives::{address, Address, U256};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use example_7702::{
+    hexutil::{parse_address, parse_hex, OutputFormat},
+    signing::sign_hash,
+    tx::Eip1559Transaction,
+};
+use secp256k1::SecretKey;
+
+#[test]
+fn prefix_is_optional() {
+    assert_eq!(parse_hex("0xdeadBEEF").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+    assert_eq!(parse_hex("deadbeef").unwrap(), [0xde, 0xad, 0xbe, 0xef]);
+    assert!(parse_hex("0x").unwrap().is_empty());
+    assert!(parse_hex("").unwrap().is_empty());
+}
+
+#[test]
+fn odd_length_is_rejected() {
+    let err = parse_hex("0xabc").unwrap_err().to_string();
+    assert_eq!(err, "invalid hex: odd number of digits (3)");
+}
+
+#[test]
+fn non_hex_is_rejected_without_echoing_it() {
+    let err = parse_hex("0x12zz").unwrap_err().to_string();
+    assert_eq!(err, "invalid hex: character 3 is not a hex digit");
+    // only one prefix is stripped
+    assert!(parse_hex("0x0xab").is_err());
+}
+
+#[test]
+fn lowercase_addresses_parse_with_or_without_prefix() {
+    let counter = address!("4F747741EF10551969F9688a8264FC6bb337fA5f");
+    assert_eq!(
+        parse_address("0x4f747741ef10551969f9688a8264fc6bb337fa5f").unwrap(),
+        counter
+    );
+    assert_eq!(
+        parse_address("4f747741ef10551969f9688a8264fc6bb337fa5f").unwrap(),
+        counter
+    );
+    assert!(parse_address("0x4f747741ef10551969f9688a8264fc6bb337fa").is_err());
+}
+
+#[test]
+fn valid_checksum_is_accepted() {
+    assert_eq!(
+        parse_address("0x4F747741EF10551969F9688a8264FC6bb337fA5f").unwrap(),
+        address!("4F747741EF10551969F9688a8264FC6bb337fA5f")
+    );
+}
+
+#[test]
+fn bad_checksum_is_rejected() {
+    // `fA` swapped to `Fa`
+    let err = parse_address("0x4F747741EF10551969F9688a8264FC6bb337Fa5f")
+        .unwrap_err()
+        .to_string();
+    assert_eq!(
+        err,
+        "invalid address: bad EIP-55 checksum in 0x4F747741EF10551969F9688a8264FC6bb337Fa5f"
+    );
+}
+
+fn signed_1559_raw() -> Vec<u8> {
+    let mut tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    (tx.r, tx.s, tx.y_parity) = sign_hash(&tx.signing_hash(), &key).unwrap();
+    tx.rlp_encode_signed()
+}
+
+#[test]
+fn hex_output_is_prefixed() {
+    let raw = signed_1559_raw();
+    let out = OutputFormat::Hex.render(&raw);
+    assert!(out.starts_with("0x02f8"));
+    assert_eq!(parse_hex(&out).unwrap(), raw);
+}
+
+#[test]
+fn hex_no_prefix_output_drops_0x() {
+    let raw = signed_1559_raw();
+    let out = OutputFormat::HexNoPrefix.render(&raw);
+    assert!(out.starts_with("02f8"));
+    assert_eq!(out, OutputFormat::Hex.render(&raw)[2..]);
+}
+
+#[test]
+fn base64_output_round_trips() {
+    let raw = signed_1559_raw();
+    let out = OutputFormat::Base64.render(&raw);
+    // 0x02 0xf8 .. encodes as `Avg`/`Avh`..
+    assert!(out.starts_with("Av"));
+    assert_eq!(STANDARD.decode(&out).unwrap(), raw);
+}
+
+#[test]
+fn default_format_is_hex() {
+    assert_eq!(OutputFormat::default(), OutputFormat::Hex);
+}
@@ -0,0 +1,41 @@
+use example_7702::{error::TxError, keys::parse_private_key, signing::address_from_key};
+
+const KEY: &str = "0fad2ca996a24d116097c481c27a59652a3d3611dfed64d8f9bf86568b1f431d";
+
+#[test]
+fn prefix_and_surrounding_whitespace_are_optional() {
+    let bare = parse_private_key(KEY).unwrap();
+    let prefixed = parse_private_key(&format!("0x{KEY}")).unwrap();
+    let pasted = parse_private_key(&format!("  0x{KEY}\n")).unwrap();
+    assert_eq!(bare, prefixed);
+    assert_eq!(bare, pasted);
+    assert_eq!(
+        address_from_key(&bare).to_string(),
+        "0x0A265d1d68fD54B09D434de7846d8E631668d99B"
+    );
+}
+
+#[test]
+fn wrong_length_is_rejected() {
+    for key in [&KEY[..62], &format!("{KEY}00"), ""] {
+        let err = parse_private_key(key).unwrap_err();
+        assert!(matches!(err, TxError::InvalidKey(_)), "{err}");
+    }
+}
+
+#[test]
+fn non_hex_is_rejected_without_echoing_it() {
+    let key = format!("{}zz", &KEY[..62]);
+    let err = parse_private_key(&key).unwrap_err();
+    assert!(matches!(err, TxError::InvalidKey(_)));
+    assert_eq!(err.to_string(), "invalid private key: not valid hex");
+}
+
+#[test]
+fn zero_key_is_rejected() {
+    let err = parse_private_key(&"00".repeat(32)).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid private key: not a valid secp256k1 private key"
+    );
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "kms")]
+
+use alloy::primitives::{keccak256, B256};
+use example_7702::{
+    error::TxError,
+    kms::{KmsClient, KmsSigner},
+    signing::{address_from_key, recover_address, Signer},
+};
+use secp256k1::{Message, Secp256k1, SecretKey};
+
+const SPKI_PREFIX: &str = "3056301006072a8648ce3d020106052b8104000a034200";
+
+/// Stands in for KMS with a local key, answering in the same DER formats.
+struct MockKms {
+    key: SecretKey,
+    /// Return the high-s twin of the signature, which KMS is free to do.
+    high_s: bool,
+}
+
+impl KmsClient for MockKms {
+    async fn get_public_key(&self, _key_id: &str) -> Result<Vec<u8>, TxError> {
+        let point = self
+            .key
+            .public_key(&Secp256k1::new())
+            .serialize_uncompressed();
+        Ok([hex::decode(SPKI_PREFIX).unwrap(), point.to_vec()].concat())
+    }
+
+    async fn sign_digest(&self, _key_id: &str, digest: &B256) -> Result<Vec<u8>, TxError> {
+        let msg = Message::from_digest(digest.0);
+        let signature = Secp256k1::new().sign_ecdsa(&msg, &self.key);
+        if !self.high_s {
+            return Ok(signature.serialize_der().to_vec());
+        }
+        // s' = n - s verifies just as well
+        let n = secp256k1::constants::CURVE_ORDER;
+        let compact = signature.serialize_compact();
+        let mut s = [0u8; 32];
+        let mut borrow = 0i16;
+        for i in (0..32).rev() {
+            let diff = n[i] as i16 - compact[32 + i] as i16 - borrow;
+            s[i] = diff.rem_euclid(256) as u8;
+            borrow = (diff < 0) as i16;
+        }
+        let mut high = [0u8; 64];
+        high[..32].copy_from_slice(&compact[..32]);
+        high[32..].copy_from_slice(&s);
+        Ok(secp256k1::ecdsa::Signature::from_compact(&high)
+            .unwrap()
+            .serialize_der()
+            .to_vec())
+    }
+}
+
+async fn check_signer(high_s: bool) {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let signer = KmsSigner::new(MockKms { key, high_s }, "alias/test")
+        .await
+        .unwrap();
+    assert_eq!(signer.address(), address_from_key(&key));
+
+    let hash = keccak256(b"some tx");
+    let (r, s, y_parity) = signer.sign_hash(&hash).await.unwrap();
+    assert_eq!(
+        recover_address(&hash, r, s, y_parity).unwrap(),
+        address_from_key(&key)
+    );
+}
+
+#[tokio::test]
+async fn kms_der_signature_recovers_to_the_key_address() {
+    check_signer(false).await;
+}
+
+#[tokio::test]
+async fn kms_high_s_signature_is_normalized() {
+    check_signer(true).await;
+}
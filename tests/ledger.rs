@@ -0,0 +1,142 @@
+#![cfg(feature = "ledger")]
+
+use std::sync::Mutex;
+
+use alloy::primitives::{address, Address, U256};
+use example_7702::{
+    error::TxError,
+    ledger::{
+        get_address_apdu, parse_path, sign_transaction_apdus, LedgerSigner, LedgerTransport,
+        DEFAULT_PATH,
+    },
+    signing::Signer,
+    tx::Eip1559Transaction,
+};
+
+const PATH_DATA: &str = "058000002c8000003c800000000000000000000000";
+const PUBKEY: &str = "044f355bdcb7cc0af728ef3cceb9615d90684bb5b2ca5f859ab0f0b704075871aa385b6b1b8ead809ca67454d9683fcf2ba03456d6fe2c4abe2b07f0fbdbb2f1c1";
+/// "19e7e376e7c213b7e7e7e46cc70a5dd086daff2a" in ASCII
+const ADDRESS_ASCII: &str =
+    "31396537653337366537633231336237653765376534366363373061356464303836646166663261";
+const ADDRESS: Address = address!("19E7E376E7C213B7E7e7e46cc70A5dD086DAff2A");
+const UNSIGNED_1559: &str = "02df0180010282520894aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa0180c0";
+const R: &str = "e339f91614e27d263af45c5666bd8ee6c4c57071029873b9d8327c5bb7d590bb";
+const S: &str = "59ecc34f33028e11bfc8079bc75719dc89e3ffe1ce02f3ef8daa81de8663b1c6";
+
+/// Replays request/response pairs in order, failing on any other request.
+struct Replay(Mutex<Vec<(String, String)>>);
+
+impl Replay {
+    fn new(exchanges: &[(String, String)]) -> Self {
+        Self(Mutex::new(exchanges.iter().rev().cloned().collect()))
+    }
+}
+
+impl LedgerTransport for Replay {
+    async fn exchange(&self, apdu: &[u8]) -> Result<Vec<u8>, TxError> {
+        let (request, response) = self.0.lock().unwrap().pop().expect("unexpected APDU");
+        assert_eq!(hex::encode(apdu), request);
+        Ok(hex::decode(response).unwrap())
+    }
+}
+
+fn get_address_exchange() -> (String, String) {
+    (
+        format!("e0020000{:02x}{PATH_DATA}", PATH_DATA.len() / 2),
+        format!("41{PUBKEY}28{ADDRESS_ASCII}9000"),
+    )
+}
+
+#[test]
+fn default_path_is_hardened_where_expected() {
+    assert_eq!(
+        parse_path(DEFAULT_PATH).unwrap(),
+        [0x8000_002c, 0x8000_003c, 0x8000_0000, 0, 0]
+    );
+    assert!(parse_path("44'/60'").is_err());
+    assert!(parse_path("m/2147483648").is_err());
+}
+
+#[test]
+fn get_address_apdu_matches_the_app_format() {
+    let path = parse_path(DEFAULT_PATH).unwrap();
+    assert_eq!(
+        hex::encode(get_address_apdu(&path)),
+        get_address_exchange().0
+    );
+}
+
+#[test]
+fn long_transactions_are_split_into_chunks() {
+    let path = parse_path(DEFAULT_PATH).unwrap();
+    let apdus = sign_transaction_apdus(&path, &[0xab; 600]);
+
+    // 21 path bytes + 600 tx bytes = 255 + 255 + 111
+    let headers: Vec<_> = apdus.iter().map(|apdu| apdu[..5].to_vec()).collect();
+    assert_eq!(
+        headers,
+        [
+            vec![0xe0, 0x04, 0x00, 0x00, 0xff],
+            vec![0xe0, 0x04, 0x80, 0x00, 0xff],
+            vec![0xe0, 0x04, 0x80, 0x00, 111],
+        ]
+    );
+    assert_eq!(hex::encode(&apdus[0][5..26]), PATH_DATA);
+}
+
+#[tokio::test]
+async fn signs_a_1559_tx_from_recorded_exchanges() {
+    let unsigned = hex::decode(UNSIGNED_1559).unwrap();
+    let transport = Replay::new(&[
+        get_address_exchange(),
+        (
+            format!(
+                "e0040000{:02x}{PATH_DATA}{UNSIGNED_1559}",
+                PATH_DATA.len() / 2 + unsigned.len()
+            ),
+            format!("01{R}{S}9000"),
+        ),
+    ]);
+
+    let signer = LedgerSigner::new(transport, DEFAULT_PATH).await.unwrap();
+    assert_eq!(signer.address(), ADDRESS);
+
+    let (r, s, y_parity) = signer.sign_transaction(&unsigned).await.unwrap();
+    assert_eq!(r, U256::from_str_radix(R, 16).unwrap());
+    assert_eq!(s, U256::from_str_radix(S, 16).unwrap());
+    assert_eq!(y_parity, 1);
+
+    // the tx above, as built by this crate
+    let tx = Eip1559Transaction {
+        chain_id: 1,
+        nonce: U256::ZERO,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    assert_eq!(tx.rlp_encode_unsigned(), unsigned);
+}
+
+#[tokio::test]
+async fn rejection_on_the_device_is_reported() {
+    let transport = Replay::new(&[
+        get_address_exchange(),
+        (
+            format!("e0040000{:02x}{PATH_DATA}{UNSIGNED_1559}", 21 + 33),
+            "6985".to_string(),
+        ),
+    ]);
+    let signer = LedgerSigner::new(transport, DEFAULT_PATH).await.unwrap();
+    let err = signer
+        .sign_transaction(&hex::decode(UNSIGNED_1559).unwrap())
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("rejected"));
+}
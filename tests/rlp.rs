@@ -0,0 +1,417 @@
+use alloy::primitives::{hex, Address, U256};
+use alloy_rlp::Header;
+use example_7702::{
+    decode::{decode_signed_tx, SignedTx},
+    era::EraTransaction,
+    tx::{
+        normalize_access_list, Authorization7702, Eip1559Transaction, Eip7702Transaction,
+        LegacyTransaction,
+    },
+};
+
+fn tx_with_integers(value: u64) -> Eip1559Transaction {
+    Eip1559Transaction {
+        chain_id: value,
+        nonce: U256::from(value),
+        max_priority_fee_per_gas: U256::from(value),
+        max_fee_per_gas: U256::from(value),
+        gas_limit: U256::from(value),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(value),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::from(value),
+        s: U256::from(value),
+    }
+}
+
+/// The raw RLP items of a tx's outer list, after the type byte if it has
+/// one.
+fn items(raw: &[u8]) -> Vec<&[u8]> {
+    let mut buf = if raw[0] >= 0xc0 { raw } else { &raw[1..] };
+    let header = Header::decode(&mut buf).unwrap();
+    assert!(header.list);
+    let mut items = Vec::new();
+    while !buf.is_empty() {
+        let start = buf;
+        // for a single byte below 0x80 this reads nothing and reports length 1
+        let item = Header::decode(&mut buf).unwrap();
+        buf = &buf[item.payload_length..];
+        items.push(&start[..start.len() - buf.len()]);
+    }
+    items
+}
+
+#[test]
+fn integers_are_minimally_encoded() {
+    let cases: [(u64, &[u8]); 5] = [
+        (0, &[0x80]),
+        (127, &[0x7f]),
+        (128, &[0x81, 0x80]),
+        (255, &[0x81, 0xff]),
+        (256, &[0x82, 0x01, 0x00]),
+    ];
+    for (value, expected) in cases {
+        let raw = tx_with_integers(value).rlp_encode_signed();
+        let items = items(&raw);
+        // chain id, nonce, the two fees, gas limit, then value and r, s
+        for index in [0, 1, 2, 3, 4, 6, 10, 11] {
+            assert_eq!(items[index], expected, "value {value}, field {index}");
+        }
+
+        let SignedTx::Eip1559(decoded) = decode_signed_tx(&raw).unwrap() else {
+            panic!("decoded as another type");
+        };
+        assert_eq!(decoded.nonce, U256::from(value));
+        assert_eq!(decoded.chain_id, value);
+    }
+}
+
+#[test]
+fn decoder_rejects_leading_zeros() {
+    let raw = tx_with_integers(1).rlp_encode_signed();
+    // swap the single-byte nonce 0x01 for the padded string 0x82 0x00 0x01
+    let items = items(&raw);
+    let mut payload = Vec::new();
+    for (index, item) in items.iter().enumerate() {
+        match index {
+            1 => payload.extend_from_slice(&[0x82, 0x00, 0x01]),
+            _ => payload.extend_from_slice(item),
+        }
+    }
+    let mut padded = vec![0x02];
+    Header {
+        list: true,
+        payload_length: payload.len(),
+    }
+    .encode(&mut padded);
+    padded.extend(payload);
+
+    assert!(decode_signed_tx(&padded).is_err());
+}
+
+#[test]
+fn access_list_encodes_like_alloy() {
+    use alloy::{
+        eips::eip2930::{AccessList, AccessListItem},
+        primitives::B256,
+    };
+    use alloy_rlp::Encodable;
+
+    let mut tx = tx_with_integers(1);
+    tx.access_list = vec![
+        (
+            Address::repeat_byte(0x11),
+            vec![U256::ZERO, U256::from(0x1234)],
+        ),
+        (Address::repeat_byte(0x22), vec![]),
+    ];
+    let raw = tx.rlp_encode_signed();
+
+    let expected = AccessList(
+        tx.access_list
+            .iter()
+            .map(|(address, keys)| AccessListItem {
+                address: *address,
+                storage_keys: keys.iter().map(|key| B256::from(*key)).collect(),
+            })
+            .collect(),
+    );
+    let mut expected_rlp = Vec::new();
+    expected.encode(&mut expected_rlp);
+    assert_eq!(items(&raw)[8], expected_rlp.as_slice());
+
+    let SignedTx::Eip1559(decoded) = decode_signed_tx(&raw).unwrap() else {
+        panic!("decoded as another type");
+    };
+    assert_eq!(decoded.access_list, tx.access_list);
+}
+
+#[test]
+fn normalized_access_list_is_sorted_and_deduplicated() {
+    let (a, b) = (Address::repeat_byte(0x0a), Address::repeat_byte(0x0b));
+    let list = normalize_access_list(vec![
+        (b, vec![U256::from(2), U256::from(1)]),
+        (a, vec![U256::from(5)]),
+        (b, vec![U256::from(1), U256::from(3)]),
+    ]);
+    assert_eq!(
+        list,
+        [
+            (a, vec![U256::from(5)]),
+            (b, vec![U256::from(1), U256::from(2), U256::from(3)]),
+        ]
+    );
+}
+
+#[test]
+fn reused_access_list_keeps_its_bytes() {
+    let mut original = tx_with_integers(1);
+    // deliberately unsorted: a reused list goes out exactly as it came in
+    original.access_list = vec![
+        (
+            Address::repeat_byte(0x22),
+            vec![U256::from(7), U256::from(1)],
+        ),
+        (Address::repeat_byte(0x11), vec![]),
+    ];
+    let original_raw = original.rlp_encode_signed();
+
+    let decoded = decode_signed_tx(&original_raw).unwrap();
+    let mut replacement = tx_with_integers(2);
+    replacement.access_list = decoded.access_list().to_vec();
+    let replacement_raw = replacement.rlp_encode_signed();
+
+    assert_eq!(items(&replacement_raw)[8], items(&original_raw)[8]);
+    assert_ne!(replacement_raw, original_raw);
+}
+
+#[test]
+fn data_is_a_byte_string_in_every_tx_type() {
+    // short strings get an 0x80-0xb7 header, long ones 0xb8-0xbf
+    for (data, header) in [(vec![0xab; 4], 0x84), (vec![0xab; 60], 0xb8)] {
+        let legacy = LegacyTransaction {
+            nonce: U256::from(1),
+            gas_price: U256::from(1),
+            gas_limit: U256::from(1),
+            to: Some(Address::repeat_byte(0xaa)),
+            value: U256::ZERO,
+            data: data.clone(),
+            v: 37,
+            r: U256::from(1),
+            s: U256::from(1),
+        };
+        let eip1559 = Eip1559Transaction {
+            data: data.clone(),
+            ..tx_with_integers(1)
+        };
+        let eip7702 = Eip7702Transaction {
+            chain_id: 1,
+            nonce: U256::from(1),
+            max_priority_fee_per_gas: U256::from(1),
+            max_fee_per_gas: U256::from(1),
+            gas_limit: U256::from(1),
+            to: Some(Address::repeat_byte(0xaa)),
+            value: U256::ZERO,
+            data: data.clone(),
+            access_list: vec![],
+            authorization_list: vec![],
+            y_parity: 0,
+            r: U256::from(1),
+            s: U256::from(1),
+        };
+        let era = EraTransaction {
+            chain_id: 1,
+            nonce: U256::from(1),
+            max_priority_fee_per_gas: U256::from(1),
+            max_fee_per_gas: U256::from(1),
+            gas_limit: U256::from(1),
+            to: Some(Address::repeat_byte(0xaa)),
+            value: U256::ZERO,
+            data: data.clone(),
+            from: Address::repeat_byte(0xbb),
+            gas_per_pubdata: U256::from(1),
+            paymaster_params: None,
+            y_parity: 0,
+            r: U256::from(1),
+            s: U256::from(1),
+        };
+
+        // (encoding, index of the data item)
+        let encodings = [
+            (legacy.rlp_encode_unsigned(1), 5),
+            (legacy.rlp_encode_signed(), 5),
+            (eip1559.rlp_encode_unsigned(), 7),
+            (eip1559.rlp_encode_signed(), 7),
+            (eip7702.rlp_encode_unsigned(), 7),
+            (eip7702.rlp_encode_signed(), 7),
+            (era.rlp_encode_signed().unwrap(), 6),
+        ];
+        for (raw, index) in encodings {
+            let item = items(&raw)[index];
+            assert_eq!(item[0], header, "{}", hex::encode(&raw));
+            assert!(item.ends_with(&data));
+        }
+    }
+}
+
+#[test]
+fn gas_per_pubdata_follows_from_in_era_txs() {
+    let era = EraTransaction {
+        chain_id: 300,
+        nonce: U256::from(4),
+        max_priority_fee_per_gas: U256::ZERO,
+        max_fee_per_gas: U256::from(25_000_000),
+        gas_limit: U256::from(300_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![],
+        from: Address::repeat_byte(0xbb),
+        gas_per_pubdata: U256::from(800),
+        paymaster_params: None,
+        y_parity: 1,
+        r: U256::from(5),
+        s: U256::from(6),
+    };
+    let raw = era.rlp_encode_signed().unwrap();
+    let items = items(&raw);
+
+    // the ten Ethereum fields, then chain id and from
+    assert_eq!(items[10], [0x82, 0x01, 0x2c]);
+    let mut from = vec![0x94];
+    from.extend_from_slice(Address::repeat_byte(0xbb).as_slice());
+    assert_eq!(items[11], from);
+    assert_eq!(items[12], [0x82, 0x03, 0x20]);
+}
+
+/// An unsigned legacy tx for chain 1 whose list payload is `payload_length`
+/// bytes: eight one-byte fields, plus `data` and its string header.
+fn legacy_with_payload(payload_length: usize) -> Vec<u8> {
+    let data_length = match payload_length - 8 {
+        n @ ..=56 => n - 1,
+        n => n - 2,
+    };
+    let tx = LegacyTransaction {
+        nonce: U256::ZERO,
+        gas_price: U256::ZERO,
+        gas_limit: U256::ZERO,
+        to: None,
+        value: U256::ZERO,
+        data: vec![0xaa; data_length],
+        v: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let encoded = tx.rlp_encode_unsigned(1);
+    let header = Header::decode(&mut &encoded[..]).unwrap();
+    assert_eq!(header.payload_length, payload_length);
+    encoded
+}
+
+#[test]
+fn list_header_switches_to_long_form_after_55_bytes() {
+    let short = legacy_with_payload(55);
+    assert_eq!(short[0], 0xc0 + 55);
+    assert_eq!(short.len(), 1 + 55);
+
+    // 0xf8, then the length in one byte
+    let long = legacy_with_payload(56);
+    assert_eq!(long[..2], [0xf8, 56]);
+    assert_eq!(long.len(), 2 + 56);
+
+    // and in two bytes past 255
+    let longer = legacy_with_payload(257);
+    assert_eq!(longer[..3], [0xf9, 0x01, 0x01]);
+    assert_eq!(longer.len(), 3 + 257);
+}
+
+#[test]
+fn legacy_encodes_to_the_eip155_example_bytes() {
+    // the example from EIP-155
+    let mut tx = LegacyTransaction {
+        nonce: U256::from(9),
+        gas_price: U256::from(20_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(Address::repeat_byte(0x35)),
+        value: U256::from(1_000_000_000_000_000_000u64),
+        data: vec![],
+        v: 1,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    assert_eq!(
+        tx.rlp_encode_unsigned(1),
+        hex!("ec098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a764000080018080")
+    );
+
+    tx.v = 37;
+    tx.r = "18515461264373351373200002665853028612451056578545711640558177340181847433846"
+        .parse()
+        .unwrap();
+    tx.s = "46948507304638947509940763649030358759909902576025900602547168820602576006531"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        tx.rlp_encode_signed(),
+        hex!(
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a7640000"
+            "8025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f"
+            "761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        )
+    );
+}
+
+#[test]
+fn eip1559_encodes_to_fixed_bytes() {
+    let tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        gas_limit: U256::from(21_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(1),
+        data: vec![0xde, 0xad],
+        access_list: vec![(Address::repeat_byte(0x11), vec![U256::from(1)])],
+        y_parity: 1,
+        r: U256::from(0x1234),
+        s: U256::from(0x5678),
+    };
+    assert_eq!(
+        tx.rlp_encode_unsigned(),
+        hex!(
+            "02f86482053903843b9aca00847735940082520894aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa01"
+            "82deadf838f7941111111111111111111111111111111111111111e1a000000000000000000000000000"
+            "00000000000000000000000000000000000001"
+        )
+    );
+    assert_eq!(
+        tx.rlp_encode_signed(),
+        hex!(
+            "02f86b82053903843b9aca00847735940082520894aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa01"
+            "82deadf838f7941111111111111111111111111111111111111111e1a000000000000000000000000000"
+            "0000000000000000000000000000000000000101821234825678"
+        )
+    );
+}
+
+#[test]
+fn eip7702_encodes_to_fixed_bytes() {
+    let tx = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        gas_limit: U256::from(100_000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![Authorization7702 {
+            chain_id: 0,
+            address: Address::repeat_byte(0x22),
+            nonce: U256::from(7),
+            y_parity: 0,
+            r: U256::from(0xabcd),
+            s: U256::from(0xef01),
+        }],
+        y_parity: 0,
+        r: U256::from(0x1234),
+        s: U256::from(0x5678),
+    };
+    assert_eq!(
+        tx.rlp_encode_unsigned(),
+        hex!(
+            "04f84a82053903843b9aca008477359400830186a094aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            "8080c0dfde80942222222222222222222222222222222222222222078082abcd82ef01"
+        )
+    );
+    assert_eq!(
+        tx.rlp_encode_signed(),
+        hex!(
+            "04f85182053903843b9aca008477359400830186a094aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa"
+            "8080c0dfde80942222222222222222222222222222222222222222078082abcd82ef0180821234825678"
+        )
+    );
+}
@@ -0,0 +1,1235 @@
+mod common;
+
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+use alloy::primitives::{address, b256, keccak256, Address, B256, U256};
+use common::{devnet, HttpReply, MockNode};
+use example_7702::{
+    decode::SignedTx,
+    error::TxError,
+    rpc::{
+        block_number, build_authorizations, call_as_delegated, code_kind, eth_call_batch,
+        fetch_chain_id, get_block, get_logs, get_nonce, get_nonces, get_storage_at,
+        get_transaction, max_priority_fee, oracle_fees, resolve_nonce, send_raw, send_transaction,
+        simulate_bundle, wait_for_delegation, wait_for_receipt, BlockTag, FeeHistory, FeeStrategy,
+        JsonRpcError, JsonRpcResponse, Log, LogFilter, NonceStrategy, Provider, Receipt,
+        RpcErrorKind, SimResult, TipSource, TransactionRequest, TxStatus,
+    },
+    signing::{address_from_key, finalize_signature},
+    tx::{Authorization7702, CodeKind, Eip1559Transaction, Eip7702Transaction},
+};
+use secp256k1::SecretKey;
+use serde_json::{json, Value};
+
+const COUNTER: Address = address!("4F747741EF10551969F9688a8264FC6bb337fA5f");
+
+#[tokio::test]
+async fn eth_call_batch_keeps_order_and_per_call_errors() {
+    // answer out of order: the second call reverts, the first returns 42
+    let node = MockNode::start(|batch| {
+        let ids: Vec<&Value> = batch
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|req| &req["id"])
+            .collect();
+        json!([
+            { "jsonrpc": "2.0", "id": ids[1], "error": { "code": 3, "message": "execution reverted" } },
+            { "jsonrpc": "2.0", "id": ids[0], "result": format!("0x{:064x}", 42) },
+        ])
+    });
+    let provider = Provider::new(&node.url);
+
+    let results = eth_call_batch(
+        &provider,
+        vec![
+            (COUNTER, vec![0x06, 0x66, 0x1a, 0xbd]),
+            (COUNTER, vec![0xd0, 0x9d, 0xe0, 0x8a]),
+        ],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(results.len(), 2);
+    let first = results[0].as_ref().unwrap();
+    assert_eq!(first.len(), 32);
+    assert_eq!(first[31], 42);
+    assert!(results[1]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains("execution reverted"));
+
+    let requests = node.requests.lock().unwrap();
+    let batch = requests[0].as_array().unwrap();
+    assert_eq!(batch[0]["method"], "eth_call");
+    assert_eq!(batch[0]["params"][0]["data"], "0x06661abd");
+    assert_eq!(batch[1]["params"][1], "latest");
+}
+
+#[tokio::test]
+async fn call_as_delegated_overrides_the_authority_code() {
+    let node = MockNode::start(
+        |req| json!({ "jsonrpc": "2.0", "id": req["id"], "result": format!("0x{:064x}", 7) }),
+    );
+    let provider = Provider::new(&node.url);
+    let authority = address!("19E7E376E7C213B7E7e7e46cc70A5dD086DAff2A");
+
+    let result = call_as_delegated(&provider, authority, COUNTER, &[0x06, 0x66, 0x1a, 0xbd])
+        .await
+        .unwrap();
+    assert_eq!(result[31], 7);
+
+    let requests = node.requests.lock().unwrap();
+    let params = &requests[0]["params"];
+    assert_eq!(requests[0]["method"], "eth_call");
+    assert_eq!(params[0]["to"], format!("0x{authority:x}"));
+    assert_eq!(
+        params[2][format!("0x{authority:x}")]["code"],
+        "0xef01004f747741ef10551969f9688a8264fc6bb337fa5f"
+    );
+}
+
+fn send_node(answer: Value) -> MockNode {
+    MockNode::start(move |req| {
+        let mut resp = answer.clone();
+        resp["jsonrpc"] = json!("2.0");
+        resp["id"] = req["id"].clone();
+        resp
+    })
+}
+
+#[tokio::test]
+async fn send_raw_returns_the_node_hash() {
+    let hash = format!("0x{}", "ab".repeat(32));
+    let node = send_node(json!({ "result": hash }));
+
+    let tx_hash = send_raw(&Provider::new(&node.url), &[0x02, 0xc0])
+        .await
+        .unwrap();
+    assert_eq!(tx_hash.to_string(), hash);
+    assert_eq!(node.requests.lock().unwrap()[0]["params"][0], "0x02c0");
+}
+
+#[tokio::test]
+async fn send_raw_treats_already_known_as_sent() {
+    let node = send_node(json!({ "error": { "code": -32000, "message": "already known" } }));
+
+    let tx_hash = send_raw(&Provider::new(&node.url), &[0x02, 0xc0])
+        .await
+        .unwrap();
+    assert_eq!(tx_hash, keccak256([0x02, 0xc0]));
+}
+
+#[tokio::test]
+async fn already_known_era_tx_is_an_error() {
+    // an Era tx hash isn't the keccak of its envelope, so there is none to fall back on
+    let node = send_node(json!({ "error": { "code": -32000, "message": "already known" } }));
+
+    let err = send_raw(&Provider::new(&node.url), &[0x71, 0xc0])
+        .await
+        .unwrap_err();
+    assert!(matches!(&err, TxError::Rpc(msg) if msg.contains("already known")));
+}
+
+#[tokio::test]
+async fn send_raw_reports_other_errors() {
+    let node = send_node(json!({ "error": { "code": -32000, "message": "nonce too low" } }));
+
+    let err = send_raw(&Provider::new(&node.url), &[0x02, 0xc0])
+        .await
+        .unwrap_err();
+    assert!(matches!(&err, TxError::Rpc(msg) if msg.contains("nonce too low")));
+}
+
+#[test]
+fn geth_insufficient_funds_is_classified() {
+    let geth = "insufficient funds for gas * price + value: address \
+                0x0A265D1d68fd54B09d434de7846D8e631668D99b have 0 want 1000046000000000000";
+    assert_eq!(
+        RpcErrorKind::classify(geth),
+        RpcErrorKind::InsufficientFunds
+    );
+    assert_eq!(
+        RpcErrorKind::classify("already known"),
+        RpcErrorKind::AlreadyKnown
+    );
+    assert_eq!(
+        RpcErrorKind::classify("replacement transaction underpriced"),
+        RpcErrorKind::Other
+    );
+}
+
+#[test]
+fn nonce_too_low_is_classified() {
+    // geth's wording
+    let geth = "nonce too low: next nonce 4, tx nonce 3";
+    assert_eq!(RpcErrorKind::classify(geth), RpcErrorKind::NonceTooLow);
+    assert_eq!(
+        RpcErrorKind::classify("Nonce too low"),
+        RpcErrorKind::NonceTooLow
+    );
+}
+
+#[tokio::test]
+async fn type_not_supported_is_classified_and_explained() {
+    let geth = "transaction type not supported";
+    assert_eq!(
+        RpcErrorKind::classify(geth),
+        RpcErrorKind::TxTypeNotSupported
+    );
+    assert_eq!(
+        RpcErrorKind::classify("unsupported transaction type: 4"),
+        RpcErrorKind::TxTypeNotSupported
+    );
+
+    let node = send_node(json!({ "error": { "code": -32000, "message": geth } }));
+    let err = send_raw(&Provider::new(&node.url), &[0x04, 0xc0])
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, TxError::Rpc(msg) if msg.contains(geth) && msg.contains("activated Prague")),
+        "{err}"
+    );
+}
+
+fn receipt_at(block: u64) -> Value {
+    json!({ "blockNumber": format!("0x{block:x}"), "gasUsed": "0x5208", "status": "0x1" })
+}
+
+#[tokio::test]
+async fn wait_for_receipt_waits_for_the_requested_depth() {
+    // mined in block 16, the head moves up one block per poll
+    let head = AtomicU64::new(0x10);
+    let node = MockNode::with_methods(move |method, _| match method {
+        "eth_getTransactionReceipt" => json!({ "result": receipt_at(0x10) }),
+        "eth_blockNumber" => {
+            json!({ "result": format!("0x{:x}", head.fetch_add(1, Ordering::SeqCst)) })
+        }
+        _ => unreachable!("{method}"),
+    });
+    let provider = Provider::new(&node.url).with_poll_interval(Duration::from_millis(5));
+
+    let receipt = wait_for_receipt(&provider, B256::ZERO, 3).await.unwrap();
+    assert_eq!(receipt.block_number, 0x10);
+    // heads 0x10, 0x11, 0x12, then 0x13 is three deep
+    let polls = node
+        .methods()
+        .iter()
+        .filter(|m| *m == "eth_blockNumber")
+        .count();
+    assert_eq!(polls, 4);
+}
+
+#[tokio::test]
+async fn wait_for_receipt_counts_depth_from_the_block_after_a_reorg() {
+    // seen in block 17, dropped by a reorg, then re-mined in block 19
+    let receipt_polls = AtomicU64::new(0);
+    let head = AtomicU64::new(0x12);
+    let node = MockNode::with_methods(move |method, _| match method {
+        "eth_getTransactionReceipt" => match receipt_polls.fetch_add(1, Ordering::SeqCst) {
+            0 => json!({ "result": receipt_at(0x11) }),
+            1 => json!({ "result": null }),
+            _ => json!({ "result": receipt_at(0x13) }),
+        },
+        // back in the mempool in between
+        "eth_getTransactionByHash" => json!({ "result": { "blockNumber": null } }),
+        "eth_blockNumber" => {
+            json!({ "result": format!("0x{:x}", head.fetch_add(1, Ordering::SeqCst)) })
+        }
+        _ => unreachable!("{method}"),
+    });
+    let provider = Provider::new(&node.url).with_poll_interval(Duration::from_millis(5));
+
+    // 17 would have been two deep at head 19, but the tx isn't there any more
+    let receipt = wait_for_receipt(&provider, B256::ZERO, 2).await.unwrap();
+    assert_eq!(receipt.block_number, 0x13);
+    assert_eq!(
+        node.methods().last().map(String::as_str),
+        Some("eth_blockNumber")
+    );
+}
+
+#[tokio::test]
+async fn wait_for_receipt_times_out_with_the_hash() {
+    // pending forever
+    let node = MockNode::with_methods(|method, _| match method {
+        "eth_getTransactionByHash" => json!({ "result": { "blockNumber": null } }),
+        _ => json!({ "result": null }),
+    });
+    let provider = Provider::new(&node.url)
+        .with_poll_interval(Duration::from_millis(5))
+        .with_wait_timeout(Duration::from_millis(50));
+    let tx_hash = B256::repeat_byte(0x22);
+
+    let started = std::time::Instant::now();
+    match wait_for_receipt(&provider, tx_hash, 0).await {
+        Err(TxError::Timeout { tx_hash: hash }) => assert_eq!(hash, tx_hash),
+        other => panic!("expected a timeout, got {other:?}"),
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn wait_for_receipt_stops_early_on_a_dropped_tx() {
+    let node = MockNode::start(|req| json!({ "jsonrpc": "2.0", "id": req["id"], "result": null }));
+    // a timeout would take far longer than the test
+    let provider = Provider::new(&node.url).with_poll_interval(Duration::from_millis(5));
+    let tx_hash = B256::repeat_byte(0x33);
+
+    let started = std::time::Instant::now();
+    match wait_for_receipt(&provider, tx_hash, 0).await {
+        Err(TxError::Dropped { tx_hash: hash }) => assert_eq!(hash, tx_hash),
+        other => panic!("expected a dropped tx, got {other:?}"),
+    }
+    assert!(started.elapsed() < Duration::from_secs(5));
+    assert_eq!(
+        node.methods(),
+        ["eth_getTransactionReceipt", "eth_getTransactionByHash"]
+    );
+}
+
+#[tokio::test]
+async fn oracle_fees_uses_whichever_sources_answer() {
+    // no eth_gasPrice on this node
+    let node = MockNode::with_methods(|method, _| match method {
+        "eth_feeHistory" => json!({ "result": {
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x5a", "0x5f", "0x64"],
+            "reward": [["0x5"], ["0xa"]],
+        } }),
+        "eth_maxPriorityFeePerGas" => json!({ "result": "0x8" }),
+        _ => json!({ "error": { "code": -32601, "message": "method not found" } }),
+    });
+    let provider = Provider::new(&node.url);
+
+    let (max_fee, priority_fee) = oracle_fees(&provider, TipSource::Highest).await.unwrap();
+    // the fee history's highest tip beats eth_maxPriorityFeePerGas
+    assert_eq!(priority_fee, U256::from(10));
+    // twice the next base fee (100), plus the tip
+    assert_eq!(max_fee, U256::from(210));
+
+    let (max_fee, priority_fee) = oracle_fees(&provider, TipSource::Node).await.unwrap();
+    assert_eq!(priority_fee, U256::from(8));
+    assert_eq!(max_fee, U256::from(208));
+    let (_, priority_fee) = oracle_fees(&provider, TipSource::History).await.unwrap();
+    assert_eq!(priority_fee, U256::from(10));
+}
+
+#[test]
+fn urgent_fees_beat_slow_on_the_same_history() {
+    // rewards at the 10th, 50th, 75th and 90th percentile
+    let history: FeeHistory = serde_json::from_value(json!({
+        "oldestBlock": "0x10",
+        "baseFeePerGas": ["0x5a", "0x5f", "0x64"],
+        "reward": [["0x1", "0x2", "0x3", "0x4"], ["0x2", "0x5", "0x9", "0xe"]],
+    }))
+    .unwrap();
+
+    let (slow_max, slow_tip) = FeeStrategy::Slow.fees(&history).unwrap();
+    let (urgent_max, urgent_tip) = FeeStrategy::Urgent.fees(&history).unwrap();
+    assert!(urgent_tip > slow_tip);
+    assert!(urgent_max > slow_max);
+    // 1.2x and 2x the next base fee (100), plus the highest tip
+    assert_eq!((slow_max, slow_tip), (U256::from(122), U256::from(2)));
+    assert_eq!((urgent_max, urgent_tip), (U256::from(214), U256::from(14)));
+}
+
+#[tokio::test]
+async fn fetch_chain_id_parses_the_hex_id() {
+    let node = MockNode::builder().on("eth_chainId", "0x539").start();
+    assert_eq!(
+        fetch_chain_id(&Provider::new(&node.url)).await.unwrap(),
+        1337
+    );
+    assert_eq!(node.methods(), ["eth_chainId"]);
+
+    let node = MockNode::builder()
+        .on_fn(
+            "eth_chainId",
+            |_| json!({ "error": { "code": -32000, "message": "down" } }),
+        )
+        .start();
+    assert!(fetch_chain_id(&Provider::new(&node.url)).await.is_err());
+}
+
+#[tokio::test]
+async fn max_priority_fee_parses_the_hex_quantity() {
+    let node = MockNode::builder()
+        .on("eth_maxPriorityFeePerGas", "0x3b9aca00")
+        .start();
+    assert_eq!(
+        max_priority_fee(&Provider::new(&node.url)).await.unwrap(),
+        U256::from(1_000_000_000u64)
+    );
+}
+
+#[tokio::test]
+async fn node_tip_falls_back_to_the_fee_history() {
+    let node = MockNode::with_methods(|method, _| match method {
+        "eth_feeHistory" => json!({ "result": {
+            "oldestBlock": "0x10",
+            "baseFeePerGas": ["0x5a", "0x64"],
+            "reward": [["0x5"]],
+        } }),
+        _ => json!({ "error": { "code": -32601, "message": "method not found" } }),
+    });
+    let provider = Provider::new(&node.url);
+
+    assert!(matches!(
+        max_priority_fee(&provider).await,
+        Err(TxError::Rpc(_))
+    ));
+    let (max_fee, priority_fee) = oracle_fees(&provider, TipSource::Node).await.unwrap();
+    assert_eq!(priority_fee, U256::from(5));
+    assert_eq!(max_fee, U256::from(205));
+}
+
+#[tokio::test]
+async fn oracle_fees_fails_without_any_source() {
+    let node = MockNode::with_methods(|method, _| match method {
+        "eth_getBlockByNumber" => json!({ "result": {
+            "number": "0x10",
+            "baseFeePerGas": "0x7",
+            "timestamp": "0x6553f100",
+            "gasLimit": "0x1c9c380",
+        } }),
+        _ => json!({ "error": { "code": -32601, "message": "method not found" } }),
+    });
+    assert!(oracle_fees(&Provider::new(&node.url), TipSource::Highest)
+        .await
+        .is_err());
+}
+
+#[tokio::test]
+async fn get_block_parses_a_recorded_header() {
+    // trimmed from mainnet block 19,000,000
+    let node = MockNode::with_methods(|method, params| match method {
+        "eth_getBlockByNumber" => {
+            assert_eq!(params, &json!(["0x121eac0", false]));
+            json!({ "result": {
+                "baseFeePerGas": "0x3c6b4f7d1",
+                "difficulty": "0x0",
+                "gasLimit": "0x1c9c380",
+                "gasUsed": "0xa9371c",
+                "hash": "0xcf384012b91b081230cdf17a3f7dd370d8e67056058af6b272b3d54aa2714fac",
+                "miner": "0x95222290dd7278aa3ddd389cc1e1d165cc4bafe5",
+                "number": "0x121eac0",
+                "timestamp": "0x65aa3ffb",
+                "transactions": [],
+            } })
+        }
+        _ => unreachable!("{method}"),
+    });
+
+    let block = get_block(&Provider::new(&node.url), BlockTag::Number(19_000_000))
+        .await
+        .unwrap();
+    assert_eq!(block.number, 19_000_000);
+    assert_eq!(block.base_fee_per_gas, Some(U256::from(16_218_650_577u64)));
+    assert_eq!(block.timestamp, 1_705_656_315);
+    assert_eq!(block.gas_limit, 30_000_000);
+}
+
+#[tokio::test]
+async fn get_block_handles_pre_london_and_missing_blocks() {
+    let node = MockNode::with_methods(|method, params| match (method, params[0].as_str()) {
+        ("eth_getBlockByNumber", Some("latest")) => json!({ "result": {
+            "number": "0x1",
+            "timestamp": "0x55ba4224",
+            "gasLimit": "0x1388",
+        } }),
+        ("eth_getBlockByNumber", _) => json!({ "result": null }),
+        _ => unreachable!("{method}"),
+    });
+    let provider = Provider::new(&node.url);
+
+    let block = get_block(&provider, BlockTag::Latest).await.unwrap();
+    assert_eq!(block.base_fee_per_gas, None);
+    assert!(get_block(&provider, BlockTag::Number(2)).await.is_err());
+}
+
+#[tokio::test]
+async fn block_number_parses_the_head() {
+    let node = MockNode::builder()
+        .on("eth_blockNumber", "0x13a2b5c")
+        .start();
+    assert_eq!(
+        block_number(&Provider::new(&node.url)).await.unwrap(),
+        20_589_404
+    );
+}
+
+#[test]
+fn json_rpc_error_deserializes_code_message_and_data() {
+    let resp: JsonRpcResponse<String> = serde_json::from_value(json!({
+        "jsonrpc": "2.0",
+        "id": 7,
+        "error": { "code": 3, "message": "execution reverted", "data": "0x08c379a0" },
+    }))
+    .unwrap();
+    let err = resp.error.unwrap();
+    assert_eq!(
+        err,
+        JsonRpcError {
+            code: 3,
+            message: "execution reverted".to_string(),
+            data: Some(json!("0x08c379a0")),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        r#"execution reverted (code 3), data: "0x08c379a0""#
+    );
+
+    // nodes that leave the reason out of the message get it from the data
+    let err: JsonRpcError = serde_json::from_value(json!({
+        "code": 3,
+        "message": "execution reverted",
+        "data": "0x08c379a0\
+                 0000000000000000000000000000000000000000000000000000000000000020\
+                 0000000000000000000000000000000000000000000000000000000000000004\
+                 6e6f706500000000000000000000000000000000000000000000000000000000",
+    }))
+    .unwrap();
+    assert_eq!(err.revert_reason().as_deref(), Some("nope"));
+    assert!(err
+        .to_string()
+        .starts_with("execution reverted: nope (code 3)"));
+
+    // data is optional
+    let err: JsonRpcError =
+        serde_json::from_value(json!({ "code": -32601, "message": "method not found" })).unwrap();
+    assert_eq!(err.data, None);
+}
+
+/// Records every event as its message and fields.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<HashMap<String, String>>>>);
+
+impl tracing::Subscriber for Capture {
+    fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+        true
+    }
+    fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+        tracing::span::Id::from_u64(1)
+    }
+    fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+    fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+    fn event(&self, event: &tracing::Event<'_>) {
+        struct Fields<'a>(&'a mut HashMap<String, String>);
+        impl tracing::field::Visit for Fields<'_> {
+            fn record_str(&mut self, field: &tracing::field::Field, value: &str) {
+                self.0.insert(field.name().to_string(), value.to_string());
+            }
+            fn record_debug(&mut self, field: &tracing::field::Field, value: &dyn std::fmt::Debug) {
+                self.0
+                    .insert(field.name().to_string(), format!("{value:?}"));
+            }
+        }
+        let mut fields = HashMap::new();
+        event.record(&mut Fields(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+    fn enter(&self, _: &tracing::span::Id) {}
+    fn exit(&self, _: &tracing::span::Id) {}
+}
+
+impl Capture {
+    /// The `id` of every event with this message.
+    fn ids(&self, message: &str) -> Vec<String> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| fields["message"] == message)
+            .map(|fields| {
+                fields["id"]
+                    .trim_start_matches("Some(")
+                    .trim_end_matches(')')
+                    .to_string()
+            })
+            .collect()
+    }
+}
+
+#[tokio::test]
+async fn rpc_calls_trace_the_request_id_both_ways() {
+    let node = MockNode::with_methods(devnet);
+    let provider = Provider::new(&node.url);
+    let capture = Capture::default();
+    let _guard = tracing::subscriber::set_default(capture.clone());
+
+    get_nonce(&provider, Address::ZERO).await.unwrap();
+    assert_eq!(capture.ids("rpc request"), ["1"]);
+    assert_eq!(capture.ids("rpc response"), ["1"]);
+
+    // a batch traces every request in it
+    get_nonces(&provider, &[Address::ZERO, Address::repeat_byte(1)])
+        .await
+        .unwrap();
+    assert_eq!(capture.ids("rpc request"), ["1", "2", "3"]);
+    assert_eq!(capture.ids("rpc response"), ["1", "2", "3"]);
+
+    let events = capture.0.lock().unwrap();
+    assert_eq!(events[0]["method"], "eth_getTransactionCount");
+    assert!(events[0]["params"].contains("0x0000000000000000000000000000000000000000"));
+}
+
+#[tokio::test]
+async fn build_authorizations_signs_each_pair_with_its_own_nonce() {
+    let alice = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let bob = SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let alice_address = format!("0x{:x}", address_from_key(&alice));
+    let node = MockNode::with_methods(move |method, params| match method {
+        "eth_getTransactionCount" if params[0] == alice_address.as_str() => {
+            json!({ "result": "0x5" })
+        }
+        "eth_getTransactionCount" => json!({ "result": "0x9" }),
+        _ => json!({ "result": null }),
+    });
+    let provider = Provider::new(&node.url);
+
+    let pairs = [(alice, COUNTER), (bob, Address::repeat_byte(0xbb))];
+    let auths = build_authorizations(&provider, 1337, &pairs).await.unwrap();
+
+    // both nonces in one batch
+    assert_eq!(node.requests.lock().unwrap().len(), 1);
+    assert_eq!(auths.len(), 2);
+    for ((key, delegate), (auth, nonce)) in pairs.iter().zip(auths.iter().zip([5, 9])) {
+        assert_eq!(auth.chain_id, 1337);
+        assert_eq!(auth.address, *delegate);
+        assert_eq!(auth.nonce, U256::from(nonce));
+        assert_eq!(auth.recover_authority().unwrap(), address_from_key(key));
+    }
+}
+
+#[tokio::test]
+async fn code_kind_tells_eoas_contracts_and_delegations_apart() {
+    let eoa = Address::repeat_byte(0x01);
+    let contract = Address::repeat_byte(0x02);
+    let node = MockNode::with_methods(move |method, params| {
+        assert_eq!(method, "eth_getCode");
+        let code = match params[0].as_str().unwrap().parse::<Address>().unwrap() {
+            addr if addr == eoa => "0x".to_string(),
+            addr if addr == contract => "0x6080604052".to_string(),
+            _ => format!("0xef0100{}", "cc".repeat(20)),
+        };
+        json!({ "result": code })
+    });
+    let provider = Provider::new(&node.url);
+
+    assert_eq!(code_kind(&provider, eoa).await.unwrap(), CodeKind::Eoa);
+    assert_eq!(
+        code_kind(&provider, contract).await.unwrap(),
+        CodeKind::Contract
+    );
+    assert_eq!(
+        code_kind(&provider, Address::repeat_byte(0x03))
+            .await
+            .unwrap(),
+        CodeKind::Delegated(Address::repeat_byte(0xcc))
+    );
+}
+
+#[tokio::test]
+async fn fetched_7702_tx_carries_its_authorizations() {
+    // as geth returns it, trimmed
+    let recorded = json!({
+        "type": "0x4",
+        "chainId": "0x539",
+        "nonce": "0x3",
+        "to": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+        "gas": "0xb3b0",
+        "maxFeePerGas": "0x3b9aca00",
+        "maxPriorityFeePerGas": "0x3b9aca00",
+        "value": "0x0",
+        "input": "0x",
+        "accessList": [],
+        "authorizationList": [
+            {
+                "chainId": "0x539",
+                "address": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+                "nonce": "0x7",
+                "yParity": "0x0",
+                "r": "0x231e38a87189f87a8fbd426fca812eeb28c049ae1905faa6abac3ab4bc72d52f",
+                "s": "0x590530c8228c2391cda0c9c136517ef2d0664d34ed738e0e52160baf85171144",
+            },
+            {
+                "chainId": "0x0",
+                "address": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+                "nonce": "0x0",
+                "yParity": "0x0",
+                "r": "0x3c3ead5e53532a8f22a476b4a6c656bbdfb520994d6ba6524d040660b6d22565",
+                "s": "0x3e3b634e642932287fd5312bd7a5a97b774ee4f9899b39ea409a7d078fbb1550",
+            },
+        ],
+        "blockNumber": "0x10",
+    });
+    let node = MockNode::with_methods(move |_, _| json!({ "result": recorded }));
+    let provider = Provider::new(&node.url);
+
+    let tx = get_transaction(&provider, B256::repeat_byte(0x11))
+        .await
+        .unwrap();
+    assert!(tx.access_list.is_empty());
+    let [first, second] = &tx.authorization_list[..] else {
+        panic!("expected two authorizations, got {tx:?}");
+    };
+    assert_eq!((first.chain_id, first.nonce), (1337, U256::from(7)));
+    assert_eq!((second.chain_id, second.nonce), (0, U256::ZERO));
+    assert_eq!(
+        first.recover_authority().unwrap(),
+        address!("19E7E376E7C213B7E7e7e46cc70A5dD086DAff2A")
+    );
+    assert_eq!(
+        second.recover_authority().unwrap(),
+        address!("1563915e194D8CfBA1943570603F7606A3115508")
+    );
+}
+
+#[tokio::test]
+async fn oversized_response_is_refused() {
+    let node =
+        MockNode::with_methods(|_, _| json!({ "result": format!("0x{}", "00".repeat(1024)) }));
+    let provider = Provider::new(&node.url).with_max_response_size(1024);
+
+    match get_nonce(&provider, Address::ZERO).await {
+        Err(TxError::Rpc(msg)) => assert!(msg.contains("response too large"), "{msg}"),
+        other => panic!("expected the size limit to trigger, got {other:?}"),
+    }
+
+    // the same answer fits the default limit
+    let provider = Provider::new(&node.url);
+    assert!(get_nonce(&provider, Address::ZERO).await.is_ok());
+}
+
+#[tokio::test]
+async fn get_storage_at_parses_a_32_byte_word() {
+    let counter = Address::repeat_byte(0xcc);
+    let node = MockNode::with_methods(move |method, params| match method {
+        "eth_getStorageAt" => {
+            assert_eq!(params, &json!([counter, "0x0", "latest"]));
+            json!({ "result": "0x000000000000000000000000000000000000000000000000000000000000002a" })
+        }
+        _ => unreachable!("{method}"),
+    });
+
+    let value = get_storage_at(
+        &Provider::new(&node.url),
+        counter,
+        U256::ZERO,
+        BlockTag::Latest,
+    )
+    .await
+    .unwrap();
+    assert_eq!(value, U256::from(42));
+}
+
+#[tokio::test]
+async fn wait_for_delegation_polls_until_the_designator_appears() {
+    let delegate = Address::repeat_byte(0xdd);
+    let polls = AtomicU64::new(0);
+    let node = MockNode::with_methods(move |method, _| match method {
+        "eth_getCode" => match polls.fetch_add(1, Ordering::SeqCst) {
+            0 => json!({ "result": "0x" }),
+            _ => json!({ "result": format!("0xef0100{}", hex::encode(delegate)) }),
+        },
+        _ => unreachable!("{method}"),
+    });
+    let provider = Provider::new(&node.url).with_poll_interval(Duration::from_millis(5));
+
+    wait_for_delegation(
+        &provider,
+        Address::repeat_byte(0xaa),
+        delegate,
+        Duration::from_secs(5),
+    )
+    .await
+    .unwrap();
+    assert_eq!(node.methods(), ["eth_getCode", "eth_getCode"]);
+}
+
+#[tokio::test]
+async fn wait_for_delegation_times_out_on_another_delegate() {
+    let node = MockNode::with_methods(|method, _| match method {
+        "eth_getCode" => json!({ "result": format!("0xef0100{}", "11".repeat(20)) }),
+        _ => unreachable!("{method}"),
+    });
+    let provider = Provider::new(&node.url).with_poll_interval(Duration::from_millis(5));
+
+    let err = wait_for_delegation(
+        &provider,
+        Address::repeat_byte(0xaa),
+        Address::repeat_byte(0xdd),
+        Duration::from_millis(30),
+    )
+    .await
+    .unwrap_err();
+    assert!(matches!(err, TxError::DelegationTimeout { .. }), "{err}");
+}
+
+#[tokio::test]
+async fn each_nonce_strategy_asks_the_node_its_own_way() {
+    let node = MockNode::with_methods(|method, params| match method {
+        "txpool_contentFrom" => json!({ "result": {
+            "pending": { "3": {}, "4": {}, "5": {} },
+            "queued": { "9": {} },
+        } }),
+        _ => devnet(method, params),
+    });
+    let provider = Provider::new(&node.url);
+    let calls = || {
+        let requests = std::mem::take(&mut *node.requests.lock().unwrap());
+        requests
+            .iter()
+            .map(|req| format!("{} {}", req["method"].as_str().unwrap(), req["params"]))
+            .collect::<Vec<_>>()
+    };
+    let nonce = |strategy: &str| {
+        let strategy: NonceStrategy = strategy.parse().unwrap();
+        resolve_nonce(&provider, Address::ZERO, strategy)
+    };
+    let zero = format!("\"{}\"", Address::ZERO);
+
+    assert_eq!(nonce("latest").await.unwrap(), U256::from(3));
+    assert_eq!(
+        calls(),
+        [format!("eth_getTransactionCount [{zero},\"latest\"]")]
+    );
+    assert_eq!(nonce("pending").await.unwrap(), U256::from(3));
+    assert_eq!(
+        calls(),
+        [format!("eth_getTransactionCount [{zero},\"pending\"]")]
+    );
+    // past the pool's pending txs, ignoring the queued one after the gap
+    assert_eq!(nonce("txpool").await.unwrap(), U256::from(6));
+    assert_eq!(
+        calls(),
+        [
+            format!("eth_getTransactionCount [{zero},\"latest\"]"),
+            format!("txpool_contentFrom [{zero}]"),
+        ]
+    );
+    assert_eq!(nonce("fixed:42").await.unwrap(), U256::from(42));
+    assert!(calls().is_empty());
+
+    assert!("fixed:".parse::<NonceStrategy>().is_err());
+    assert!("earliest".parse::<NonceStrategy>().is_err());
+}
+
+#[tokio::test]
+async fn simulate_bundle_reads_each_top_level_trace() {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let mut delegation = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(100_000),
+        to: Some(COUNTER),
+        value: U256::ZERO,
+        data: vec![0xd0, 0x9d, 0xe0, 0x8a],
+        access_list: vec![],
+        authorization_list: vec![
+            Authorization7702::sign(1337, COUNTER, U256::from(4), &key).unwrap()
+        ],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut delegation, &key).unwrap();
+    let mut call = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(5),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(50_000),
+        to: Some(COUNTER),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut call, &key).unwrap();
+
+    // recorded from a Reth devnet, vmTrace and stateDiff not asked for
+    let node = MockNode::with_methods(|method, _| match method {
+        "trace_callMany" => json!({ "result": [
+            {
+                "output": "0x000000000000000000000000000000000000000000000000000000000000002a",
+                "stateDiff": null,
+                "trace": [{
+                    "action": {
+                        "callType": "call",
+                        "from": "0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a",
+                        "gas": "0x13670",
+                        "input": "0xd09de08a",
+                        "to": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+                        "value": "0x0"
+                    },
+                    "result": { "gasUsed": "0x6d60", "output": "0x" },
+                    "subtraces": 0,
+                    "traceAddress": [],
+                    "type": "call"
+                }],
+                "vmTrace": null
+            },
+            {
+                "output": "0xdeadbeef",
+                "stateDiff": null,
+                "trace": [{
+                    "action": {
+                        "callType": "call",
+                        "from": "0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a",
+                        "gas": "0x7530",
+                        "input": "0x",
+                        "to": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+                        "value": "0x0"
+                    },
+                    "error": "Reverted",
+                    "subtraces": 0,
+                    "traceAddress": [],
+                    "type": "call"
+                }],
+                "vmTrace": null
+            }
+        ] }),
+        _ => unreachable!("{method}"),
+    });
+    let results = simulate_bundle(
+        &Provider::new(&node.url),
+        &[SignedTx::Eip7702(delegation), SignedTx::Eip1559(call)],
+    )
+    .await
+    .unwrap();
+
+    assert_eq!(
+        results,
+        [
+            SimResult {
+                success: true,
+                gas_used: 0x6d60,
+                output: U256::from(42).to_be_bytes_vec(),
+                error: None,
+            },
+            SimResult {
+                success: false,
+                gas_used: 0,
+                output: vec![0xde, 0xad, 0xbe, 0xef],
+                error: Some("Reverted".to_string()),
+            },
+        ]
+    );
+    let params = node.requests.lock().unwrap()[0]["params"].clone();
+    let calls = params[0].as_array().unwrap();
+    assert_eq!(calls[0][0]["from"], json!(address_from_key(&key)));
+    assert_eq!(calls[0][0]["authorizationList"][0]["nonce"], "0x4");
+    assert_eq!(calls[0][1], json!(["trace"]));
+    assert!(calls[1][0].get("authorizationList").is_none());
+    assert_eq!(params[1], "latest");
+}
+
+#[tokio::test]
+async fn get_logs_parses_a_recorded_response() {
+    let tx_hash = b256!("0a5f2d3ae2bd0fb0b2232b7d8b5ac05420c605e3bd9f93d7a1e29b4fcab5b39e");
+    let topic = b256!("ddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef");
+    let node = MockNode::with_methods(move |method, _| match method {
+        "eth_getLogs" => json!({ "result": [{
+            "address": "0x19e7e376e7c213b7e7e7e46cc70a5dd086daff2a",
+            "topics": [
+                "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef",
+                "0x00000000000000000000000019e7e376e7c213b7e7e7e46cc70a5dd086daff2a"
+            ],
+            "data": "0x000000000000000000000000000000000000000000000000000000000000002a",
+            "blockNumber": "0x1b4",
+            "blockHash": "0xc4b1e1d1b2a4bfb7f2a2e3b5e8c1d0a9b8c7d6e5f4a3b2c1d0e9f8a7b6c5d4e3",
+            "transactionHash": "0x0a5f2d3ae2bd0fb0b2232b7d8b5ac05420c605e3bd9f93d7a1e29b4fcab5b39e",
+            "transactionIndex": "0x0",
+            "logIndex": "0x2",
+            "removed": false
+        }] }),
+        _ => unreachable!("{method}"),
+    });
+    let authority = address!("19e7e376e7c213b7e7e7e46cc70a5dd086daff2a");
+    let filter = LogFilter {
+        from_block: BlockTag::Number(0x1b0),
+        to_block: BlockTag::Latest,
+        address: Some(authority),
+        topics: vec![Some(topic), None],
+    };
+    let logs = get_logs(&Provider::new(&node.url), &filter).await.unwrap();
+
+    assert_eq!(
+        logs,
+        [Log {
+            address: authority,
+            topics: vec![topic, B256::left_padding_from(authority.as_slice())],
+            data: U256::from(42).to_be_bytes_vec().into(),
+            block_number: Some(0x1b4),
+            transaction_hash: Some(tx_hash),
+            log_index: Some(2),
+            removed: false,
+        }]
+    );
+    assert_eq!(
+        node.requests.lock().unwrap()[0]["params"],
+        json!([{
+            "fromBlock": "0x1b0",
+            "toBlock": "latest",
+            "address": authority,
+            "topics": [topic, null],
+        }])
+    );
+}
+
+#[tokio::test]
+async fn send_transaction_carries_the_type_and_authorizations() {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let from = address!("19e7e376e7c213b7e7e7e46cc70a5dd086daff2a");
+    let tx = SignedTx::Eip7702(Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(3),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(46_000),
+        to: Some(from),
+        value: U256::ZERO,
+        data: vec![0xd0, 0x9d, 0xe0, 0x8a],
+        access_list: vec![(COUNTER, vec![U256::from(1)])],
+        authorization_list: vec![
+            Authorization7702::sign(1337, COUNTER, U256::from(4), &key).unwrap()
+        ],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    });
+    let node = MockNode::builder()
+        .on("eth_sendTransaction", format!("0x{}", "ab".repeat(32)))
+        .start();
+    let tx_hash = send_transaction(
+        &Provider::new(&node.url),
+        &TransactionRequest::new(from, &tx),
+    )
+    .await
+    .unwrap();
+    assert_eq!(tx_hash, B256::repeat_byte(0xab));
+
+    let request = node.requests.lock().unwrap()[0]["params"][0].clone();
+    assert_eq!(request["type"], "0x4");
+    assert_eq!(request["from"], json!(from));
+    assert_eq!(request["gas"], "0xb3b0");
+    assert_eq!(request["maxFeePerGas"], "0x2");
+    assert_eq!(request["data"], "0xd09de08a");
+    assert_eq!(request["chainId"], "0x539");
+    assert!(request.get("gasPrice").is_none());
+    assert_eq!(
+        request["accessList"][0]["storageKeys"][0],
+        json!(B256::from(U256::from(1)))
+    );
+    let SignedTx::Eip7702(tx) = &tx else {
+        unreachable!()
+    };
+    assert_eq!(
+        request["authorizationList"],
+        json!([{
+            "chainId": "0x539",
+            "address": COUNTER,
+            "nonce": "0x4",
+            "yParity": format!("0x{:x}", tx.authorization_list[0].y_parity),
+            "r": tx.authorization_list[0].r,
+            "s": tx.authorization_list[0].s,
+        }])
+    );
+}
+
+fn chain_id_reply(req: &Value) -> HttpReply {
+    HttpReply::ok(json!({ "jsonrpc": "2.0", "id": req["id"], "result": "0x539" }).to_string())
+}
+
+#[tokio::test]
+async fn rate_limited_request_waits_for_retry_after() {
+    let attempts = AtomicU64::new(0);
+    let node = MockNode::start_http(move |req| {
+        Some(if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+            HttpReply::status(429, "slow down").header("retry-after", "1")
+        } else {
+            chain_id_reply(req)
+        })
+    });
+    let started = std::time::Instant::now();
+    assert_eq!(
+        fetch_chain_id(&Provider::new(&node.url)).await.unwrap(),
+        1337
+    );
+    assert!(started.elapsed() >= Duration::from_secs(1));
+    assert_eq!(node.methods(), ["eth_chainId", "eth_chainId"]);
+}
+
+#[tokio::test]
+async fn rate_limit_retries_run_out() {
+    let node =
+        MockNode::start_http(|_| Some(HttpReply::status(429, "").header("retry-after", "0")));
+    let err = fetch_chain_id(&Provider::new(&node.url)).await.unwrap_err();
+    assert!(
+        matches!(&err, TxError::Rpc(msg) if msg.contains("HTTP 429")),
+        "{err}"
+    );
+    // the first try and three retries
+    assert_eq!(node.methods().len(), 4);
+}
+
+#[tokio::test]
+async fn html_error_page_is_an_rpc_error() {
+    let node = MockNode::start_http(|_| {
+        let mut reply = HttpReply::status(
+            503,
+            "<html><body><h1>503 Service Unavailable</h1></body></html>",
+        );
+        reply.headers = vec![("content-type", "text/html".to_string())];
+        Some(reply)
+    });
+    let err = fetch_chain_id(&Provider::new(&node.url)).await.unwrap_err();
+    assert!(
+        matches!(&err, TxError::Rpc(msg) if msg.contains("HTTP 503") && !msg.contains("<html>")),
+        "{err}"
+    );
+    // not retried
+    assert_eq!(node.methods().len(), 1);
+}
+
+#[test]
+fn receipts_deserialize_in_all_three_shapes() {
+    let success: Receipt = serde_json::from_value(json!({
+        "blockNumber": "0x10",
+        "gasUsed": "0x5208",
+        "effectiveGasPrice": "0x8",
+        "status": "0x1",
+    }))
+    .unwrap();
+    assert_eq!(success.status, Some(TxStatus::Success));
+    assert_eq!(success.effective_gas_price, Some(U256::from(8)));
+    assert_eq!(success.root, None);
+
+    let reverted: Receipt = serde_json::from_value(json!({
+        "blockNumber": "0x10",
+        "gasUsed": "0x5208",
+        "status": "0x0",
+    }))
+    .unwrap();
+    assert_eq!(reverted.status, Some(TxStatus::Reverted));
+    assert_eq!(reverted.effective_gas_price, None);
+
+    // pre-Byzantium: a state root and no status
+    let root = B256::repeat_byte(0x5a);
+    let pre_byzantium: Receipt = serde_json::from_value(json!({
+        "blockNumber": "0x10",
+        "gasUsed": "0x5208",
+        "root": root,
+    }))
+    .unwrap();
+    assert_eq!(pre_byzantium.status, None);
+    assert_eq!(pre_byzantium.root, Some(root));
+}
+
+#[test]
+fn unknown_receipt_status_is_rejected() {
+    let err = serde_json::from_value::<Receipt>(json!({
+        "blockNumber": "0x10",
+        "gasUsed": "0x5208",
+        "status": "0x2",
+    }))
+    .unwrap_err();
+    assert!(
+        err.to_string().contains("invalid receipt status 0x2"),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn batch_responses_are_matched_back_by_id() {
+    // answers every request with its own id as the result, batch reversed
+    let node = MockNode::start(|body| match body {
+        Value::Array(batch) => batch
+            .iter()
+            .rev()
+            .map(|req| json!({ "jsonrpc": "2.0", "id": req["id"], "result": req["id"] }))
+            .collect(),
+        req => json!({ "jsonrpc": "2.0", "id": req["id"], "result": req["id"] }),
+    });
+    let provider = Provider::new(&node.url);
+
+    let first: JsonRpcResponse<u64> = provider.rpc_call("eth_chainId", ()).await.unwrap();
+    let calls = vec![
+        ("eth_blockNumber", ()),
+        ("eth_chainId", ()),
+        ("eth_gasPrice", ()),
+    ];
+    let resps: Vec<JsonRpcResponse<u64>> = provider.batch_call(calls).await.unwrap();
+
+    // ids keep counting up across single calls and batches
+    let first_id = first.result.unwrap();
+    let ids: Vec<u64> = resps.into_iter().map(|resp| resp.result.unwrap()).collect();
+    assert_eq!(ids, [first_id + 1, first_id + 2, first_id + 3]);
+
+    let requests = node.requests.lock().unwrap();
+    let methods: Vec<_> = requests[1]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|req| req["method"].as_str().unwrap())
+        .collect();
+    assert_eq!(methods, ["eth_blockNumber", "eth_chainId", "eth_gasPrice"]);
+}
+
+#[tokio::test]
+async fn batch_response_missing_an_id_is_an_error() {
+    let node = MockNode::start(|body| {
+        let first = &body.as_array().unwrap()[0];
+        json!([{ "jsonrpc": "2.0", "id": first["id"], "result": 1 }])
+    });
+    let provider = Provider::new(&node.url);
+    let Err(err) = provider
+        .batch_call::<_, u64>(vec![("eth_chainId", ()), ("eth_gasPrice", ())])
+        .await
+    else {
+        panic!("a missing response went unnoticed");
+    };
+    assert!(
+        matches!(&err, TxError::Rpc(msg) if msg.starts_with("no response for eth_gasPrice")),
+        "{err}"
+    );
+}
+
+#[tokio::test]
+async fn null_nonce_is_an_error_unless_read_as_zero() {
+    let node = MockNode::builder()
+        .on("eth_getTransactionCount", Value::Null)
+        .start();
+    let addr = Address::repeat_byte(0x11);
+
+    let err = get_nonce(&Provider::new(&node.url), addr)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(&err, TxError::Rpc(msg) if msg.contains("returned no result")),
+        "{err}"
+    );
+
+    let provider = Provider::new(&node.url).with_null_nonce_as_zero(true);
+    assert_eq!(get_nonce(&provider, addr).await.unwrap(), U256::ZERO);
+    // batched lookups read it the same way
+    assert_eq!(
+        get_nonces(&provider, &[addr, addr]).await.unwrap(),
+        [U256::ZERO, U256::ZERO]
+    );
+}
@@ -0,0 +1,42 @@
+use alloy::{
+    primitives::{address, keccak256, Bytes},
+    sol,
+    sol_types::{eip712_domain, SolStruct},
+};
+use example_7702::safe::{
+    safe_domain_separator, safe_message_hash, DOMAIN_SEPARATOR_TYPEHASH, SAFE_MSG_TYPEHASH,
+};
+
+sol! {
+    struct SafeMessage {
+        bytes message;
+    }
+}
+
+#[test]
+fn type_hashes_match_the_safe_contracts() {
+    assert_eq!(
+        DOMAIN_SEPARATOR_TYPEHASH,
+        keccak256("EIP712Domain(uint256 chainId,address verifyingContract)")
+    );
+    assert_eq!(SAFE_MSG_TYPEHASH, keccak256("SafeMessage(bytes message)"));
+}
+
+#[test]
+fn safe_message_hash_matches_alloys_eip712() {
+    let safe = address!("a063cb7cfd8e57c30c788a0572cbbf2129ae56b6");
+    let message = b"hello safe".to_vec();
+    let domain = eip712_domain! {
+        chain_id: 1,
+        verifying_contract: safe,
+    };
+
+    assert_eq!(safe_domain_separator(safe, 1), domain.separator());
+    let expected = SafeMessage {
+        message: Bytes::from(message.clone()),
+    }
+    .eip712_signing_hash(&domain);
+    assert_eq!(safe_message_hash(safe, 1, &message), expected);
+    // the domain binds the chain
+    assert_ne!(safe_message_hash(safe, 1337, &message), expected);
+}
@@ -0,0 +1,402 @@
+use alloy::primitives::{keccak256, uint, Address, B256, U256};
+use example_7702::{
+    decode::{chain_id_from_v, decode_signed_tx, SignedTx},
+    error::TxError,
+    signing::{
+        address_from_key, address_from_pkey, finalize_signature, finalize_signature_with_hash,
+        find_recovery_id, recover_address, recovery_to_parity, resign, sign_hash, signature_bytes,
+        signature_from_bytes,
+    },
+    tx::{Authorization7702, Eip1559Transaction, Eip7702Transaction, LegacyTransaction},
+};
+use secp256k1::SecretKey;
+
+#[test]
+fn signature_bytes_roundtrip() {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let hash = keccak256(b"roundtrip");
+    let (r, s, y_parity) = sign_hash(&hash, &key).unwrap();
+
+    let bytes = signature_bytes(r, s, y_parity).unwrap();
+    assert_eq!(bytes[64], 27 + y_parity);
+    assert_eq!(signature_from_bytes(&bytes).unwrap(), (r, s, y_parity));
+    assert_eq!(
+        recover_address(&hash, r, s, y_parity).unwrap(),
+        address_from_key(&key)
+    );
+}
+
+#[test]
+fn find_recovery_id_recovers_the_dropped_parity() {
+    // deterministic, so this is always the same signature
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let hash = keccak256(b"bare r and s");
+    let (r, s, y_parity) = sign_hash(&hash, &key).unwrap();
+
+    assert_eq!(
+        find_recovery_id(&hash, r, s, address_from_key(&key)),
+        Some(y_parity)
+    );
+    assert_eq!(
+        find_recovery_id(&hash, r, s, Address::repeat_byte(0xaa)),
+        None
+    );
+}
+
+#[test]
+fn signature_from_bytes_accepts_a_bare_parity() {
+    let mut bytes = signature_bytes(U256::from(1), U256::from(2), 1).unwrap();
+    bytes[64] = 1;
+    assert_eq!(
+        signature_from_bytes(&bytes).unwrap(),
+        (U256::from(1), U256::from(2), 1)
+    );
+}
+
+#[test]
+fn parities_without_a_v_are_rejected() {
+    for y_parity in [2, 228, 229, u8::MAX] {
+        let err = signature_bytes(U256::from(1), U256::from(2), y_parity).unwrap_err();
+        assert!(matches!(err, TxError::Signer(_)), "{err}");
+    }
+
+    let mut bytes = signature_bytes(U256::from(1), U256::from(2), 0).unwrap();
+    for v in [2, 26, 29, 37, u8::MAX] {
+        bytes[64] = v;
+        let err = signature_from_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!("decode error: signature v {v} is not 0, 1, 27 or 28")
+        );
+    }
+}
+
+#[test]
+fn signing_hash_is_the_hash_of_the_unsigned_rlp() {
+    let to = Some(Address::repeat_byte(0xaa));
+    let legacy = LegacyTransaction {
+        nonce: U256::from(1),
+        gas_price: U256::from(2),
+        gas_limit: U256::from(21000),
+        to,
+        value: U256::from(3),
+        data: vec![0xde, 0xad],
+        v: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    assert_eq!(
+        legacy.signing_hash(1337),
+        keccak256(legacy.rlp_encode_unsigned(1337))
+    );
+
+    let eip1559 = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(1),
+        max_priority_fee_per_gas: U256::from(2),
+        max_fee_per_gas: U256::from(3),
+        gas_limit: U256::from(21000),
+        to,
+        value: U256::from(4),
+        data: vec![0xbe, 0xef],
+        access_list: vec![(Address::repeat_byte(0xbb), vec![U256::from(5)])],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    assert_eq!(
+        eip1559.signing_hash(),
+        keccak256(eip1559.rlp_encode_unsigned())
+    );
+
+    let authorization = Authorization7702::new(
+        1337,
+        Address::repeat_byte(0xcc),
+        U256::from(6),
+        format!("0x{}", "11".repeat(32)),
+    )
+    .unwrap();
+    let fields: [&dyn alloy_rlp::Encodable; 3] =
+        [&1337u64, &authorization.address, &authorization.nonce];
+    let mut payload = vec![0x05];
+    alloy_rlp::encode_list::<_, dyn alloy_rlp::Encodable>(&fields, &mut payload);
+    assert_eq!(authorization.signing_hash(), keccak256(payload));
+
+    let eip7702 = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(1),
+        max_priority_fee_per_gas: U256::from(2),
+        max_fee_per_gas: U256::from(3),
+        gas_limit: U256::from(46000),
+        to,
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![authorization],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    assert_eq!(
+        eip7702.signing_hash(),
+        keccak256(eip7702.rlp_encode_unsigned())
+    );
+}
+
+#[test]
+fn authorization_for_a_specific_chain() {
+    let key = format!("0x{}", "11".repeat(32));
+    let authority = address_from_key(&SecretKey::from_slice(&[0x11; 32]).unwrap());
+    let delegate = Address::repeat_byte(0xcc);
+
+    let any_chain = Authorization7702::new(0, delegate, U256::from(6), key.clone()).unwrap();
+    let devnet = Authorization7702::new(1337, delegate, U256::from(6), key).unwrap();
+    assert_eq!(devnet.chain_id, 1337);
+    assert_eq!(any_chain.recover_authority().unwrap(), authority);
+    assert_eq!(devnet.recover_authority().unwrap(), authority);
+
+    // the chain id is part of what gets signed
+    assert_ne!(any_chain.signing_hash(), devnet.signing_hash());
+    assert_ne!((any_chain.r, any_chain.s), (devnet.r, devnet.s));
+
+    // 0x05 || rlp([1337, delegate, 6])
+    let mut payload = vec![0x05, 0xd9, 0x82, 0x05, 0x39, 0x94];
+    payload.extend_from_slice(delegate.as_slice());
+    payload.push(0x06);
+    assert_eq!(devnet.signing_hash(), keccak256(&payload));
+}
+
+#[test]
+fn finalize_signature_signs_every_type() {
+    let key = SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let sender = address_from_key(&key);
+    let to = Some(Address::repeat_byte(0xaa));
+
+    // unsigned, v carries the chain id
+    let mut legacy = LegacyTransaction {
+        nonce: U256::from(1),
+        gas_price: U256::from(2),
+        gas_limit: U256::from(21000),
+        to,
+        value: U256::from(3),
+        data: vec![],
+        v: 1337,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let hash = legacy.signing_hash(1337);
+    finalize_signature(&mut legacy, &key).unwrap();
+    assert_eq!(chain_id_from_v(legacy.v), Some(1337));
+    let y_parity = (legacy.v - 35 - 2 * 1337) as u8;
+    assert_eq!(
+        recover_address(&hash, legacy.r, legacy.s, y_parity).unwrap(),
+        sender
+    );
+
+    let mut eip1559 = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(1),
+        max_priority_fee_per_gas: U256::from(2),
+        max_fee_per_gas: U256::from(3),
+        gas_limit: U256::from(21000),
+        to,
+        value: U256::from(4),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut eip1559, &key).unwrap();
+    assert_eq!(
+        recover_address(
+            &eip1559.signing_hash(),
+            eip1559.r,
+            eip1559.s,
+            eip1559.y_parity
+        )
+        .unwrap(),
+        sender
+    );
+
+    let mut eip7702 = Eip7702Transaction {
+        chain_id: 1337,
+        nonce: U256::from(1),
+        max_priority_fee_per_gas: U256::from(2),
+        max_fee_per_gas: U256::from(3),
+        gas_limit: U256::from(46000),
+        to,
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        authorization_list: vec![Authorization7702::sign(
+            0,
+            Address::repeat_byte(0xcc),
+            U256::ZERO,
+            &key,
+        )
+        .unwrap()],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut eip7702, &key).unwrap();
+    assert_eq!(
+        recover_address(
+            &eip7702.signing_hash(),
+            eip7702.r,
+            eip7702.s,
+            eip7702.y_parity
+        )
+        .unwrap(),
+        sender
+    );
+    // low-s, so the decoder takes all of them
+    for raw in [
+        legacy.rlp_encode_signed(),
+        eip1559.rlp_encode_signed(),
+        eip7702.rlp_encode_signed(),
+    ] {
+        decode_signed_tx(&raw).unwrap();
+    }
+}
+
+#[test]
+fn legacy_v_for_huge_chain_ids_is_checked() {
+    let key = SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let legacy = |v| LegacyTransaction {
+        nonce: U256::from(1),
+        gas_price: U256::from(2),
+        gas_limit: U256::from(21000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(3),
+        data: vec![],
+        v,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+
+    // the largest chain id whose v still fits in a u64 with either parity
+    let chain_id = (u64::MAX - 36) / 2;
+    let mut tx = legacy(chain_id);
+    finalize_signature(&mut tx, &key).unwrap();
+    assert_eq!(chain_id_from_v(tx.v), Some(chain_id));
+    assert_eq!(tx.recover_sender().unwrap(), address_from_key(&key));
+
+    // 2 * chain_id + 35 no longer fits
+    let err = finalize_signature(&mut legacy(u64::MAX / 2), &key).unwrap_err();
+    assert!(err.to_string().contains("too large for EIP-155"), "{err}");
+}
+
+#[test]
+fn signing_a_fixed_digest_is_reproducible() {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let digest = B256::repeat_byte(0x42);
+    let unsigned = || Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::ZERO,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+
+    // RFC 6979 nonces, so the same key and digest always give this
+    let mut tx = unsigned();
+    finalize_signature_with_hash(&mut tx, &digest, &key).unwrap();
+    assert_eq!(
+        tx.r,
+        uint!(0x0f394e2439520511a6bc9c9abdfc2238e845bd8b6d57a555a319fbac502d51de_U256)
+    );
+    assert_eq!(
+        tx.s,
+        uint!(0x25ba594ec4bf19aad4391a95ecffd6f08f7cffbc721637bae3c37f9b16862b2f_U256)
+    );
+    assert_eq!(tx.y_parity, 1);
+    assert_eq!((tx.r, tx.s, tx.y_parity), sign_hash(&digest, &key).unwrap());
+
+    // the convenience path is the same thing over the tx's own hash
+    let (mut hashed, mut given) = (unsigned(), unsigned());
+    finalize_signature(&mut hashed, &key).unwrap();
+    let hash = given.signing_hash();
+    finalize_signature_with_hash(&mut given, &hash, &key).unwrap();
+    assert_eq!(hashed.rlp_encode_signed(), given.rlp_encode_signed());
+}
+
+#[test]
+fn resign_changes_the_recovered_sender() {
+    let old_key = SecretKey::from_slice(&[0x22; 32]).unwrap();
+    let new_key = SecretKey::from_slice(&[0x33; 32]).unwrap();
+
+    let mut eip1559 = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(1),
+        max_priority_fee_per_gas: U256::from(2),
+        max_fee_per_gas: U256::from(3),
+        gas_limit: U256::from(21000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(4),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut eip1559, &old_key).unwrap();
+    resign(&mut eip1559, &new_key).unwrap();
+    let sender = SignedTx::Eip1559(eip1559).sender().unwrap();
+    assert_eq!(sender, address_from_key(&new_key));
+    assert_ne!(sender, address_from_key(&old_key));
+
+    // legacy v goes back to the chain id before the new signature
+    let mut legacy = LegacyTransaction {
+        nonce: U256::from(1),
+        gas_price: U256::from(2),
+        gas_limit: U256::from(21000),
+        to: Some(Address::repeat_byte(0xaa)),
+        value: U256::from(3),
+        data: vec![],
+        v: 1337,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    finalize_signature(&mut legacy, &old_key).unwrap();
+    resign(&mut legacy, &new_key).unwrap();
+    assert_eq!(chain_id_from_v(legacy.v), Some(1337));
+    assert_eq!(
+        SignedTx::Legacy(legacy).sender().unwrap(),
+        address_from_key(&new_key)
+    );
+}
+
+#[test]
+fn recovery_ids_outside_zero_and_one_are_an_error() {
+    assert_eq!(recovery_to_parity(0).unwrap(), 0);
+    assert_eq!(recovery_to_parity(1).unwrap(), 1);
+    let err = recovery_to_parity(2).unwrap_err().to_string();
+    assert_eq!(
+        err,
+        "signer error: recovery id 2 has no y_parity, expected 0 or 1"
+    );
+    assert!(recovery_to_parity(-1).is_err());
+}
+
+#[test]
+fn invalid_authority_key_is_an_error_not_a_panic() {
+    for key in ["0xnothex", "0x1234", &format!("0x{}", "00".repeat(32))] {
+        assert!(matches!(
+            Authorization7702::new(1337, Address::ZERO, U256::ZERO, key.to_string()),
+            Err(TxError::InvalidKey(_))
+        ));
+        assert!(matches!(
+            address_from_pkey(key),
+            Err(TxError::InvalidKey(_))
+        ));
+    }
+}
@@ -0,0 +1,141 @@
+use alloy::primitives::{address, U256};
+use example_7702::{
+    decode::{decode_signed_tx, SignedTx},
+    signing::sign_hash,
+    summary::{describe_access_list, describe_signatures, diff_tx, signature_components, summary},
+    tx::Eip1559Transaction,
+};
+use secp256k1::SecretKey;
+
+#[test]
+fn summary_shows_to_and_value() {
+    let to = address!("4F747741EF10551969F9688a8264FC6bb337fA5f");
+    let tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(1_000_000_000u64),
+        max_fee_per_gas: U256::from(2_000_000_000u64),
+        gas_limit: U256::from(21000),
+        to: Some(to),
+        value: U256::from(1_500_000_000_000_000_000u64),
+        data: vec![],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+
+    let out = summary(to, &SignedTx::Eip1559(tx));
+
+    assert!(out.contains("To:        0x4F747741EF10551969F9688a8264FC6bb337fA5f"));
+    assert!(out.contains("Value:     1500000000000000000 wei (1.5"));
+    // 21000 * 2 gwei on top of the value
+    assert!(out.contains("Max cost:  1500042000000000000 wei"));
+}
+
+#[test]
+fn diff_tx_lists_only_the_bumped_fees() {
+    let tx = |priority: u64, max_fee: u64, r: u64| Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(priority),
+        max_fee_per_gas: U256::from(max_fee),
+        gas_limit: U256::from(21000),
+        to: Some(address!("4F747741EF10551969F9688a8264FC6bb337fA5f")),
+        value: U256::from(1),
+        data: vec![0xab],
+        access_list: vec![],
+        y_parity: 0,
+        r: U256::from(r),
+        s: U256::from(r),
+    };
+    let original = tx(1_000_000_000, 2_000_000_000, 1);
+    // a new signature isn't a change worth reporting
+    let replacement = tx(1_125_000_000, 2_250_000_000, 2);
+
+    let field = |name: &str, old: &str, new: &str| (name.into(), old.into(), new.into());
+    assert_eq!(
+        diff_tx(&original, &replacement),
+        vec![
+            field("max_priority_fee_per_gas", "1000000000", "1125000000"),
+            field("max_fee_per_gas", "2000000000", "2250000000"),
+        ]
+    );
+    assert!(diff_tx(&original, &original).is_empty());
+}
+
+#[test]
+fn small_signature_words_keep_their_leading_zeros() {
+    let out = signature_components(U256::from(1) << 248, U256::from(5), "y_parity", 1);
+    let [r, s, v] = out.lines().collect::<Vec<_>>()[..] else {
+        panic!("expected three lines, got {out}");
+    };
+    assert_eq!(r, format!("  r: 0x01{}", "0".repeat(62)));
+    assert_eq!(s, format!("  s: 0x{}05", "0".repeat(62)));
+    assert_eq!(v, "  y_parity: 1");
+}
+
+#[test]
+fn signatures_are_listed_for_the_tx() {
+    let tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::ZERO,
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(21000),
+        to: None,
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![],
+        y_parity: 1,
+        r: U256::from(2),
+        s: U256::from(3),
+    };
+    let out = describe_signatures(&SignedTx::Eip1559(tx));
+    assert!(out.starts_with("Signature:\n  r: 0x"), "{out}");
+    assert!(
+        out.contains(&format!("  s: 0x{}03", "0".repeat(62))),
+        "{out}"
+    );
+    assert!(!out.contains("Authorization"));
+}
+
+#[test]
+fn decoded_access_list_shows_every_key_in_full() {
+    let mut tx = Eip1559Transaction {
+        chain_id: 1337,
+        nonce: U256::from(7),
+        max_priority_fee_per_gas: U256::from(1),
+        max_fee_per_gas: U256::from(2),
+        gas_limit: U256::from(30000),
+        to: Some(address!("4F747741EF10551969F9688a8264FC6bb337fA5f")),
+        value: U256::ZERO,
+        data: vec![],
+        access_list: vec![
+            (
+                address!("4F747741EF10551969F9688a8264FC6bb337fA5f"),
+                vec![U256::from(1), U256::from(0xabcd)],
+            ),
+            (address!("00000000000000000000000000000000000000aa"), vec![]),
+        ],
+        y_parity: 0,
+        r: U256::ZERO,
+        s: U256::ZERO,
+    };
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    (tx.r, tx.s, tx.y_parity) = sign_hash(&tx.signing_hash(), &key).unwrap();
+
+    let decoded = decode_signed_tx(&tx.rlp_encode_signed()).unwrap();
+    assert_eq!(
+        describe_access_list(decoded.access_list()),
+        format!(
+            "Access list: 2 addresses, 2 storage keys\n  \
+             0x4F747741EF10551969F9688a8264FC6bb337fA5f\n    \
+             0x{}01\n    \
+             0x{}abcd\n  \
+             0x00000000000000000000000000000000000000AA\n",
+            "0".repeat(62),
+            "0".repeat(60)
+        )
+    );
+}
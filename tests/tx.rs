@@ -0,0 +1,121 @@
+use alloy::primitives::{address, Address, U256};
+use example_7702::{
+    error::TxError,
+    signing::finalize_signature,
+    tx::{
+        authorizations_from_json, authorizations_to_json, build_delegation_only_tx,
+        Authorization7702,
+    },
+};
+use secp256k1::SecretKey;
+
+#[test]
+fn delegation_only_tx_moves_nothing() {
+    let sender = Address::repeat_byte(0x0a);
+    let authorization = Authorization7702::new(
+        0,
+        Address::repeat_byte(0xcc),
+        U256::ZERO,
+        format!("0x{}", "11".repeat(32)),
+    )
+    .unwrap();
+    let tx = build_delegation_only_tx(
+        sender,
+        vec![authorization],
+        1337,
+        U256::from(4),
+        U256::from(2_000_000_000u64),
+        U256::from(1_000_000_000u64),
+        U256::from(46000),
+    )
+    .unwrap();
+
+    assert_eq!(tx.value, U256::ZERO);
+    assert!(tx.data.is_empty());
+    assert_eq!(tx.to, Some(sender));
+    assert_eq!(tx.nonce, U256::from(4));
+    assert_eq!(tx.authorization_list.len(), 1);
+}
+
+#[test]
+fn empty_authorization_list_is_rejected_before_signing() {
+    let build = |authorizations| {
+        build_delegation_only_tx(
+            Address::repeat_byte(0x0a),
+            authorizations,
+            1337,
+            U256::ZERO,
+            U256::from(2),
+            U256::from(1),
+            U256::from(46000),
+        )
+    };
+    assert!(matches!(
+        build(vec![]),
+        Err(TxError::EmptyAuthorizationList)
+    ));
+
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let authorization =
+        Authorization7702::sign(0, Address::repeat_byte(0xcc), U256::ZERO, &key).unwrap();
+    let mut tx = build(vec![authorization]).unwrap();
+    finalize_signature(&mut tx, &key).unwrap();
+
+    // emptied after building, signing still refuses it
+    tx.authorization_list.clear();
+    assert!(matches!(
+        finalize_signature(&mut tx, &key),
+        Err(TxError::EmptyAuthorizationList)
+    ));
+}
+
+#[test]
+fn authorizations_roundtrip_through_json() {
+    let key = SecretKey::from_slice(&[0x11; 32]).unwrap();
+    let authorizations = vec![
+        Authorization7702::sign(1337, Address::repeat_byte(0xcc), U256::from(7), &key).unwrap(),
+        Authorization7702::sign(0, Address::repeat_byte(0xdd), U256::ZERO, &key).unwrap(),
+    ];
+
+    let json = authorizations_to_json(&authorizations);
+    assert!(json.contains(r#""chainId": "0x539""#), "{json}");
+    assert!(json.contains(r#""yParity": "0x"#), "{json}");
+
+    let parsed = authorizations_from_json(&json).unwrap();
+    assert_eq!(
+        parsed.iter().map(alloy_rlp::encode).collect::<Vec<_>>(),
+        authorizations
+            .iter()
+            .map(alloy_rlp::encode)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn authorization_list_from_a_block_explorer_parses() {
+    // as an explorer's JSON view shows it, with a `v` next to `yParity`
+    let json = r#"[
+        {
+            "chainId": "0x539",
+            "address": "0x4f747741ef10551969f9688a8264fc6bb337fa5f",
+            "nonce": "0x7",
+            "yParity": "0x0",
+            "v": "0x0",
+            "r": "0x231e38a87189f87a8fbd426fca812eeb28c049ae1905faa6abac3ab4bc72d52f",
+            "s": "0x590530c8228c2391cda0c9c136517ef2d0664d34ed738e0e52160baf85171144"
+        }
+    ]"#;
+
+    let [auth] = &authorizations_from_json(json).unwrap()[..] else {
+        panic!("expected one authorization");
+    };
+    assert_eq!((auth.chain_id, auth.nonce), (1337, U256::from(7)));
+    assert_eq!(
+        auth.recover_authority().unwrap(),
+        address!("19E7E376E7C213B7E7e7e46cc70A5dD086DAff2A")
+    );
+    assert!(matches!(
+        authorizations_from_json(r#"[{"chainId": "0x1"}]"#),
+        Err(TxError::Config(_))
+    ));
+}
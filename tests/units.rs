@@ -0,0 +1,30 @@
+use alloy::primitives::U256;
+use example_7702::units::{format_ether, format_gwei};
+
+const GWEI: u64 = 1_000_000_000;
+const ETHER: u64 = 1_000_000_000_000_000_000;
+
+#[test]
+fn one_wei() {
+    assert_eq!(format_ether(U256::from(1)), "0.000000000000000001");
+    assert_eq!(format_gwei(U256::from(1)), "0.000000001");
+}
+
+#[test]
+fn one_gwei() {
+    assert_eq!(format_ether(U256::from(GWEI)), "0.000000001");
+    assert_eq!(format_gwei(U256::from(GWEI)), "1");
+}
+
+#[test]
+fn one_ether() {
+    assert_eq!(format_ether(U256::from(ETHER)), "1");
+    assert_eq!(format_gwei(U256::from(ETHER)), "1000000000");
+}
+
+#[test]
+fn trailing_zeros_are_trimmed() {
+    assert_eq!(format_ether(U256::from(1_234 * (ETHER / 1_000))), "1.234");
+    assert_eq!(format_ether(U256::from(3 * (ETHER / 2))), "1.5");
+    assert_eq!(format_ether(U256::ZERO), "0");
+}